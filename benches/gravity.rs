@@ -0,0 +1,68 @@
+//! Throughput of point-mass gravity for a 1000-satellite constellation, one position at a
+//! time against the batched kernel. Run with `cargo bench --bench gravity`.
+
+use kosmoss::physics::gravity::{gravity_acceleration, gravity_acceleration_batch};
+use nalgebra as na;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SATELLITES: usize = 1000;
+const ROUNDS: usize = 10_000;
+
+/// Walker-like shell: satellites spread over 20 planes between 520 and 1510 km altitude
+fn constellation() -> Vec<na::Vector3<f64>> {
+    (0..SATELLITES)
+        .map(|i| {
+            let radius = 6.9e6 + 1.0e4 * (i % 100) as f64;
+            let raan = (i % 20) as f64 * std::f64::consts::TAU / 20.0;
+            let anomaly = (i / 20) as f64 * std::f64::consts::TAU / 50.0;
+            let inclination = 53.0_f64.to_radians();
+            na::Vector3::new(
+                radius
+                    * (raan.cos() * anomaly.cos() - raan.sin() * anomaly.sin() * inclination.cos()),
+                radius
+                    * (raan.sin() * anomaly.cos() + raan.cos() * anomaly.sin() * inclination.cos()),
+                radius * anomaly.sin() * inclination.sin(),
+            )
+        })
+        .collect()
+}
+
+/// Best of `ROUNDS` timings of `f`, to keep scheduler noise out of the comparison
+fn best_time(mut f: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let positions = constellation();
+
+    let single = best_time(|| {
+        let accelerations: Vec<_> = black_box(&positions)
+            .iter()
+            .map(gravity_acceleration)
+            .collect();
+        black_box(accelerations);
+    });
+    let batch = best_time(|| {
+        black_box(gravity_acceleration_batch(black_box(&positions)));
+    });
+
+    for (name, time) in [("per position", single), ("batched", batch)] {
+        println!(
+            "{name:>12}: {:>8.1} µs per {SATELLITES} satellites, {:>6.1} M evaluations/s",
+            time.as_secs_f64() * 1e6,
+            SATELLITES as f64 / time.as_secs_f64() / 1e6
+        );
+    }
+    println!(
+        "     speedup: {:.2}x",
+        single.as_secs_f64() / batch.as_secs_f64()
+    );
+}
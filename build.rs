@@ -9,17 +9,59 @@ use std::time::{Duration, SystemTime};
 const CELESTRAK_URL: &str = "https://celestrak.org/SpaceData/EOP-All.csv";
 const CACHE_FILE: &str = "eop_cache.csv";
 const CACHE_EXPIRATION_HOURS: u64 = 6; // CelesTrak updates every 6 hours
+const CACHE_ENV_VAR: &str = "KOSMOSS_EOP_CACHE";
+const FETCHED_AT_ENV_VAR: &str = "KOSMOSS_EOP_FETCHED_AT";
 
 fn main() {
     // Get Cargo's OUT_DIR (temporary build directory)
     let out_dir = env::var("OUT_DIR").expect("Cargo should set OUT_DIR");
     let cache_path = PathBuf::from(out_dir).join(CACHE_FILE);
 
+    // Only the std build reads EOP data
+    if env::var_os("CARGO_FEATURE_STD").is_none() {
+        return;
+    }
+
+    // Any rerun-if directive replaces Cargo's default of rerunning on every package change,
+    // so name everything the embedded data depends on
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed={}", CACHE_ENV_VAR);
+
+    // Embed an existing shared cache file instead of downloading, for offline and
+    // reproducible builds
+    if let Some(shared_cache) = env::var_os(CACHE_ENV_VAR).map(PathBuf::from) {
+        if shared_cache.is_file() {
+            println!("cargo:rerun-if-changed={}", shared_cache.display());
+            fs::copy(&shared_cache, &cache_path).expect("Failed to copy shared EOP cache");
+            emit_fetched_at(&shared_cache);
+            return;
+        }
+    }
+
+    // Downloaded data is refreshed, once stale, whenever the sources change, as before
+    println!("cargo:rerun-if-changed=src");
+
     // Download and store the EOP data
     match fetch_eop_data(&cache_path) {
         Ok(_) => println!("EOP data fetched successfully!"),
         Err(e) => panic!("Failed to fetch EOP data: {}", e),
     }
+    emit_fetched_at(&cache_path);
+}
+
+/// Tells the crate when the embedded data was fetched, as the modification time of `path`
+/// in seconds since the Unix epoch, so that its age can be reported at run time
+fn emit_fetched_at(path: &PathBuf) {
+    let fetched_at = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or_else(|_| SystemTime::now())
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO);
+    println!(
+        "cargo:rustc-env={}={}",
+        FETCHED_AT_ENV_VAR,
+        fetched_at.as_secs()
+    );
 }
 
 fn fetch_eop_data(cache_path: &PathBuf) -> Result<(), Box<dyn Error>> {
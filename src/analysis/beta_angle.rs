@@ -0,0 +1,68 @@
+use nalgebra as na;
+
+/// Unit normal of the orbit plane, along the angular momentum r × v
+pub fn orbit_normal(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> na::Vector3<f64> {
+    r.cross(v).normalize()
+}
+
+/// Beta angle [rad]: the elevation of the Sun above the orbit plane, positive when the Sun
+/// is on the side the orbit normal points to. `sun_position` is the geocentric Sun vector,
+/// e.g. from `sun_position`; only its direction matters.
+pub fn beta_angle(
+    r: &na::Vector3<f64>,
+    v: &na::Vector3<f64>,
+    sun_position: &na::Vector3<f64>,
+) -> f64 {
+    orbit_normal(r, v)
+        .dot(&sun_position.normalize())
+        .clamp(-1.0, 1.0)
+        .asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{AU, PI};
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_beta_angle_in_plane_and_normal() {
+        // Inclined circular orbit, so the plane lies along none of the GCRS axes
+        let (r, v) = OrbitalMechanics::circular_orbit_state(500_000.0, 0.9, 0.4, 1.3);
+        let normal = orbit_normal(&r, &v);
+        assert_relative_eq!(normal.dot(&r), 0.0, epsilon = 1e-6);
+        assert_relative_eq!(normal.dot(&v), 0.0, epsilon = 1e-9);
+
+        // Sun in the orbit plane, on either side of the Earth
+        let in_plane = (r.normalize() * 0.6 + v.normalize() * 0.8) * AU;
+        assert_relative_eq!(beta_angle(&r, &v, &in_plane), 0.0, epsilon = 1e-12);
+        assert_relative_eq!(beta_angle(&r, &v, &-in_plane), 0.0, epsilon = 1e-12);
+
+        // Sun along the orbit normal and opposite it
+        assert_relative_eq!(beta_angle(&r, &v, &(normal * AU)), PI / 2.0, epsilon = 1e-6);
+        assert_relative_eq!(
+            beta_angle(&r, &v, &(-normal * AU)),
+            -PI / 2.0,
+            epsilon = 1e-6
+        );
+
+        // Halfway between, at 30° above the plane
+        let tilted = (r.normalize() * 30.0_f64.to_radians().cos()
+            + normal * 30.0_f64.to_radians().sin())
+            * AU;
+        assert_relative_eq!(
+            beta_angle(&r, &v, &tilted),
+            30.0_f64.to_radians(),
+            epsilon = 1e-12
+        );
+
+        // The angle does not depend on where along the orbit it is evaluated
+        let (r2, v2) = OrbitalMechanics::circular_orbit_state(500_000.0, 0.9, 0.4, 2.9);
+        assert_relative_eq!(
+            beta_angle(&r2, &v2, &tilted),
+            beta_angle(&r, &v, &tilted),
+            epsilon = 1e-12
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::coordinates::coordinate_transformation::{gcrs_to_itrs, itrs_to_geodetic};
+use crate::coordinates::eop_provider::EOPProvider;
+use crate::errors::KosmossError;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Sub-satellite points (longitude, latitude) in degrees, one per state
+pub fn ground_track<T: SpacecraftProperties>(
+    states: &[State<T>],
+    eop_provider: &dyn EOPProvider,
+) -> Result<Vec<(f64, f64)>, KosmossError> {
+    states
+        .iter()
+        .map(|state| {
+            let eop = eop_provider.eop_data(state.epoch)?;
+            let (longitude, latitude, _) =
+                itrs_to_geodetic(&gcrs_to_itrs(&state.position, &state.epoch, &eop));
+            Ok((longitude, latitude))
+        })
+        .collect()
+}
+
+/// Splits a ground track wherever it crosses the ±180° meridian, so that no segment draws a
+/// line across the whole map. Both sides of a crossing get a point on the meridian itself,
+/// at the latitude interpolated between the neighbouring samples.
+pub fn split_at_antimeridian(points: &[(f64, f64)]) -> Vec<Vec<(f64, f64)>> {
+    let mut segments = Vec::new();
+    let mut segment: Vec<(f64, f64)> = Vec::new();
+
+    for &(longitude, latitude) in points {
+        if let Some(&(previous_longitude, previous_latitude)) = segment.last() {
+            let step = longitude - previous_longitude;
+            if step.abs() > 180.0 {
+                // Going the short way round, the track leaves through the meridian on the
+                // side of the previous point
+                let edge = 180.0_f64.copysign(previous_longitude);
+                let unwrapped = longitude + 360.0_f64.copysign(previous_longitude);
+                let fraction = (edge - previous_longitude) / (unwrapped - previous_longitude);
+                let crossing_latitude =
+                    previous_latitude + (latitude - previous_latitude) * fraction;
+
+                segment.push((edge, crossing_latitude));
+                segments.push(std::mem::take(&mut segment));
+                segment.push((-edge, crossing_latitude));
+            }
+        }
+        segment.push((longitude, latitude));
+    }
+
+    if !segment.is_empty() {
+        segments.push(segment);
+    }
+    segments
+}
+
+/// Writes the ground track of `states` as a GeoJSON FeatureCollection, with one LineString
+/// feature per segment between antimeridian crossings
+pub fn write_ground_track_geojson<T: SpacecraftProperties>(
+    states: &[State<T>],
+    path: &Path,
+    eop_provider: &dyn EOPProvider,
+) -> Result<(), KosmossError> {
+    let segments = split_at_antimeridian(&ground_track(states, eop_provider)?);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, ground_track_geojson(&segments))?;
+    Ok(())
+}
+
+fn ground_track_geojson(segments: &[Vec<(f64, f64)>]) -> String {
+    let features: Vec<String> = segments
+        .iter()
+        .map(|segment| {
+            let mut coordinates = String::new();
+            for (i, (longitude, latitude)) in segment.iter().enumerate() {
+                if i > 0 {
+                    coordinates.push(',');
+                }
+                // GeoJSON positions are [longitude, latitude]; 1e-6° is about 0.1 m
+                let _ = write!(coordinates, "[{:.6},{:.6}]", longitude, latitude);
+            }
+            format!(
+                "{{\"type\":\"Feature\",\"properties\":{{}},\
+                 \"geometry\":{{\"type\":\"LineString\",\"coordinates\":[{}]}}}}",
+                coordinates
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\"type\":\"FeatureCollection\",\"features\":[{}]}}\n",
+        features.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::WGS84_A;
+    use crate::coordinates::coordinate_transformation::{gcrs_to_itrs_rotation, EOPData};
+    use crate::coordinates::eop_provider::StaticEOP;
+    use crate::numerics::quaternion::Quaternion;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+    use nalgebra as na;
+    use serde_json::Value;
+
+    #[test]
+    fn test_dateline_crossing_track_is_split() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let eop = StaticEOP::new(EOPData::default());
+
+        // Rotation from inertial to Earth-fixed at this epoch; its transpose places the ITRS
+        // samples in GCRS
+        let to_itrs = gcrs_to_itrs_rotation(&epoch, &eop.eop).matrix();
+
+        // Northbound over the equator from 170°E to 170°W, crossing the dateline at 0.25°N
+        let radius = WGS84_A + 500_000.0;
+        let states: Vec<State<SimpleSat>> = (0..6)
+            .map(|k| {
+                let longitude = (170.0 + 4.0 * k as f64).to_radians();
+                let latitude = (-2.25 + k as f64).to_radians();
+                let itrs = na::Vector3::new(
+                    latitude.cos() * longitude.cos(),
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                ) * radius;
+                State::new(
+                    &spacecraft,
+                    SimpleSat::inertia_tensor(),
+                    to_itrs.transpose() * itrs,
+                    na::Vector3::zeros(),
+                    Quaternion::new(1.0, 0.0, 0.0, 0.0),
+                    na::Vector3::zeros(),
+                    epoch,
+                )
+            })
+            .collect();
+
+        let path = std::env::temp_dir().join(format!(
+            "kosmoss_ground_track_{}.geojson",
+            std::process::id()
+        ));
+        write_ground_track_geojson(&states, &path, &eop).unwrap();
+        let geojson: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        for feature in features {
+            assert_eq!(feature["type"], "Feature");
+            assert_eq!(feature["geometry"]["type"], "LineString");
+        }
+
+        let coordinates = |feature: &Value| -> Vec<(f64, f64)> {
+            feature["geometry"]["coordinates"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|position| (position[0].as_f64().unwrap(), position[1].as_f64().unwrap()))
+                .collect()
+        };
+        let west = coordinates(&features[0]);
+        let east = coordinates(&features[1]);
+
+        // 170°E to 178°E, then the dateline; the dateline again, then 178°W to 170°W
+        assert_eq!(west.len(), 4);
+        assert_eq!(east.len(), 4);
+        assert_relative_eq!(west[0].0, 170.0, epsilon = 1e-5);
+        assert_relative_eq!(west[3].0, 180.0);
+        assert_relative_eq!(east[0].0, -180.0);
+        assert_relative_eq!(east[3].0, -170.0, epsilon = 1e-5);
+
+        // Geodetic latitudes run slightly above the geocentric ones used to place the samples,
+        // but the track still crosses the equator before the dateline
+        assert_relative_eq!(west[3].1, east[0].1);
+        assert!(west[0].1 < 0.0 && west[3].1 > 0.0);
+        assert_relative_eq!(west[3].1, 0.25, epsilon = 0.01);
+    }
+
+    #[test]
+    fn test_eastbound_and_westbound_crossings() {
+        assert_eq!(split_at_antimeridian(&[]).len(), 0);
+        assert_eq!(split_at_antimeridian(&[(10.0, 0.0), (20.0, 1.0)]).len(), 1);
+
+        let westbound = split_at_antimeridian(&[(-178.0, 10.0), (178.0, 20.0)]);
+        assert_eq!(
+            westbound,
+            vec![
+                vec![(-178.0, 10.0), (-180.0, 15.0)],
+                vec![(180.0, 15.0), (178.0, 20.0)]
+            ]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod beta_angle;
+pub mod ground_track;
+pub mod orbit_average;
+pub mod visibility;
@@ -0,0 +1,104 @@
+use crate::constants::MU_EARTH;
+use crate::integrators::Integrator;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::physics::orbital::OrbitalMechanics;
+use crate::physics::orbital_errors::OrbitalErrors;
+
+/// Integration steps per revolution used by `orbit_average`
+const STEPS_PER_ORBIT: usize = 1000;
+
+/// Time average of `f` over one revolution, starting at `initial_state` and propagated by
+/// `integrator`. The period comes from the osculating semi-major axis at the start, so the
+/// orbit must be bound.
+pub fn orbit_average<'a, T, I, F>(
+    initial_state: &State<'a, T>,
+    integrator: &I,
+    f: F,
+) -> Result<f64, OrbitalErrors>
+where
+    T: SpacecraftProperties,
+    I: Integrator<State = State<'a, T>>,
+    F: Fn(&State<'a, T>) -> f64,
+{
+    orbit_average_with_steps(initial_state, integrator, f, STEPS_PER_ORBIT)
+}
+
+/// As `orbit_average`, taking `steps` equal steps over the period. The samples are combined
+/// by the trapezoidal rule, which converges quickly for quantities periodic in the orbit.
+pub fn orbit_average_with_steps<'a, T, I, F>(
+    initial_state: &State<'a, T>,
+    integrator: &I,
+    f: F,
+    steps: usize,
+) -> Result<f64, OrbitalErrors>
+where
+    T: SpacecraftProperties,
+    I: Integrator<State = State<'a, T>>,
+    F: Fn(&State<'a, T>) -> f64,
+{
+    let (r, v) = (&initial_state.position, &initial_state.velocity);
+    if !OrbitalMechanics::is_bound(r, v) {
+        return Err(OrbitalErrors::UnboundOrbit {
+            specific_energy: v.magnitude_squared() / 2.0 - MU_EARTH / r.magnitude(),
+        });
+    }
+    let period =
+        OrbitalMechanics::compute_orbital_period(OrbitalMechanics::cartesian_to_keplerian(r, v)[0]);
+    let steps = steps.max(1);
+    let dt = period / steps as f64;
+
+    let mut state = initial_state.clone();
+    let mut sum = f(&state) / 2.0;
+    for step in 1..=steps {
+        state = integrator.integrate(&state, dt);
+        let weight = if step == steps { 0.5 } else { 1.0 };
+        sum += weight * f(&state);
+    }
+    Ok(sum / steps as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::integrators::rk4::RK4;
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::dynamics::SpacecraftDynamics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+    use nalgebra as na;
+
+    #[test]
+    fn test_orbit_averaged_radius() {
+        let (a, e) = (12_000_000.0, 0.3);
+        let elements = na::Vector6::new(a, e, 0.5, 1.0, 2.0, 0.7);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+
+        let spacecraft = SimpleSat::default();
+        let initial = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let integrator = RK4::new(SpacecraftDynamics::<SimpleSat>::new(None, None));
+
+        // The time-averaged radius of a Keplerian orbit is a(1 + e²/2), whatever the start
+        let mean_radius =
+            orbit_average(&initial, &integrator, |state| state.position.magnitude()).unwrap();
+        assert_relative_eq!(mean_radius, a * (1.0 + e * e / 2.0), max_relative = 1e-8);
+
+        let escaping = State {
+            velocity: velocity * 2.0,
+            ..initial.clone()
+        };
+        assert!(matches!(
+            orbit_average(&escaping, &integrator, |state| state.position.magnitude()),
+            Err(OrbitalErrors::UnboundOrbit { .. })
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::constants::WGS84_A;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use nalgebra as na;
+
+/// Straight-line distance between two spacecraft [m]
+pub fn inter_satellite_range<T: SpacecraftProperties, U: SpacecraftProperties>(
+    a: &State<T>,
+    b: &State<U>,
+) -> f64 {
+    (b.position - a.position).magnitude()
+}
+
+/// Returns true if the line joining the two spacecraft clears a spherical Earth of radius
+/// `WGS84_A`, i.e. the closest point of the chord to the Earth's center lies outside it
+pub fn has_line_of_sight<T: SpacecraftProperties, U: SpacecraftProperties>(
+    a: &State<T>,
+    b: &State<U>,
+) -> bool {
+    chord_clears_earth(&a.position, &b.position)
+}
+
+fn chord_clears_earth(r_a: &na::Vector3<f64>, r_b: &na::Vector3<f64>) -> bool {
+    let chord = r_b - r_a;
+    let chord_length_sq = chord.dot(&chord);
+    if chord_length_sq < 1e-11 {
+        return r_a.magnitude() > WGS84_A;
+    }
+
+    // Parameter of the point on the segment closest to the Earth's center
+    let t = (-r_a.dot(&chord) / chord_length_sq).clamp(0.0, 1.0);
+    let closest = r_a + chord * t;
+
+    closest.magnitude() > WGS84_A
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::numerics::quaternion::Quaternion;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    fn state_at(spacecraft: &SimpleSat, position: na::Vector3<f64>) -> State<'_, SimpleSat> {
+        State::new(
+            spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            na::Vector3::zeros(),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        )
+    }
+
+    #[test]
+    fn test_opposite_sides_of_earth_have_no_line_of_sight() {
+        let spacecraft = SimpleSat::default();
+        let radius = WGS84_A + 500_000.0;
+        let a = state_at(&spacecraft, na::Vector3::new(radius, 0.0, 0.0));
+        let b = state_at(&spacecraft, na::Vector3::new(-radius, 0.0, 0.0));
+
+        assert!(!has_line_of_sight(&a, &b));
+        assert_relative_eq!(inter_satellite_range(&a, &b), 2.0 * radius, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_nearby_satellites_have_line_of_sight() {
+        let spacecraft = SimpleSat::default();
+        let radius = WGS84_A + 500_000.0;
+        let a = state_at(&spacecraft, na::Vector3::new(radius, 0.0, 0.0));
+        let b = state_at(&spacecraft, na::Vector3::new(radius, 30_000.0, 40_000.0));
+
+        assert!(has_line_of_sight(&a, &b));
+        assert_relative_eq!(inter_satellite_range(&a, &b), 50_000.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_line_of_sight_grazing_limit() {
+        let spacecraft = SimpleSat::default();
+        let radius = WGS84_A + 500_000.0;
+
+        // Two satellites at the same altitude see each other while the chord's midpoint,
+        // at radius * cos(half_angle), stays above the surface
+        let limit = (WGS84_A / radius).acos();
+        for (half_angle, visible) in [(limit - 1e-3, true), (limit + 1e-3, false)] {
+            let a = state_at(
+                &spacecraft,
+                na::Vector3::new(radius * half_angle.cos(), radius * half_angle.sin(), 0.0),
+            );
+            let b = state_at(
+                &spacecraft,
+                na::Vector3::new(radius * half_angle.cos(), -radius * half_angle.sin(), 0.0),
+            );
+            assert_eq!(has_line_of_sight(&a, &b), visible);
+        }
+    }
+}
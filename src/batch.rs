@@ -0,0 +1,176 @@
+use crate::errors::KosmossError;
+use crate::gnc::control::attitude_controller::GeometricAttitudeController;
+use crate::gnc::guidance::hohmann::{ApsisTargeting, ApsisType};
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::numerics::quaternion::Quaternion;
+use crate::physics::orbital::OrbitalMechanics;
+use crate::sim::Simulation;
+use hifitime::Epoch;
+use nalgebra as na;
+
+/// Initial conditions and settings for one trajectory of a batch run
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    pub elements: na::Vector6<f64>, // [a, e, i, Omega, omega, nu]
+    pub inertia: na::Matrix3<f64>,
+    pub angular_velocity: na::Vector3<f64>,
+    pub start_epoch: Epoch,
+    pub duration: f64, // seconds
+    pub dt: f64,       // seconds
+    pub kp: f64,
+    pub kd: f64,
+    pub target_apogee_radius: f64, // meters
+    pub maneuver_time: Option<f64>,
+}
+
+pub type BatchResult<'a, T> = Result<State<'a, T>, KosmossError>;
+
+/// Builds the closed-loop simulation for `config`: the state at its elements, the
+/// controller tuned for `spacecraft`, apogee targeting and the maneuver, if any
+pub fn build_simulation<'a, T: SpacecraftProperties>(
+    spacecraft: &'a T,
+    config: &BatchConfig,
+) -> Simulation<'a, T> {
+    let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&config.elements);
+    let initial_state = State::new(
+        spacecraft,
+        config.inertia,
+        position,
+        velocity,
+        Quaternion::new(1.0, 0.0, 0.0, 0.0),
+        config.angular_velocity,
+        config.start_epoch,
+    );
+
+    let attitude_controller = GeometricAttitudeController::for_spacecraft(
+        config.kp,
+        config.kd,
+        config.inertia,
+        spacecraft,
+    );
+    let guidance = ApsisTargeting::new(config.target_apogee_radius, ApsisType::Apogee, 0.0);
+
+    let mut simulation = Simulation::new(initial_state, attitude_controller, guidance, config.dt);
+    if let Some(maneuver_time) = config.maneuver_time {
+        simulation.schedule_maneuver(maneuver_time);
+    }
+    simulation
+}
+
+/// Runs a single trajectory to completion and returns its final state.
+/// Batch runs do not log telemetry, so each run only touches its own state.
+pub fn run_single<'a, T: SpacecraftProperties>(
+    spacecraft: &'a T,
+    config: &BatchConfig,
+) -> BatchResult<'a, T> {
+    let mut simulation = build_simulation(spacecraft, config);
+    simulation.run_until(config.duration)?;
+    Ok(simulation.state().clone())
+}
+
+/// Runs every trajectory in sequence, returning results in the order of `configs`
+pub fn run_batch<'a, T: SpacecraftProperties>(
+    spacecraft: &'a T,
+    configs: &[BatchConfig],
+) -> Vec<BatchResult<'a, T>> {
+    configs
+        .iter()
+        .map(|config| run_single(spacecraft, config))
+        .collect()
+}
+
+/// Runs the trajectories across the rayon thread pool, returning results in the order of `configs`
+#[cfg(feature = "rayon")]
+pub fn run_batch_parallel<'a, T: SpacecraftProperties + Sync>(
+    spacecraft: &'a T,
+    configs: &[BatchConfig],
+) -> Vec<BatchResult<'a, T>> {
+    use rayon::prelude::*;
+
+    configs
+        .par_iter()
+        .map(|config| run_single(spacecraft, config))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::WGS84_A;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn seeded_configs(seed: u64, count: usize) -> Vec<BatchConfig> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| BatchConfig {
+                elements: na::Vector6::new(
+                    WGS84_A + rng.gen_range(400_000.0..800_000.0),
+                    rng.gen_range(0.0..0.05),
+                    rng.gen_range(0.0..1.5),
+                    rng.gen_range(0.0..6.0),
+                    rng.gen_range(0.0..6.0),
+                    rng.gen_range(0.0..6.0),
+                ),
+                inertia: SimpleSat::inertia_tensor(),
+                angular_velocity: na::Vector3::new(
+                    rng.gen_range(-0.05..0.05),
+                    rng.gen_range(-0.05..0.05),
+                    rng.gen_range(-0.05..0.05),
+                ),
+                start_epoch: Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+                duration: 30.0,
+                dt: 0.5,
+                kp: 1.0,
+                kd: 0.1,
+                target_apogee_radius: WGS84_A + 1_000_000.0,
+                maneuver_time: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_run_batch_matches_individual_runs() {
+        let spacecraft = SimpleSat::default();
+        let configs = seeded_configs(7, 4);
+
+        let results = run_batch(&spacecraft, &configs);
+
+        assert_eq!(results.len(), configs.len());
+        for (config, result) in configs.iter().zip(results) {
+            let batch_state = result.unwrap();
+            let single_state = run_single(&spacecraft, config).unwrap();
+            assert_eq!(batch_state.position, single_state.position);
+            assert_eq!(batch_state.velocity, single_state.velocity);
+            assert_eq!(batch_state.mission_elapsed_time, config.duration);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_batch_matches_serial() {
+        let spacecraft = SimpleSat::default();
+
+        for seed in [1, 2, 3] {
+            let configs = seeded_configs(seed, 8);
+
+            let serial = run_batch(&spacecraft, &configs);
+            let parallel = run_batch_parallel(&spacecraft, &configs);
+
+            assert_eq!(serial.len(), parallel.len());
+            for (serial_state, parallel_state) in serial.into_iter().zip(parallel) {
+                let serial_state = serial_state.unwrap();
+                let parallel_state = parallel_state.unwrap();
+                assert_eq!(serial_state.position, parallel_state.position);
+                assert_eq!(serial_state.velocity, parallel_state.velocity);
+                assert_eq!(serial_state.quaternion.data, parallel_state.quaternion.data);
+                assert_eq!(
+                    serial_state.angular_velocity,
+                    parallel_state.angular_velocity
+                );
+            }
+        }
+    }
+}
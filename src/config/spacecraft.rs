@@ -1,28 +1,97 @@
 use crate::models::spacecraft::SpacecraftProperties;
 use nalgebra as na;
 
-pub struct SimpleSat;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimpleSat {
+    pub c_d: f64,
+    pub radius: f64, // meters
+    pub mass: f64,   // kg
+}
 
 impl SimpleSat {
     pub const MASS: f64 = 100.0; // kg
     pub const C_D: f64 = 2.2;
     pub const R_SPACECRAFT: f64 = 1.0; // meters
 
+    pub const fn new(c_d: f64, radius: f64, mass: f64) -> Self {
+        Self { c_d, radius, mass }
+    }
+
     pub fn inertia_tensor() -> na::Matrix3<f64> {
         na::Matrix3::new(10.0, 0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0, 10.0)
     }
 }
 
+impl Default for SimpleSat {
+    fn default() -> Self {
+        Self::new(Self::C_D, Self::R_SPACECRAFT, Self::MASS)
+    }
+}
+
 impl SpacecraftProperties for SimpleSat {
     fn mass(&self) -> f64 {
-        Self::MASS
+        self.mass
     }
 
     fn drag_coefficient(&self) -> f64 {
-        Self::C_D
+        self.c_d
     }
 
     fn reference_area(&self) -> f64 {
-        std::f64::consts::PI * Self::R_SPACECRAFT.powi(2)
+        std::f64::consts::PI * self.radius.powi(2)
+    }
+}
+
+/// Rectangular box spacecraft with uniform density, whose drag area depends on attitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxSat {
+    pub c_d: f64,
+    pub dimensions: na::Vector3<f64>, // meters, edge lengths along the body axes
+    pub mass: f64,                    // kg
+}
+
+impl BoxSat {
+    pub const fn new(c_d: f64, dimensions: na::Vector3<f64>, mass: f64) -> Self {
+        Self {
+            c_d,
+            dimensions,
+            mass,
+        }
+    }
+
+    /// Areas of the faces normal to the body x, y and z axes
+    pub fn face_areas(&self) -> na::Vector3<f64> {
+        let d = self.dimensions;
+        na::Vector3::new(d.y * d.z, d.x * d.z, d.x * d.y)
+    }
+
+    pub fn inertia_tensor(&self) -> na::Matrix3<f64> {
+        let d2 = self.dimensions.component_mul(&self.dimensions);
+        na::Matrix3::from_diagonal(&na::Vector3::new(d2.y + d2.z, d2.x + d2.z, d2.x + d2.y))
+            * (self.mass / 12.0)
+    }
+}
+
+impl SpacecraftProperties for BoxSat {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    fn drag_coefficient(&self) -> f64 {
+        self.c_d
+    }
+
+    /// Largest face, seen face-on
+    fn reference_area(&self) -> f64 {
+        self.face_areas().max()
+    }
+
+    fn projected_area(&self, direction: &na::Vector3<f64>) -> f64 {
+        self.face_areas().dot(&direction.normalize().abs())
+    }
+
+    /// By Cauchy's formula a convex body's mean projected area is a quarter of its surface
+    fn mean_projected_area(&self) -> f64 {
+        self.face_areas().sum() / 2.0
     }
 }
@@ -1,18 +1,23 @@
 pub const G: f64 = 6.67430e-11; // Gravitational constant (m³/kg/s²)
 pub const M_EARTH: f64 = 5.972e24; // Mass of Earth (kg)
+pub const MU_EARTH: f64 = 3.986004418e14; // Earth's gravitational parameter GM (m³/s²)
+pub const G0: f64 = 9.80665; // Standard gravity (m/s²)
 #[allow(dead_code)]
 pub const R_EARTH: f64 = 6.371e6; // Radius of Earth (m)
 #[allow(dead_code)]
 pub const ORBIT_ALTITUDE: f64 = 400_000.0; // Orbital altitude for LEO (m)
 
 // Environmental constants
-pub const M_0: f64 = 4.0 * std::f64::consts::PI * 1e-7; // Vacuum permeability
-                                                        // pub const SOLAR_CONSTANT: f64 = 1361.0; // Solar constant at 1 AU (W/m^2)
-                                                        // pub const EARTH_J2: f64 = 1.08263e-3; // Earth's J2 perturbation coefficient
+pub const M_0: f64 = 4.0 * core::f64::consts::PI * 1e-7; // Vacuum permeability
+                                                         // pub const SOLAR_CONSTANT: f64 = 1361.0; // Solar constant at 1 AU (W/m^2)
+pub const EARTH_J2: f64 = 1.08263e-3; // Earth's J2 perturbation coefficient
+pub const TUMBLING_RATE_THRESHOLD: f64 = 0.01; // Body rate above which a body is tumbling (rad/s)
 #[allow(dead_code)]
 pub const EARTH_ANGULAR_VELOCITY: f64 = 7.2921150e-5; // Earth's rotation rate (rad/s)
 pub const WGS84_A: f64 = 6378137.0; // Semi-major axis [m]
 pub const WGS84_F: f64 = 1.0 / 298.257223563; // Flattening
+pub const AU: f64 = 1.495978707e11; // Astronomical unit (m)
+pub const EARTH_MOON_MASS_RATIO: f64 = 0.012150585; // Moon mass / (Earth + Moon mass)
 
 // // Spacecraft properties
 // pub const C_D: f64 = 2.2;
@@ -20,4 +25,4 @@ pub const WGS84_F: f64 = 1.0 / 298.257223563; // Flattening
 // pub const REFLECTIVITY_COEFFICIENT: f64 = 0.3;
 
 // Math
-pub const PI: f64 = std::f64::consts::PI;
+pub const PI: f64 = core::f64::consts::PI;
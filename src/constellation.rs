@@ -0,0 +1,340 @@
+use crate::coordinates::eop_provider::EOPProvider;
+use crate::errors::KosmossError;
+use crate::integrators::rk4::RK4;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::{State, StateDerivative};
+use crate::physics::dynamics::{EquationsOfMotion, SpacecraftDynamics};
+use crate::physics::gravity::gravity_acceleration_batch;
+use crate::sim::Simulation;
+use nalgebra as na;
+use std::rc::Rc;
+
+/// Steps several independent spacecraft simulations together, one tick at a time.
+///
+/// Each member keeps its own state, controllers and telemetry; members only share the
+/// Earth orientation provider, so every ground track is computed from the same EOP series.
+pub struct Constellation<'a, T: SpacecraftProperties> {
+    members: Vec<Simulation<'a, T>>,
+    eop_provider: Option<Rc<dyn EOPProvider>>,
+}
+
+impl<'a, T: SpacecraftProperties> Constellation<'a, T> {
+    pub fn new() -> Self {
+        Self {
+            members: Vec::new(),
+            eop_provider: None,
+        }
+    }
+
+    /// Adds a member and returns its index
+    pub fn add_member(&mut self, mut member: Simulation<'a, T>) -> usize {
+        if let Some(eop_provider) = &self.eop_provider {
+            member.set_eop_provider(Box::new(eop_provider.clone()));
+        }
+        self.members.push(member);
+        self.members.len() - 1
+    }
+
+    /// Shares one Earth orientation provider across all current and future members
+    pub fn set_eop_provider(&mut self, eop_provider: Rc<dyn EOPProvider>) {
+        for member in &mut self.members {
+            member.set_eop_provider(Box::new(eop_provider.clone()));
+        }
+        self.eop_provider = Some(eop_provider);
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn member(&self, index: usize) -> Option<&Simulation<'a, T>> {
+        self.members.get(index)
+    }
+
+    /// Mutable access to a member, e.g. to attach its telemetry or schedule a maneuver
+    pub fn member_mut(&mut self, index: usize) -> Option<&mut Simulation<'a, T>> {
+        self.members.get_mut(index)
+    }
+
+    pub fn states(&self) -> impl Iterator<Item = &State<'a, T>> {
+        self.members.iter().map(|member| member.state())
+    }
+
+    /// Two-body gravitational acceleration on every member at its current position, in
+    /// member order, evaluated in one batch
+    pub fn gravity_accelerations(&self) -> Vec<na::Vector3<f64>> {
+        let positions: Vec<_> = self.states().map(|state| state.position).collect();
+        gravity_acceleration_batch(&positions)
+    }
+
+    /// Advances every member by one of its own time steps. The members are integrated
+    /// together, so that the gravity on all of them is evaluated in one batch per RK4 stage.
+    pub fn step(&mut self) -> Result<(), KosmossError> {
+        let dts: Vec<_> = self
+            .members
+            .iter()
+            .map(|member| Some(member.next_dt()))
+            .collect();
+        self.advance(&dts)
+    }
+
+    /// Steps the members together until each one's mission elapsed time reaches `t_end`
+    /// seconds, or its step hook stops it. Members that are done sit out the remaining steps.
+    pub fn run_until(&mut self, t_end: f64) -> Result<(), KosmossError> {
+        loop {
+            let dts: Vec<_> = self
+                .members
+                .iter()
+                .map(|member| {
+                    let dt = member.next_dt();
+                    let remaining = t_end - member.current_time();
+                    // Shorten the last step so the member lands on t_end, as on its own
+                    (!member.is_stopped() && remaining > 1e-6 * dt).then(|| dt.min(remaining))
+                })
+                .collect();
+            if dts.iter().all(Option::is_none) {
+                return Ok(());
+            }
+            self.advance(&dts)?;
+        }
+    }
+
+    /// Advances each member by its entry of `dts`, leaving those with `None` where they are
+    fn advance(&mut self, dts: &[Option<f64>]) -> Result<(), KosmossError> {
+        let mut stepping: Vec<_> = self
+            .members
+            .iter_mut()
+            .zip(dts)
+            .filter_map(|(member, dt)| dt.map(|dt| (member, dt)))
+            .collect();
+        let mut controls = Vec::with_capacity(stepping.len());
+        for (member, dt) in &mut stepping {
+            controls.push(member.prepare_step(*dt)?);
+        }
+
+        let dynamics = MemberDynamics {
+            members: stepping
+                .iter()
+                .zip(&controls)
+                .map(|((member, dt), &(thrust, torque))| (member.dynamics(thrust, torque), *dt))
+                .collect(),
+        };
+        let states = MemberStates(
+            stepping
+                .iter()
+                .map(|(member, _)| member.state().clone())
+                .collect(),
+        );
+        let MemberStates(next) = RK4::new(dynamics).integrate(&states, 1.0);
+
+        for (((member, dt), next), (thrust, torque)) in stepping.into_iter().zip(next).zip(controls)
+        {
+            member.complete_step(next, dt, &thrust, &torque)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes every member's telemetry
+    pub fn finish(&mut self) -> Result<(), KosmossError> {
+        for member in &mut self.members {
+            member.finish()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: SpacecraftProperties> Default for Constellation<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The states of all members, stepped together
+struct MemberStates<'a, T: SpacecraftProperties>(Vec<State<'a, T>>);
+
+impl<'a, T: SpacecraftProperties> Clone for MemberStates<'a, T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+#[derive(Clone)]
+struct MemberDerivatives(Vec<StateDerivative>);
+
+impl std::ops::Add for MemberDerivatives {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(
+            self.0
+                .into_iter()
+                .zip(other.0)
+                .map(|(a, b)| a + b)
+                .collect(),
+        )
+    }
+}
+
+impl std::ops::Mul<f64> for MemberDerivatives {
+    type Output = Self;
+
+    fn mul(self, scalar: f64) -> Self {
+        Self(self.0.into_iter().map(|d| d * scalar).collect())
+    }
+}
+
+impl<'a, T: SpacecraftProperties> std::ops::Add<MemberDerivatives> for MemberStates<'a, T> {
+    type Output = Self;
+
+    fn add(self, increment: MemberDerivatives) -> Self {
+        Self(
+            self.0
+                .into_iter()
+                .zip(increment.0)
+                .map(|(s, d)| s + d)
+                .collect(),
+        )
+    }
+}
+
+/// Each member's dynamics with its step size. Members may take steps of different sizes,
+/// so time is normalised to the step: each derivative is scaled by its member's `dt` and
+/// the whole constellation is integrated over a unit step.
+struct MemberDynamics<'a, T: SpacecraftProperties> {
+    members: Vec<(SpacecraftDynamics<'a, T>, f64)>,
+}
+
+impl<'a, T: SpacecraftProperties> EquationsOfMotion for MemberDynamics<'a, T> {
+    type State = MemberStates<'a, T>;
+    type Derivative = MemberDerivatives;
+
+    fn compute_derivative(&self, states: &Self::State) -> Self::Derivative {
+        let positions: Vec<_> = states.0.iter().map(|state| state.position).collect();
+        let gravity = gravity_acceleration_batch(&positions);
+        MemberDerivatives(
+            self.members
+                .iter()
+                .zip(&states.0)
+                .zip(gravity)
+                .map(|(((dynamics, dt), state), gravity)| {
+                    dynamics.compute_derivative_with_gravity(state, gravity) * *dt
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::WGS84_A;
+    use crate::gnc::control::attitude_controller::GeometricAttitudeController;
+    use crate::gnc::guidance::hohmann::{ApsisTargeting, ApsisType};
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::gravity::gravity_acceleration;
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    fn circular_member(
+        spacecraft: &SimpleSat,
+        altitude: f64,
+        inclination: f64,
+        dt: f64,
+    ) -> Simulation<'_, SimpleSat> {
+        let elements = na::Vector6::new(WGS84_A + altitude, 0.0, inclination, 0.0, 0.0, 0.0);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let state = State::new(
+            spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.01, 0.0, 0.0),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let controller = GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+        let guidance = ApsisTargeting::new(WGS84_A + altitude, ApsisType::Apogee, 0.0);
+        Simulation::new(state, controller, guidance, dt)
+    }
+
+    #[test]
+    fn test_constellation_members_propagate_independently() {
+        let spacecraft = SimpleSat::default();
+        // Members with different step sizes are stepped together all the same
+        let orbits = [(400_000.0, 0.0, 1.0), (800_000.0, 1.2, 2.0)];
+
+        let mut constellation = Constellation::new();
+        for (altitude, inclination, dt) in orbits {
+            constellation.add_member(circular_member(&spacecraft, altitude, inclination, dt));
+        }
+        assert_eq!(constellation.len(), 2);
+
+        for _ in 0..120 {
+            constellation.step().unwrap();
+        }
+
+        let gravity = constellation.gravity_accelerations();
+        for (index, (altitude, inclination, dt)) in orbits.into_iter().enumerate() {
+            // Each member matches the same spacecraft flown on its own, to rounding
+            let mut alone = circular_member(&spacecraft, altitude, inclination, dt);
+            let expected = alone.run_until(120.0 * dt).unwrap();
+            let state = constellation.member(index).unwrap().state();
+            assert_relative_eq!(state.position, expected.position, max_relative = 1e-12);
+            assert_relative_eq!(state.velocity, expected.velocity, max_relative = 1e-12);
+            assert_eq!(state.mission_elapsed_time, expected.mission_elapsed_time);
+
+            // And stays on its own circular orbit
+            assert_relative_eq!(
+                state.position.magnitude(),
+                WGS84_A + altitude,
+                max_relative = 1e-6
+            );
+            let elements =
+                OrbitalMechanics::cartesian_to_keplerian(&state.position, &state.velocity);
+            assert_relative_eq!(elements[2], inclination, epsilon = 1e-9);
+            assert_relative_eq!(
+                gravity[index],
+                gravity_acceleration(&state.position),
+                max_relative = 1e-14
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_until_matches_members_flown_alone() {
+        let spacecraft = SimpleSat::default();
+        // Neither step size divides t_end, so both members end on a shortened step
+        let orbits = [(400_000.0, 0.0, 0.7), (800_000.0, 1.2, 3.0)];
+        let t_end = 100.0;
+
+        let mut constellation = Constellation::new();
+        for (altitude, inclination, dt) in orbits {
+            constellation.add_member(circular_member(&spacecraft, altitude, inclination, dt));
+        }
+        constellation.run_until(t_end).unwrap();
+
+        for (index, (altitude, inclination, dt)) in orbits.into_iter().enumerate() {
+            let mut alone = circular_member(&spacecraft, altitude, inclination, dt);
+            alone.run_until(t_end).unwrap();
+            let member = constellation.member(index).unwrap();
+            assert_eq!(member.step_count(), alone.step_count());
+            assert_relative_eq!(member.current_time(), t_end, epsilon = 1e-9);
+            assert_relative_eq!(
+                member.state().position,
+                alone.state().position,
+                max_relative = 1e-12
+            );
+            assert_relative_eq!(
+                member.state().velocity,
+                alone.state().velocity,
+                max_relative = 1e-12
+            );
+        }
+    }
+}
@@ -4,14 +4,14 @@ use crate::coordinates::eop_manager::EOPManager;
 use hifitime::Epoch;
 use lazy_static::lazy_static;
 use nalgebra as na;
-use std::sync::{Mutex, OnceLock};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
 
 lazy_static! {
     static ref EOP_MANAGER: Mutex<EOPManager> = Mutex::new(EOPManager::new());
-    static ref INIT_STATUS: OnceLock<bool> = OnceLock::new(); // Tracks if `initialize()` was called
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct EOPData {
     pub x_pole: f64,  // Polar motion x (arcsec)
     pub y_pole: f64,  // Polar motion y (arcsec)
@@ -40,16 +40,63 @@ impl TryFrom<Epoch> for EOPData {
     /// Try to get EOP data for a given epoch
     /// This will fetch the EOP data from the cache file if available otherwise it will fail
     fn try_from(epoch: Epoch) -> Result<Self, Self::Error> {
-        let mut manager = EOP_MANAGER.lock().unwrap();
+        eop_data_from(&mut lock_eop_manager(), epoch)
+    }
+}
 
-        // Ensure `initialize()` is only called once
-        if INIT_STATUS.get().is_none() {
-            manager.initialize()?;
-            INIT_STATUS.set(true).unwrap();
-        }
+/// Looks `epoch` up in `manager`, loading its data on first use
+fn eop_data_from(manager: &mut EOPManager, epoch: Epoch) -> Result<EOPData, EOPErrors> {
+    // Ensure `initialize()` is only called once
+    if !manager.is_initialized() {
+        manager.initialize()?;
+    }
+
+    // Fetch EOP data
+    manager.get_eop_data(epoch, false)
+}
+
+/// When the shared manager last downloaded or read fresh EOP data, `None` if it has only
+/// loaded the data embedded at build time (or nothing yet)
+pub fn eop_last_update() -> Option<chrono::DateTime<chrono::Utc>> {
+    lock_eop_manager().last_update()
+}
 
-        // Fetch EOP data
-        manager.get_eop_data(epoch, false)
+/// Age of the EOP data held by the shared manager, loading the data first if need be. For
+/// the data embedded at build time this is the time since the build fetched it; `None` if
+/// no data can be loaded.
+pub fn eop_data_age() -> Option<chrono::Duration> {
+    let mut manager = lock_eop_manager();
+    if !manager.is_initialized() && manager.initialize().is_err() {
+        return None;
+    }
+    manager.data_age()
+}
+
+/// Points the shared manager at the cache file `path`, or with `None` at the data embedded
+/// at build time only, overriding `KOSMOSS_EOP_CACHE`. Data already loaded is dropped.
+pub fn set_eop_cache_path(path: Option<PathBuf>) {
+    *lock_eop_manager() = match path {
+        Some(path) => EOPManager::with_cache_path(path),
+        None => EOPManager::without_cache(),
+    };
+}
+
+/// Locks the shared EOP manager
+fn lock_eop_manager() -> MutexGuard<'static, EOPManager> {
+    lock_recovering(&EOP_MANAGER)
+}
+
+/// Locks `mutex`. A panic while the lock was held may have left the manager half-updated,
+/// so a poisoned lock is recovered by starting again from a fresh manager.
+fn lock_recovering(mutex: &Mutex<EOPManager>) -> MutexGuard<'_, EOPManager> {
+    match mutex.lock() {
+        Ok(manager) => manager,
+        Err(poisoned) => {
+            let mut manager = poisoned.into_inner();
+            *manager = EOPManager::new();
+            mutex.clear_poison();
+            manager
+        }
     }
 }
 
@@ -67,8 +114,41 @@ impl EOPData {
     }
 }
 
-/// Convert ITRS Cartesian to Geodetic coordinates (WGS84)
+/// Convert ITRS Cartesian to Geodetic coordinates (WGS84), with altitude clamped at zero for
+/// display. Use `itrs_to_geodetic_signed` to see positions below the surface.
 pub fn itrs_to_geodetic(pos: &na::Vector3<f64>) -> (f64, f64, f64) {
+    let (longitude, latitude, altitude) = itrs_to_geodetic_signed(pos);
+    (longitude, latitude, altitude.max(0.0))
+}
+
+/// Geodetic local vertical at `pos`: the outward unit normal to the WGS84 ellipsoid through
+/// the point. It leans away from the geocentric radial by the deflection between geodetic and
+/// geocentric latitude, up to about 0.19° at mid latitudes on the surface.
+pub fn geodetic_normal(pos: &na::Vector3<f64>) -> na::Vector3<f64> {
+    let (_, latitude, _) = itrs_to_geodetic_signed(pos);
+    let latitude = latitude.to_radians();
+    let p = (pos.x * pos.x + pos.y * pos.y).sqrt();
+    let (cos_longitude, sin_longitude) = if p < 1e-10 {
+        (1.0, 0.0)
+    } else {
+        (pos.x / p, pos.y / p)
+    };
+
+    na::Vector3::new(
+        latitude.cos() * cos_longitude,
+        latitude.cos() * sin_longitude,
+        latitude.sin(),
+    )
+}
+
+/// Returns true if the ITRS position lies inside the WGS84 ellipsoid
+pub fn is_below_surface(pos: &na::Vector3<f64>) -> bool {
+    itrs_to_geodetic_signed(pos).2 < 0.0
+}
+
+/// Convert ITRS Cartesian to Geodetic coordinates (WGS84), returning a negative altitude for
+/// positions inside the ellipsoid
+pub fn itrs_to_geodetic_signed(pos: &na::Vector3<f64>) -> (f64, f64, f64) {
     let x = pos[0];
     let y = pos[1];
     let z = pos[2];
@@ -88,7 +168,7 @@ pub fn itrs_to_geodetic(pos: &na::Vector3<f64>) -> (f64, f64, f64) {
     if p < 1e-10 {
         let longitude: f64 = 0.0;
         let latitude: f64 = if z < 0.0 { -PI / 2.0 } else { PI / 2.0 };
-        let altitude: f64 = (z.abs() - b).max(0.0); // Ensure non-negative
+        let altitude: f64 = z.abs() - b;
         return (longitude.to_degrees(), latitude.to_degrees(), altitude);
     }
 
@@ -113,59 +193,540 @@ pub fn itrs_to_geodetic(pos: &na::Vector3<f64>) -> (f64, f64, f64) {
     // Calculate final altitude
     let sin_lat = latitude.sin();
     let n = a / (1.0 - e2 * sin_lat * sin_lat).sqrt();
-    let altitude = (p / latitude.cos() - n).max(0.0); // Ensure non-negative
+    let altitude = p / latitude.cos() - n;
 
     (longitude.to_degrees(), latitude.to_degrees(), altitude)
 }
 
-/// Convert GCRS to ITRS using IAU 2000/2006 CIO-based transformation
-pub fn gcrs_to_itrs(position: &na::Vector3<f64>, epoch: &Epoch, eop: &EOPData) -> na::Vector3<f64> {
+/// Cost/accuracy tradeoff for the celestial-to-terrestrial transformation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransformPrecision {
+    /// Precession-only CIP series. Nutation is left out, so the pole is only good to about
+    /// 20 arcsec (roughly 0.7 km at LEO radius).
+    #[default]
+    Fast,
+    /// Adds the leading IAU 2000B nutation terms to the CIP series and the periodic terms
+    /// of the CIO locator, good to a few milliarcseconds
+    Precise,
+}
+
+/// Luni-solar nutation terms: multipliers of (l, l', F, D, Ω), then the Δψ sine and Δε
+/// cosine amplitudes in arcsec (IAU 2000B, largest terms first)
+const NUTATION_TERMS: [([f64; 5], f64, f64); 17] = [
+    ([0.0, 0.0, 0.0, 0.0, 1.0], -17.2064161, 9.2052331),
+    ([0.0, 0.0, 2.0, -2.0, 2.0], -1.3170906, 0.5730336),
+    ([0.0, 0.0, 2.0, 0.0, 2.0], -0.2276413, 0.0978459),
+    ([0.0, 0.0, 0.0, 0.0, 2.0], 0.2074554, -0.0897492),
+    ([0.0, 1.0, 0.0, 0.0, 0.0], 0.1475877, 0.0073871),
+    ([0.0, 1.0, 2.0, -2.0, 2.0], -0.0516821, 0.0224386),
+    ([1.0, 0.0, 0.0, 0.0, 0.0], 0.0711159, -0.0006750),
+    ([0.0, 0.0, 2.0, 0.0, 1.0], -0.0387298, 0.0200728),
+    ([1.0, 0.0, 2.0, 0.0, 2.0], -0.0301461, 0.0129025),
+    ([0.0, -1.0, 2.0, -2.0, 2.0], 0.0215829, -0.0095929),
+    ([0.0, 0.0, 2.0, -2.0, 1.0], 0.0128227, -0.0068982),
+    ([-1.0, 0.0, 2.0, 0.0, 2.0], 0.0123457, -0.0053311),
+    ([-1.0, 0.0, 0.0, 2.0, 0.0], 0.0156994, -0.0001235),
+    ([1.0, 0.0, 0.0, 0.0, 1.0], 0.0063110, -0.0033228),
+    ([-1.0, 0.0, 0.0, 0.0, 1.0], -0.0057976, 0.0031429),
+    ([-1.0, 0.0, 2.0, 2.0, 2.0], -0.0059641, 0.0025543),
+    ([1.0, 0.0, 2.0, 0.0, 1.0], -0.0051613, 0.0026366),
+];
+
+/// CIP coordinates X, Y and CIO locator s in radians, `t` Julian centuries since J2000.0.
+/// The frame bias enters through the constant terms of X and Y in both paths.
+fn cip_coordinates(t: f64, precision: TransformPrecision) -> (f64, f64, f64) {
     // Convert arcseconds to radians
     let arcsec_to_rad = std::f64::consts::PI / (180.0 * 3600.0);
 
+    match precision {
+        TransformPrecision::Fast => {
+            // Get X, Y coordinates of the CIP in GCRS (IAU 2006 precession polynomial)
+            let x = -0.016617 + 2004.191898 * t - 0.4297829 * t * t - 0.19861834 * t * t * t;
+            let y = -0.006951 - 0.025896 * t - 22.4072747 * t * t + 0.00190059 * t * t * t;
+
+            // Calculate s = CIO locator (simplified, accuracy ~0.1 mas)
+            let s = -0.0015506 + (-0.0001729 - 0.000000127 * t) * t;
+
+            (x * arcsec_to_rad, y * arcsec_to_rad, s * arcsec_to_rad)
+        }
+        TransformPrecision::Precise => {
+            // Delaunay arguments (IERS Conventions 2003), arcsec
+            let arguments = [
+                485868.249036 + (1717915923.2178 + 31.8792 * t) * t,
+                1287104.79305 + (129596581.0481 - 0.5532 * t) * t,
+                335779.526232 + (1739527262.8478 - 12.7512 * t) * t,
+                1072260.70369 + (1602961601.2090 - 6.3706 * t) * t,
+                450160.398036 + (-6962890.5431 + 7.4722 * t) * t,
+            ];
+
+            let (mut dpsi, mut deps) = (0.0, 0.0);
+            for (multipliers, psi_sin, eps_cos) in NUTATION_TERMS {
+                let argument = multipliers
+                    .iter()
+                    .zip(arguments)
+                    .map(|(m, a)| m * a * arcsec_to_rad)
+                    .sum::<f64>();
+                dpsi += psi_sin * argument.sin();
+                deps += eps_cos * argument.cos();
+            }
+            // Secular change of the 18.6-year term
+            let omega = arguments[4] * arcsec_to_rad;
+            dpsi += -0.0174666 * t * omega.sin();
+            deps += 0.0009086 * t * omega.cos();
+
+            // To first order, nutation moves the pole by Δψ sin ε₀ along X and Δε along Y.
+            // The cross terms with the accumulated precession in longitude ψ_A grow to
+            // tens of mas within a few decades of J2000.
+            let (sin_eps0, cos_eps0) = (84381.406 * arcsec_to_rad).sin_cos();
+            let psi_a = 5038.481507 * t * arcsec_to_rad;
+            let x = -0.016617
+                + t * (2004.191898
+                    + t * (-0.4297829 + t * (-0.19861834 + t * (0.000007578 + t * 0.0000059285))))
+                + dpsi * sin_eps0
+                + deps * cos_eps0 * psi_a;
+            let y = -0.006951
+                + t * (-0.025896
+                    + t * (-22.4072747 + t * (0.00190059 + t * (0.001112526 + t * 0.0000001358))))
+                + deps
+                - dpsi * sin_eps0 * cos_eps0 * psi_a;
+            let (x, y) = (x * arcsec_to_rad, y * arcsec_to_rad);
+
+            // s + XY/2 (IAU 2006/2000A), keeping the terms above 10 µas
+            let s_xy2 = 0.000094 + t * (0.00380865 + t * (-0.00012268 - 0.07257411 * t))
+                - 0.00264073 * omega.sin()
+                - 0.00006353 * (2.0 * omega).sin();
+
+            (x, y, s_xy2 * arcsec_to_rad - x * y / 2.0)
+        }
+    }
+}
+
+/// Earth rotation angle (IERS Conventions 2010, eq. 5.15) in radians within [0, 2π): the
+/// angle between the CIO and the TIO, a linear function of UT1 = UTC + `eop.ut1_utc`
+pub fn earth_rotation_angle(epoch: &Epoch, eop: &EOPData) -> f64 {
+    // Days of UT1 since J2000.0, counted from the MJD to keep sub-millisecond resolution
+    let ut1_days = epoch.to_mjd_utc_days() - 51544.5 + eop.ut1_utc / 86400.0;
+
+    // Splitting off the whole turns per day keeps the large product out of the sum
+    let turns = ut1_days.fract() + 0.7790572732640 + 0.00273781191135448 * ut1_days;
+    (2.0 * PI * turns).rem_euclid(2.0 * PI)
+}
+
+/// The factors of the GCRS to ITRS rotation, ITRS = W R Q GCRS. Build one per epoch to
+/// transform many vectors, or to inspect the chain stage by stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GcrsToItrs {
+    /// Q: GCRS to the celestial intermediate frame (precession, nutation and frame bias)
+    pub celestial_to_intermediate: na::Matrix3<f64>,
+    /// R: rotation about the CIP by the Earth rotation angle, less the CIO locator s
+    pub earth_rotation: na::Matrix3<f64>,
+    /// W: polar motion, from the terrestrial intermediate frame to ITRS
+    pub polar_motion: na::Matrix3<f64>,
+}
+
+impl GcrsToItrs {
+    /// The combined rotation W R Q
+    pub fn matrix(&self) -> na::Matrix3<f64> {
+        self.polar_motion * self.earth_rotation * self.celestial_to_intermediate
+    }
+}
+
+/// Convert GCRS to ITRS using IAU 2000/2006 CIO-based transformation. This is the inertial
+/// (ECI) to Earth-fixed (ECEF) conversion for the whole crate: the Earth rotation angle,
+/// precession-nutation and polar motion all follow from `epoch` and `eop`, so callers never
+/// compute a sidereal angle themselves.
+pub fn gcrs_to_itrs(position: &na::Vector3<f64>, epoch: &Epoch, eop: &EOPData) -> na::Vector3<f64> {
+    gcrs_to_itrs_with_precision(position, epoch, eop, TransformPrecision::default())
+}
+
+/// Same as `gcrs_to_itrs`, with the CIP series chosen by `precision`
+pub fn gcrs_to_itrs_with_precision(
+    position: &na::Vector3<f64>,
+    epoch: &Epoch,
+    eop: &EOPData,
+    precision: TransformPrecision,
+) -> na::Vector3<f64> {
+    gcrs_to_itrs_rotation_with_precision(epoch, eop, precision).matrix() * position
+}
+
+/// The rotation applied by `gcrs_to_itrs` at `epoch`, split into its factors
+pub fn gcrs_to_itrs_rotation(epoch: &Epoch, eop: &EOPData) -> GcrsToItrs {
+    gcrs_to_itrs_rotation_with_precision(epoch, eop, TransformPrecision::default())
+}
+
+/// Same as `gcrs_to_itrs_rotation`, with the CIP series chosen by `precision`
+pub fn gcrs_to_itrs_rotation_with_precision(
+    epoch: &Epoch,
+    eop: &EOPData,
+    precision: TransformPrecision,
+) -> GcrsToItrs {
     // Get time since J2000.0 in Julian centuries
     let t = (epoch.to_jde_tai(hifitime::Unit::Day) - 2451545.0) / 36525.0;
+    let (x, y, s) = cip_coordinates(t, precision);
 
-    // Calculate Earth Rotation Angle (ERA)
-    let ut1_jd = epoch.to_jde_tai(hifitime::Unit::Day) + (eop.ut1_utc / 86400.0);
-    let theta = 2.0 * PI * (0.7790572732640 + 1.00273781191135448 * (ut1_jd - 2451545.0));
+    celestial_to_terrestrial(x, y, s, epoch, eop)
+}
 
-    // Get X, Y coordinates of the CIP in GCRS (simplified IAU 2006/2000A, accuracy ~1 mas)
-    let x = -0.016617 + 2004.191898 * t - 0.4297829 * t * t - 0.19861834 * t * t * t;
-    let y = -0.006951 - 0.025896 * t - 22.4072747 * t * t + 0.00190059 * t * t * t;
-    let x = x * arcsec_to_rad;
-    let y = y * arcsec_to_rad;
+/// GCRS to ITRS rotation for the CIP at (`x`, `y`) and CIO locator `s`, all in radians
+fn celestial_to_terrestrial(x: f64, y: f64, s: f64, epoch: &Epoch, eop: &EOPData) -> GcrsToItrs {
+    // Convert arcseconds to radians
+    let arcsec_to_rad = std::f64::consts::PI / (180.0 * 3600.0);
 
-    // Calculate s = CIO locator (simplified, accuracy ~0.1 mas)
-    let s = -0.0015506 + (-0.0001729 - 0.000000127 * t) * t;
-    let s = s * arcsec_to_rad;
+    let theta = earth_rotation_angle(epoch, eop);
 
-    // Form the celestial-to-intermediate matrix (Q)
-    let d = 1.0 + 0.5 * (x * x + y * y);
+    // Form the celestial-to-intermediate matrix (Q), which takes the CIP (x, y, z) onto the
+    // pole: the transpose of IERS Conventions 2010 eq. 5.10, with s left to R. With
+    // a = 1 / (1 + z) it is exactly orthogonal.
+    let z = (1.0 - x * x - y * y).sqrt();
+    let a = 1.0 / (1.0 + z);
 
     let q_matrix = na::Matrix3::new(
-        1.0 - x * x / 2.0 / d,
-        -x * y / 2.0 / d,
-        -x / d,
-        -x * y / 2.0 / d,
-        1.0 - y * y / 2.0 / d,
-        -y / d,
+        1.0 - a * x * x,
+        -a * x * y,
+        -x,
+        -a * x * y,
+        1.0 - a * y * y,
+        -y,
         x,
         y,
-        1.0 / d,
+        z,
     );
 
-    // Form the Earth rotation matrix (R)
-    let r_matrix = na::Rotation3::from_axis_angle(&na::Vector3::z_axis(), theta - s);
+    // Form the Earth rotation matrix (R). R3(ERA - s) is a frame rotation, so the
+    // equivalent vector rotation turns through the negative angle
+    let r_matrix = na::Rotation3::from_axis_angle(&na::Vector3::z_axis(), -(theta - s));
 
     // Polar motion matrix (W)
     let xp = eop.x_pole * arcsec_to_rad;
     let yp = eop.y_pole * arcsec_to_rad;
     let w_matrix = na::Rotation3::from_euler_angles(-yp, -xp, 0.0);
 
-    // Combined transformation
-    let transform = w_matrix.matrix() * r_matrix.matrix() * q_matrix;
+    GcrsToItrs {
+        celestial_to_intermediate: q_matrix,
+        earth_rotation: *r_matrix.matrix(),
+        polar_motion: *w_matrix.matrix(),
+    }
+}
+
+/// Orbital RSW frame (radial, along-track, orbit normal) for the GCRS state `r`, `v`.
+/// The columns of the returned matrix are the R, S and W unit vectors in GCRS, so it maps
+/// RSW components into GCRS and its transpose maps GCRS components into RSW.
+///
+/// For (near enough) purely radial motion, e.g. a vertical fall at the end of reentry, there
+/// is no orbit plane, and W is taken as any unit vector normal to `r` so the frame stays
+/// finite. Use `try_gcrs_to_rsw_rotation` to handle that case instead.
+pub fn gcrs_to_rsw_rotation(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> na::Matrix3<f64> {
+    try_gcrs_to_rsw_rotation(r, v).unwrap_or_else(|| {
+        let r_unit = r.normalize();
+        let helper = if r_unit.x.abs() < 0.9 {
+            na::Vector3::x()
+        } else {
+            na::Vector3::y()
+        };
+        rsw_from_normal(r_unit, r_unit.cross(&helper).normalize())
+    })
+}
+
+/// The RSW frame as for `gcrs_to_rsw_rotation`, or `None` for purely radial motion, which
+/// has no orbit plane to define it
+pub fn try_gcrs_to_rsw_rotation(
+    r: &na::Vector3<f64>,
+    v: &na::Vector3<f64>,
+) -> Option<na::Matrix3<f64>> {
+    let w_unit = r
+        .cross(v)
+        .try_normalize(1e-9 * r.magnitude() * v.magnitude())?;
+    Some(rsw_from_normal(r.normalize(), w_unit))
+}
+
+fn rsw_from_normal(r_unit: na::Vector3<f64>, w_unit: na::Vector3<f64>) -> na::Matrix3<f64> {
+    let s_unit = w_unit.cross(&r_unit);
+    na::Matrix3::from_columns(&[r_unit, s_unit, w_unit])
+}
+
+/// Orbital NTW frame (in-plane normal, tangent, orbit normal) for the GCRS state `r`, `v`.
+/// T lies along the velocity, W along the orbit normal as in RSW, and N = T × W completes
+/// the frame, pointing away from the Earth. The columns are the N, T and W unit vectors in
+/// GCRS.
+pub fn gcrs_to_ntw_rotation(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> na::Matrix3<f64> {
+    let w_unit = gcrs_to_rsw_rotation(r, v).column(2).into_owned();
+    let t_unit = v.normalize();
+    let n_unit = t_unit.cross(&w_unit);
+
+    na::Matrix3::from_columns(&[n_unit, t_unit, w_unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_earth_rotation_angle() {
+        // ERA at UT1 MJD 54388.0, from the SOFA test suite (iauEra00)
+        let epoch = Epoch::from_gregorian_utc_at_midnight(2007, 10, 15);
+        let eop = EOPData {
+            ut1_utc: 0.0,
+            ..EOPData::default()
+        };
+        assert_relative_eq!(
+            earth_rotation_angle(&epoch, &eop),
+            0.4022837240028158,
+            epsilon = 1e-10
+        );
+
+        // UT1-UTC shifts the angle by the sidereal rotation rate
+        let late = EOPData {
+            ut1_utc: 0.5,
+            ..EOPData::default()
+        };
+        assert_relative_eq!(
+            earth_rotation_angle(&epoch, &late) - earth_rotation_angle(&epoch, &eop),
+            0.5 * EARTH_ANGULAR_VELOCITY,
+            max_relative = 1e-6
+        );
+    }
+
+    #[test]
+    fn test_rotation_chain() {
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let eop = EOPData::default();
+        let rotation = gcrs_to_itrs_rotation(&epoch, &eop);
+
+        // Each factor is a proper rotation, and the three compose to the full transform
+        for factor in [
+            rotation.celestial_to_intermediate,
+            rotation.earth_rotation,
+            rotation.polar_motion,
+        ] {
+            assert_relative_eq!(
+                factor.transpose() * factor,
+                na::Matrix3::identity(),
+                epsilon = 1e-15
+            );
+        }
+        let matrix = rotation.matrix();
+        assert_eq!(
+            matrix,
+            rotation.polar_motion * rotation.earth_rotation * rotation.celestial_to_intermediate
+        );
+
+        // One matrix reused across vectors matches transforming each on its own
+        let position = na::Vector3::new(6_778_137.0, -1_200_000.0, 350_000.0);
+        let velocity = na::Vector3::new(-1_100.0, 7_300.0, 900.0);
+        assert_eq!(matrix * position, gcrs_to_itrs(&position, &epoch, &eop));
+        assert_eq!(matrix * velocity, gcrs_to_itrs(&velocity, &epoch, &eop));
+
+        // Dropping polar motion leaves the terrestrial intermediate frame, which shares the
+        // CIP as its pole; the two differ by the sub-arcsecond pole offset
+        let tirs = rotation.earth_rotation * rotation.celestial_to_intermediate * position;
+        let offset = (rotation.matrix() * position - tirs).magnitude() / position.magnitude();
+        assert!(offset > 0.0 && offset < 2e-6);
+    }
+
+    #[test]
+    fn test_transform_precision() {
+        let mas_to_rad = std::f64::consts::PI / (180.0 * 3600.0 * 1000.0);
+
+        // IAU 2006/2000A CIP and CIO locator at 2006-01-01 0h TT, from the SOFA test suite
+        // (TT = TAI + 32.184 s)
+        let epoch = Epoch::from_gregorian_tai(2005, 12, 31, 23, 59, 27, 816_000_000);
+        let (x_ref, y_ref, s_ref) = (
+            0.579130848283529e-3,
+            0.402058009945402e-4,
+            -0.122003229416458e-7,
+        );
+
+        let t = (epoch.to_jde_tai(hifitime::Unit::Day) - 2451545.0) / 36525.0;
+        let pole_error = |precision| {
+            let (x, y, s) = cip_coordinates(t, precision);
+            (
+                ((x - x_ref).powi(2) + (y - y_ref).powi(2)).sqrt(),
+                (s - s_ref).abs(),
+            )
+        };
+        let (fast_pole, _) = pole_error(TransformPrecision::Fast);
+        let (precise_pole, precise_s) = pole_error(TransformPrecision::Precise);
+
+        // Each path is within its stated accuracy
+        assert!(fast_pole < 20_000.0 * mas_to_rad, "fast {fast_pole}");
+        assert!(precise_pole < 5.0 * mas_to_rad, "precise {precise_pole}");
+        assert!(precise_s < 0.1 * mas_to_rad);
+
+        // Applied to a position, the two paths agree to the Fast tolerance, and Precise lands
+        // much closer to the rotation built from the reference pole
+        let eop = EOPData::default();
+        let position = na::Vector3::new(5_000_000.0, -3_000_000.0, 4_000_000.0);
+        let reference =
+            celestial_to_terrestrial(x_ref, y_ref, s_ref, &epoch, &eop).matrix() * position;
+        let fast = gcrs_to_itrs_with_precision(&position, &epoch, &eop, TransformPrecision::Fast);
+        let precise =
+            gcrs_to_itrs_with_precision(&position, &epoch, &eop, TransformPrecision::Precise);
+        let radius = position.magnitude();
+
+        assert_eq!(gcrs_to_itrs(&position, &epoch, &eop), fast);
+        assert!((fast - precise).magnitude() < 20_000.0 * mas_to_rad * radius);
+        assert!((precise - reference).magnitude() < 5.0 * mas_to_rad * radius);
+        assert!((precise - reference).magnitude() < (fast - reference).magnitude() / 100.0);
+    }
+
+    #[test]
+    fn test_gcrs_to_rsw_rotation_is_orthonormal() {
+        // An inclined, eccentric state away from the apsides, so v has a radial component
+        let r = na::Vector3::new(6_000_000.0, 2_500_000.0, 1_200_000.0);
+        let v = na::Vector3::new(-2_000.0, 6_500.0, 3_100.0);
+        let rotation = gcrs_to_rsw_rotation(&r, &v);
 
-    // Apply transformation
-    transform * position
+        assert_relative_eq!(
+            rotation.transpose() * rotation,
+            na::Matrix3::identity(),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(rotation.determinant(), 1.0, epsilon = 1e-12);
+
+        // Radial maps to the first RSW axis, and the orbit normal to the third
+        assert_relative_eq!(
+            rotation.transpose() * r.normalize(),
+            na::Vector3::x(),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            rotation.transpose() * r.cross(&v).normalize(),
+            na::Vector3::z(),
+            epsilon = 1e-12
+        );
+
+        // The velocity lies in the R-S plane with a positive along-track component
+        let v_rsw = rotation.transpose() * v;
+        assert_relative_eq!(v_rsw.z, 0.0, epsilon = 1e-9);
+        assert!(v_rsw.y > 0.0);
+    }
+
+    #[test]
+    fn test_gcrs_to_itrs_follows_earth_rotation() {
+        // The Earth turns east under a point fixed in GCRS, so its Earth-fixed longitude
+        // falls by the rotation rate times the elapsed time
+        let position = na::Vector3::new(7_000_000.0, 0.0, 0.0);
+        let eop = EOPData::default();
+        let start = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let longitude = |epoch: &Epoch| {
+            let itrs = gcrs_to_itrs(&position, epoch, &eop);
+            itrs.y.atan2(itrs.x)
+        };
+
+        let elapsed = 600.0;
+        let later = start + hifitime::Duration::from_seconds(elapsed);
+        let change = (longitude(&later) - longitude(&start) + PI).rem_euclid(2.0 * PI) - PI;
+        assert_relative_eq!(
+            change,
+            -EARTH_ANGULAR_VELOCITY * elapsed,
+            max_relative = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_gcrs_to_ntw_rotation() {
+        let r = na::Vector3::new(6_000_000.0, 2_500_000.0, 1_200_000.0);
+        let v = na::Vector3::new(-2_000.0, 6_500.0, 3_100.0);
+        let ntw = gcrs_to_ntw_rotation(&r, &v);
+        let rsw = gcrs_to_rsw_rotation(&r, &v);
+
+        assert_relative_eq!(
+            ntw.transpose() * ntw,
+            na::Matrix3::identity(),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(ntw.determinant(), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(ntw.column(1).into_owned(), v.normalize(), epsilon = 1e-12);
+        assert_relative_eq!(ntw.column(2), rsw.column(2), epsilon = 1e-12);
+
+        // With a radial velocity component, N and R differ, but N still points outward
+        assert!(ntw.column(0).dot(&r) > 0.0);
+        assert!(ntw.column(0).dot(&rsw.column(0)) < 1.0 - 1e-6);
+    }
+
+    #[test]
+    fn test_gcrs_to_rsw_rotation_for_radial_motion() {
+        // Falling straight down, so there is no orbit plane
+        let r = na::Vector3::new(-6_051_337.0, 35_801.0, -2_051_086.0);
+        let rotation = gcrs_to_rsw_rotation(&r, &(r * -1e-5));
+
+        assert_relative_eq!(
+            rotation.transpose() * rotation,
+            na::Matrix3::identity(),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            rotation.column(0).into_owned(),
+            r.normalize(),
+            epsilon = 1e-12
+        );
+        assert!(try_gcrs_to_rsw_rotation(&r, &(r * -1e-5)).is_none());
+    }
+
+    #[test]
+    fn test_eop_lookup_recovers_from_poisoned_lock() {
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let manager = Mutex::new(EOPManager::without_cache());
+
+        let result = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let _manager = manager.lock();
+                    panic!("poisoning the EOP manager lock");
+                })
+                .join()
+        });
+        assert!(result.is_err());
+        assert!(manager.is_poisoned());
+
+        let eop = eop_data_from(&mut lock_recovering(&manager), epoch)
+            .expect("EOP lookup should recover from a poisoned lock");
+        assert!(!manager.is_poisoned());
+
+        let position = na::Vector3::new(7_000_000.0, 0.0, 0.0);
+        let itrs = gcrs_to_itrs(&position, &epoch, &eop);
+        assert_relative_eq!(itrs.magnitude(), position.magnitude(), max_relative = 1e-9);
+    }
+
+    #[test]
+    fn test_eop_without_cache_serves_embedded_data() {
+        let mut manager = EOPManager::without_cache();
+
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        assert!(eop_data_from(&mut manager, epoch).is_ok());
+        assert_eq!(manager.last_update(), None);
+    }
+
+    #[test]
+    fn test_geodetic_below_surface() {
+        let b = WGS84_A * (1.0 - WGS84_F);
+
+        // Equator, pole and mid-latitude points 10 km inside the ellipsoid
+        let lat = 45.0_f64.to_radians();
+        let e2 = 2.0 * WGS84_F - WGS84_F * WGS84_F;
+        let n = WGS84_A / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+        let h = -10_000.0;
+        let mid_latitude =
+            na::Vector3::new((n + h) * lat.cos(), 0.0, (n * (1.0 - e2) + h) * lat.sin());
+        let cases = [
+            (na::Vector3::new(WGS84_A - 10_000.0, 0.0, 0.0), 0.0),
+            (na::Vector3::new(0.0, 0.0, b - 10_000.0), 90.0),
+            (mid_latitude, 45.0),
+        ];
+
+        for (position, expected_latitude) in cases {
+            let (_, latitude, altitude) = itrs_to_geodetic_signed(&position);
+            assert_relative_eq!(latitude, expected_latitude, epsilon = 1e-9);
+            assert_relative_eq!(altitude, -10_000.0, epsilon = 1e-6);
+            assert!(is_below_surface(&position));
+
+            // The display variant still clamps
+            assert_eq!(itrs_to_geodetic(&position).2, 0.0);
+        }
+
+        let above = na::Vector3::new(WGS84_A + 400_000.0, 0.0, 0.0);
+        assert!(!is_below_surface(&above));
+        assert_relative_eq!(itrs_to_geodetic(&above).2, 400_000.0, epsilon = 1e-6);
+    }
 }
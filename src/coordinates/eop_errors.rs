@@ -1,3 +1,4 @@
+use super::coordinate_transformation::EOPData;
 use reqwest;
 use std::{error::Error, fmt, io, num::ParseFloatError};
 
@@ -11,6 +12,22 @@ pub enum EOPErrors {
     MissingEOPData,
     DataInterpolationError,
     HttpForbidden,
+    /// The epoch lies outside the loaded EOP series. `nearest` is the boundary value, for
+    /// callers that can tolerate holding it constant.
+    OutOfRange {
+        epoch: hifitime::Epoch,
+        nearest: EOPData,
+    },
+}
+
+impl EOPErrors {
+    /// Turns an out-of-range lookup into its boundary value, passing other errors through
+    pub fn into_nearest(self) -> Result<EOPData, EOPErrors> {
+        match self {
+            EOPErrors::OutOfRange { nearest, .. } => Ok(nearest),
+            e => Err(e),
+        }
+    }
 }
 
 impl fmt::Display for EOPErrors {
@@ -24,6 +41,9 @@ impl fmt::Display for EOPErrors {
             EOPErrors::MissingEOPData => write!(f, "EOP data is missing"),
             EOPErrors::DataInterpolationError => write!(f, "Failed to interpolate EOP data"),
             EOPErrors::HttpForbidden => write!(f, "HTTP 403 Forbidden"),
+            EOPErrors::OutOfRange { epoch, .. } => {
+                write!(f, "No EOP data for {}, outside the loaded range", epoch)
+            }
         }
     }
 }
@@ -5,6 +5,7 @@ use csv::ReaderBuilder;
 use hifitime::Epoch;
 use reqwest;
 use std::collections::BTreeMap;
+use std::ffi::OsString;
 use std::fs;
 use std::path::PathBuf;
 
@@ -12,31 +13,75 @@ const CACHE_DURATION_HOURS: i64 = 24;
 const CACHE_FILE: &str = "eop_cache.csv";
 const CELESTRAK_URL: &str = "https://celestrak.org/SpaceData/EOP-All.csv";
 
+/// Overrides the cache file location; "off" or "none" disables caching
+pub const CACHE_ENV_VAR: &str = "KOSMOSS_EOP_CACHE";
+
 pub(super) struct EOPManager {
-    cache_path: PathBuf,
+    cache_path: Option<PathBuf>, // None when caching is disabled
     last_update: Option<DateTime<Utc>>,
+    embedded: bool, // true while the data embedded at build time is loaded
     eop_data: BTreeMap<i64, EOPData>, // Unix timestamp -> EOPData
+    initialized: bool,
 }
 
 impl EOPManager {
     /// Creates a new EOPManager. Does not load data as it may fail.
+    ///
+    /// The cache lives at `KOSMOSS_EOP_CACHE` if that is set, and under the user cache
+    /// directory otherwise.
     pub fn new() -> Self {
-        let cache_dir = dirs::cache_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("kosmoss");
-        fs::create_dir_all(&cache_dir).unwrap_or_default();
+        Self::with_cache(cache_path_from_env(std::env::var_os(CACHE_ENV_VAR)))
+    }
+
+    /// Creates a manager that caches downloaded data at `path`, ignoring `KOSMOSS_EOP_CACHE`
+    pub fn with_cache_path(path: impl Into<PathBuf>) -> Self {
+        Self::with_cache(Some(path.into()))
+    }
 
+    /// Creates a manager that never downloads or caches data, and only serves the data
+    /// embedded at build time
+    pub fn without_cache() -> Self {
+        Self::with_cache(None)
+    }
+
+    fn with_cache(cache_path: Option<PathBuf>) -> Self {
         Self {
-            cache_path: cache_dir.join(CACHE_FILE),
+            cache_path,
             last_update: None,
+            embedded: false,
             eop_data: BTreeMap::new(),
+            initialized: false,
         }
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// When the loaded data was last downloaded or read from a fresh cache file. `None`
+    /// while only the data embedded at build time has been loaded.
+    pub fn last_update(&self) -> Option<DateTime<Utc>> {
+        self.last_update
+    }
+
+    /// When the loaded data was fetched: `last_update`, or for the data embedded at build
+    /// time, when the build fetched it. `None` before any data is loaded.
+    pub fn fetched_at(&self) -> Option<DateTime<Utc>> {
+        self.last_update
+            .or_else(|| self.embedded.then(embedded_fetched_at).flatten())
+    }
+
+    /// Time since `fetched_at`
+    pub fn data_age(&self) -> Option<Duration> {
+        self.fetched_at().map(|fetched_at| Utc::now() - fetched_at)
+    }
+
     /// Explicitly loads cached data. Returns an error if loading fails.
     pub fn initialize(&mut self) -> Result<(), EOPErrors> {
         self.load_embedded_data()
-            .or_else(|_| self.update_cache_if_needed())
+            .or_else(|_| self.update_cache_if_needed())?;
+        self.initialized = true;
+        Ok(())
     }
 
     /// Allows users to refresh the EOP data manually at runtime.
@@ -44,8 +89,8 @@ impl EOPManager {
         println!("Refreshing EOP data...");
 
         match self.download_eop_data() {
-            Ok(_) => {
-                self.parse_eop_data()?; // Parse new data only if download succeeded
+            Ok(data) => {
+                self.parse_eop_data_from_bytes(&data)?; // Parse new data only if download succeeded
                 self.last_update = Some(Utc::now());
                 println!("EOP data refreshed successfully.");
                 Ok(())
@@ -54,11 +99,19 @@ impl EOPManager {
         }
     }
 
-    /// Returns the EOP data for a given epoch. If the data is not available and if `refresh` is `true`
-    /// it will attempt to refresh the cache.
+    /// Returns the EOP data for a given epoch. If the cache cannot be updated, already loaded data
+    /// (e.g. the embedded data) is used instead; if there is none and `refresh` is `true` it will
+    /// attempt to refresh the cache.
     pub fn get_eop_data(&mut self, epoch: Epoch, refresh: bool) -> Result<EOPData, EOPErrors> {
         match self.update_cache_if_needed() {
             Ok(_) => self.interpolate_eop_data(epoch),
+            Err(e) if !self.eop_data.is_empty() => {
+                eprintln!(
+                    "Warning: Failed to update EOP data, using loaded data: {}",
+                    e
+                );
+                self.interpolate_eop_data(epoch)
+            }
             Err(e) => {
                 eprintln!("Warning: Failed to load cached EOP data: {}", e);
                 if refresh {
@@ -72,8 +125,8 @@ impl EOPManager {
         }
     }
 
-    /// Downloads the latest EOP data.
-    fn download_eop_data(&self) -> Result<(), EOPErrors> {
+    /// Downloads the latest EOP data, writing it to the cache file if caching is enabled.
+    fn download_eop_data(&self) -> Result<Vec<u8>, EOPErrors> {
         let client = reqwest::blocking::Client::new();
         let response = client.get(CELESTRAK_URL).send()?;
         let status = response.status();
@@ -88,18 +141,46 @@ impl EOPManager {
             ));
         }
 
-        fs::write(&self.cache_path, response.bytes()?)?;
-        Ok(())
+        let data = response.bytes()?.to_vec();
+        if let Some(cache_path) = &self.cache_path {
+            if let Some(cache_dir) = cache_path.parent() {
+                fs::create_dir_all(cache_dir)?;
+            }
+            fs::write(cache_path, &data)?;
+        }
+        Ok(data)
     }
 
     /// Loads the EOP data that was downloaded at compile time.
     fn load_embedded_data(&mut self) -> Result<(), EOPErrors> {
         let data = include_bytes!(concat!(env!("OUT_DIR"), "/eop_cache.csv")); // Use compile-time cached data
         self.parse_eop_data_from_bytes(data)?;
+        self.embedded = true;
         Ok(())
     }
 
     fn update_cache_if_needed(&mut self) -> Result<(), EOPErrors> {
+        let Some(cache_path) = &self.cache_path else {
+            // Without a cache only the embedded data is used
+            if self.eop_data.is_empty() {
+                return Err(EOPErrors::MissingEOPData);
+            }
+            return Ok(());
+        };
+
+        // A cache file written within the cache duration, e.g. by another process, is used
+        // as is
+        if self.last_update.is_none() {
+            let modified = fs::metadata(cache_path).and_then(|metadata| metadata.modified());
+            if let Ok(modified) = modified {
+                let modified = DateTime::<Utc>::from(modified);
+                if Utc::now() - modified <= Duration::hours(CACHE_DURATION_HOURS) {
+                    self.parse_eop_data()?;
+                    self.last_update = Some(modified);
+                }
+            }
+        }
+
         let should_update = match self.last_update {
             None => true,
             Some(last_update) => Utc::now() - last_update > Duration::hours(CACHE_DURATION_HOURS),
@@ -107,17 +188,18 @@ impl EOPManager {
 
         if should_update {
             println!("Updating EOP data cache...");
-            self.download_eop_data()?;
-            self.parse_eop_data()?;
+            let data = self.download_eop_data()?;
+            self.parse_eop_data_from_bytes(&data)?;
             self.last_update = Some(Utc::now());
         }
 
         Ok(())
     }
 
-    /// Parses EOP data from file.
+    /// Parses EOP data from the cache file.
     fn parse_eop_data(&mut self) -> Result<(), EOPErrors> {
-        let data = fs::read(&self.cache_path)?;
+        let cache_path = self.cache_path.as_ref().ok_or(EOPErrors::MissingEOPData)?;
+        let data = fs::read(cache_path)?;
         self.parse_eop_data_from_bytes(&data)
     }
 
@@ -154,27 +236,155 @@ impl EOPManager {
         Ok(())
     }
 
-    /// Interpolates EOP data for a given epoch.
+    /// Interpolates EOP data for a given epoch between the bracketing records. Outside the
+    /// loaded series this fails with `EOPErrors::OutOfRange`, which carries the nearest
+    /// record for callers that accept holding it constant.
     fn interpolate_eop_data(&self, epoch: Epoch) -> Result<EOPData, EOPErrors> {
         if self.eop_data.is_empty() {
             return Err(EOPErrors::MissingEOPData);
         }
 
         let target_time = epoch.to_unix_seconds() as i64;
-        let mut iter = self.eop_data.range(..=target_time);
-        let after = iter.next_back();
-        let before = iter.next_back();
+        let before = self.eop_data.range(..=target_time).next_back();
+        let after = self.eop_data.range(target_time..).next();
 
         match (before, after) {
             (Some((&t1, eop1)), Some((&t2, eop2))) => {
+                if t1 == t2 {
+                    return Ok(eop1.clone());
+                }
                 let fraction = (target_time - t1) as f64 / (t2 - t1) as f64;
                 Ok(EOPData::interpolate(eop1, eop2, fraction))
             }
-            (Some((_, eop)), None) | (None, Some((_, eop))) => {
-                println!("Warning: Using nearest EOP value without interpolation");
-                Ok(eop.clone())
-            }
+            (Some((_, eop)), None) | (None, Some((_, eop))) => Err(EOPErrors::OutOfRange {
+                epoch,
+                nearest: eop.clone(),
+            }),
             (None, None) => Err(EOPErrors::DataInterpolationError),
         }
     }
 }
+
+/// When the build fetched the embedded data, as recorded by the build script
+fn embedded_fetched_at() -> Option<DateTime<Utc>> {
+    let seconds = env!("KOSMOSS_EOP_FETCHED_AT").parse().ok()?;
+    DateTime::from_timestamp(seconds, 0)
+}
+
+/// Cache file location for a `KOSMOSS_EOP_CACHE` setting of `value`
+fn cache_path_from_env(value: Option<OsString>) -> Option<PathBuf> {
+    match value {
+        Some(value) if value == "off" || value == "none" => None,
+        Some(value) if !value.is_empty() => Some(PathBuf::from(value)),
+        _ => Some(
+            dirs::cache_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("kosmoss")
+                .join(CACHE_FILE),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_reads_data_from_configured_cache() {
+        let cache_path = std::env::temp_dir()
+            .join(format!("kosmoss_eop_{}", std::process::id()))
+            .join(CACHE_FILE);
+        fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+        fs::write(
+            &cache_path,
+            "DATE,X,Y,UT1-UTC,LOD,DPSI,DEPS\n\
+             2031-01-01,0.123,0.456,-0.0789,0.0011,-0.05,-0.004\n\
+             2031-01-02,0.123,0.456,-0.0789,0.0011,-0.05,-0.004\n",
+        )
+        .unwrap();
+
+        // The cache file is fresh, so it is read instead of downloading new data
+        let mut manager = EOPManager::with_cache_path(&cache_path);
+        manager.initialize().unwrap();
+        let epoch = Epoch::from_gregorian_utc(2031, 1, 2, 0, 0, 0, 0);
+        let eop = manager.get_eop_data(epoch, false).unwrap();
+        assert_relative_eq!(eop.x_pole, 0.123);
+        assert_relative_eq!(eop.y_pole, 0.456);
+        assert_relative_eq!(eop.ut1_utc, -0.0789);
+        assert!(manager.last_update.is_some());
+
+        // Freshly loaded, so its age is about the time since the file was written
+        assert!(manager.data_age().unwrap() < Duration::minutes(1));
+
+        fs::remove_dir_all(cache_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_epoch_past_cached_range() {
+        let mut manager = EOPManager::without_cache();
+        manager
+            .parse_eop_data_from_bytes(
+                b"DATE,X,Y,UT1-UTC,LOD,DPSI,DEPS\n\
+                  2031-01-01,0.100,0.400,-0.0700,0.0011,-0.05,-0.004\n\
+                  2031-01-02,0.200,0.500,-0.0800,0.0011,-0.05,-0.004\n",
+            )
+            .unwrap();
+
+        // Inside the range the bracketing records are interpolated
+        let midday = Epoch::from_gregorian_utc(2031, 1, 1, 12, 0, 0, 0);
+        let eop = manager.interpolate_eop_data(midday).unwrap();
+        assert_relative_eq!(eop.x_pole, 0.150, epsilon = 1e-12);
+        assert_relative_eq!(eop.ut1_utc, -0.0750, epsilon = 1e-12);
+
+        // Past the last record the lookup is flagged, but the last record is still usable
+        let later = Epoch::from_gregorian_utc(2031, 6, 1, 0, 0, 0, 0);
+        let error = manager.interpolate_eop_data(later).unwrap_err();
+        assert!(matches!(error, EOPErrors::OutOfRange { epoch, .. } if epoch == later));
+        let nearest = error.into_nearest().unwrap();
+        assert_relative_eq!(nearest.x_pole, 0.200);
+        assert_relative_eq!(nearest.ut1_utc, -0.0800);
+
+        // Other errors are not mistaken for an out-of-range lookup
+        assert!(EOPErrors::MissingEOPData.into_nearest().is_err());
+    }
+
+    #[test]
+    fn test_cache_setting_from_env() {
+        assert_eq!(cache_path_from_env(Some("off".into())), None);
+        assert_eq!(cache_path_from_env(Some("none".into())), None);
+        assert_eq!(
+            cache_path_from_env(Some("/srv/eop.csv".into())),
+            Some(PathBuf::from("/srv/eop.csv"))
+        );
+        assert!(cache_path_from_env(None)
+            .unwrap()
+            .ends_with("kosmoss/eop_cache.csv"));
+
+        // Without a cache the embedded data is served and nothing is written
+        let mut manager = EOPManager::without_cache();
+        manager.initialize().unwrap();
+        assert!(manager.update_cache_if_needed().is_ok());
+        assert!(manager.last_update.is_none());
+    }
+
+    #[test]
+    fn test_data_age() {
+        let mut manager = EOPManager::without_cache();
+        manager.initialize().unwrap();
+
+        // Embedded data was never updated, and dates from the build that fetched it
+        assert_eq!(manager.last_update(), None);
+        let fetched_at = embedded_fetched_at().unwrap();
+        assert_eq!(manager.fetched_at(), Some(fetched_at));
+        assert!(fetched_at <= Utc::now());
+        assert!(manager.data_age().unwrap() >= Duration::zero());
+
+        // A cache loaded a month ago
+        let loaded = Utc::now() - Duration::days(30);
+        manager.last_update = Some(loaded);
+        assert_eq!(manager.last_update(), Some(loaded));
+        let age = manager.data_age().unwrap();
+        assert!(age >= Duration::days(30) && age < Duration::days(30) + Duration::minutes(1));
+    }
+}
@@ -0,0 +1,116 @@
+use super::coordinate_transformation::{eop_data_age, EOPData};
+use super::eop_errors::EOPErrors;
+use chrono::Duration;
+use hifitime::Epoch;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Source of Earth orientation parameters for frame transformations
+pub trait EOPProvider {
+    fn eop_data(&self, epoch: Epoch) -> Result<EOPData, EOPErrors>;
+
+    /// Time since the data was last updated, where the provider knows it
+    fn data_age(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Lets several consumers share one provider, and so one EOP series
+impl<P: EOPProvider + ?Sized> EOPProvider for Rc<P> {
+    fn eop_data(&self, epoch: Epoch) -> Result<EOPData, EOPErrors> {
+        (**self).eop_data(epoch)
+    }
+
+    fn data_age(&self) -> Option<Duration> {
+        (**self).data_age()
+    }
+}
+
+/// Looks up EOP through the shared EOP manager, fetching and caching data as needed. Epochs
+/// outside the loaded series get the nearest record, with a warning, unless `strict` is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ManagedEOP {
+    /// Fail with `EOPErrors::OutOfRange` instead of holding the nearest record
+    pub strict: bool,
+}
+
+/// Whether the out-of-range warning was printed, so long runs past the data print it once
+static OUT_OF_RANGE_WARNED: AtomicBool = AtomicBool::new(false);
+
+impl ManagedEOP {
+    /// A provider that fails for epochs outside the loaded series
+    pub fn strict() -> Self {
+        Self { strict: true }
+    }
+
+    /// Applies the out-of-range policy to a lookup in the EOP series
+    fn resolve(&self, lookup: Result<EOPData, EOPErrors>) -> Result<EOPData, EOPErrors> {
+        match lookup {
+            Err(e @ EOPErrors::OutOfRange { .. }) if !self.strict => {
+                if !OUT_OF_RANGE_WARNED.swap(true, Ordering::Relaxed) {
+                    eprintln!("Warning: {}, using the nearest record", e);
+                }
+                e.into_nearest()
+            }
+            result => result,
+        }
+    }
+}
+
+impl EOPProvider for ManagedEOP {
+    fn eop_data(&self, epoch: Epoch) -> Result<EOPData, EOPErrors> {
+        self.resolve(EOPData::try_from(epoch))
+    }
+
+    fn data_age(&self) -> Option<Duration> {
+        eop_data_age()
+    }
+}
+
+/// Returns the same EOP for every epoch, for reproducible runs that must not depend on
+/// downloaded data
+#[derive(Clone, Default)]
+pub struct StaticEOP {
+    pub eop: EOPData,
+}
+
+impl StaticEOP {
+    pub fn new(eop: EOPData) -> Self {
+        Self { eop }
+    }
+}
+
+impl EOPProvider for StaticEOP {
+    fn eop_data(&self, _epoch: Epoch) -> Result<EOPData, EOPErrors> {
+        Ok(self.eop.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinates::eop_manager::EOPManager;
+
+    #[test]
+    fn test_managed_eop_past_loaded_range() {
+        let epoch = Epoch::from_gregorian_utc(2200, 1, 1, 0, 0, 0, 0);
+
+        // A manager of its own, serving the embedded data, so the test neither depends on
+        // nor disturbs the shared one
+        let mut manager = EOPManager::without_cache();
+        manager.initialize().unwrap();
+        assert!(matches!(
+            manager.get_eop_data(epoch, false),
+            Err(EOPErrors::OutOfRange { .. })
+        ));
+
+        // By default the nearest record is held, so a long run does not abort
+        let lookup = manager.get_eop_data(epoch, false);
+        assert!(ManagedEOP::default().resolve(lookup).is_ok());
+
+        // The strict provider reports the gap instead
+        let lookup = manager.get_eop_data(epoch, false);
+        let error = ManagedEOP::strict().resolve(lookup).unwrap_err();
+        assert!(matches!(error, EOPErrors::OutOfRange { .. }));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod coordinate_transformation;
 pub mod eop_errors;
 pub mod eop_manager;
+pub mod eop_provider;
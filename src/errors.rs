@@ -0,0 +1,72 @@
+use crate::coordinates::eop_errors::EOPErrors;
+use std::{error::Error, fmt, io};
+
+/// Failure modes of a simulation run
+#[derive(Debug)]
+pub enum KosmossError {
+    Eop(EOPErrors),
+    Io(io::Error),
+    Csv(csv::Error),
+    /// The spacecraft passed below the WGS84 ellipsoid
+    OrbitDecayed {
+        time: f64,     // s since start
+        altitude: f64, // m, negative
+    },
+    /// The propagated orbit stopped being finite, typically from a step size too large for
+    /// the dynamics
+    NumericalDivergence {
+        time: f64,
+    },
+    /// A scenario setting outside its valid range
+    InvalidConfig(String),
+}
+
+impl fmt::Display for KosmossError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KosmossError::Eop(e) => write!(f, "EOP error: {}", e),
+            KosmossError::Io(e) => write!(f, "I/O error: {}", e),
+            KosmossError::Csv(e) => write!(f, "CSV error: {}", e),
+            KosmossError::OrbitDecayed { time, altitude } => write!(
+                f,
+                "Orbit decayed at t={:.2}s ({:.0} m below the surface)",
+                time, -altitude
+            ),
+            KosmossError::NumericalDivergence { time } => {
+                write!(f, "State diverged to a non-finite value at t={:.2}s", time)
+            }
+            KosmossError::InvalidConfig(reason) => write!(f, "Invalid configuration: {}", reason),
+        }
+    }
+}
+
+impl Error for KosmossError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            KosmossError::Eop(e) => Some(e),
+            KosmossError::Io(e) => Some(e),
+            KosmossError::Csv(e) => Some(e),
+            KosmossError::OrbitDecayed { .. }
+            | KosmossError::NumericalDivergence { .. }
+            | KosmossError::InvalidConfig(_) => None,
+        }
+    }
+}
+
+impl From<EOPErrors> for KosmossError {
+    fn from(err: EOPErrors) -> Self {
+        KosmossError::Eop(err)
+    }
+}
+
+impl From<io::Error> for KosmossError {
+    fn from(err: io::Error) -> Self {
+        KosmossError::Io(err)
+    }
+}
+
+impl From<csv::Error> for KosmossError {
+    fn from(err: csv::Error) -> Self {
+        KosmossError::Csv(err)
+    }
+}
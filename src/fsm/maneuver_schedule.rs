@@ -0,0 +1,223 @@
+use crate::gnc::guidance::hohmann::ApsisType;
+use crate::gnc::guidance::impulsive::ImpulsiveBurn;
+use crate::gnc::guidance::Guidance;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::physics::orbital::OrbitalMechanics;
+use std::collections::VecDeque;
+
+/// Radial distance from an apsis within which an apsis trigger fires [m]
+const APSIS_TRIGGER_TOLERANCE: f64 = 100.0;
+
+/// Condition under which a scheduled maneuver may be commanded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManeuverTrigger {
+    /// At the first step at or after this mission elapsed time [s]
+    Time(f64),
+    /// At the first pass through `apsis` at or after `after` seconds
+    Apsis { apsis: ApsisType, after: f64 },
+}
+
+impl ManeuverTrigger {
+    /// Earliest mission elapsed time at which the trigger can fire
+    pub fn earliest_time(&self) -> f64 {
+        match *self {
+            ManeuverTrigger::Time(time) => time,
+            ManeuverTrigger::Apsis { after, .. } => after,
+        }
+    }
+
+    pub fn is_met<T: SpacecraftProperties>(&self, state: &State<T>) -> bool {
+        if state.mission_elapsed_time < self.earliest_time() {
+            return false;
+        }
+        match *self {
+            ManeuverTrigger::Time(_) => true,
+            ManeuverTrigger::Apsis { apsis, .. } => {
+                let (at_apogee, at_perigee) = OrbitalMechanics::is_near_apsis(
+                    &state.position,
+                    &state.velocity,
+                    APSIS_TRIGGER_TOLERANCE,
+                );
+                match apsis {
+                    ApsisType::Apogee => at_apogee,
+                    ApsisType::Perigee => at_perigee,
+                }
+            }
+        }
+    }
+}
+
+/// One burn of a mission plan
+pub struct Maneuver {
+    pub trigger: ManeuverTrigger,
+    /// Guidance for this burn; `None` flies the simulation's own guidance
+    pub guidance: Option<Guidance>,
+    /// Applied in full at the start of the burn, which then ends at once; the guidance is
+    /// not flown
+    pub impulse: Option<ImpulsiveBurn>,
+    /// Longest time spent maneuvering before the burn is ended and the FSM returns to
+    /// nominal operation [s]. The burn also ends as soon as the guidance reports its target
+    /// reached. Infinite by default, so only the target or an FSM fault ends it.
+    pub duration: f64,
+}
+
+impl Maneuver {
+    pub fn new(trigger: ManeuverTrigger) -> Self {
+        Self {
+            trigger,
+            guidance: None,
+            impulse: None,
+            duration: f64::INFINITY,
+        }
+    }
+
+    pub fn at(time: f64) -> Self {
+        Self::new(ManeuverTrigger::Time(time))
+    }
+
+    pub fn with_guidance(self, guidance: impl Into<Guidance>) -> Self {
+        Self {
+            guidance: Some(guidance.into()),
+            ..self
+        }
+    }
+
+    pub fn with_impulse(self, impulse: ImpulsiveBurn) -> Self {
+        Self {
+            impulse: Some(impulse),
+            ..self
+        }
+    }
+
+    pub fn with_duration(self, duration: f64) -> Self {
+        Self { duration, ..self }
+    }
+}
+
+/// Time-ordered queue of maneuvers. The driver issues the front maneuver to the FSM once
+/// its trigger is met and no other maneuver is in progress.
+#[derive(Default)]
+pub struct ManeuverSchedule {
+    queue: VecDeque<Maneuver>,
+    active: Option<Maneuver>,
+    commanded: Vec<f64>,
+}
+
+impl ManeuverSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `maneuver` after every maneuver that can fire no later than it
+    pub fn push(&mut self, maneuver: Maneuver) {
+        let earliest = maneuver.trigger.earliest_time();
+        let index = self
+            .queue
+            .iter()
+            .position(|queued| queued.trigger.earliest_time() > earliest)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(index, maneuver);
+    }
+
+    /// Maneuvers still waiting to be commanded
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// The maneuver being flown, from the moment it was commanded until it ends
+    pub fn active(&self) -> Option<&Maneuver> {
+        self.active.as_ref()
+    }
+
+    /// The maneuver being flown, e.g. to update the state of its guidance
+    pub fn active_mut(&mut self) -> Option<&mut Maneuver> {
+        self.active.as_mut()
+    }
+
+    /// Mission elapsed times at which maneuvers were commanded, in order
+    pub fn commanded_times(&self) -> &[f64] {
+        &self.commanded
+    }
+
+    /// True when nothing is in progress and the front maneuver's trigger is met
+    pub fn is_due<T: SpacecraftProperties>(&self, state: &State<T>) -> bool {
+        self.active.is_none()
+            && self
+                .queue
+                .front()
+                .is_some_and(|maneuver| maneuver.trigger.is_met(state))
+    }
+
+    /// Makes the front maneuver active, once the FSM has accepted it at `time`
+    pub fn start_next(&mut self, time: f64) -> Option<&Maneuver> {
+        self.active = self.queue.pop_front();
+        if self.active.is_some() {
+            self.commanded.push(time);
+        }
+        self.active.as_ref()
+    }
+
+    /// Ends the active maneuver, whether it completed or was abandoned
+    pub fn finish_active(&mut self) -> Option<Maneuver> {
+        self.active.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::{PI, WGS84_A};
+    use crate::numerics::quaternion::Quaternion;
+    use hifitime::Epoch;
+    use nalgebra as na;
+
+    #[test]
+    fn test_queue_is_time_ordered_and_apsis_triggers_wait_for_the_apsis() {
+        let mut schedule = ManeuverSchedule::new();
+        schedule.push(Maneuver::at(300.0));
+        schedule.push(Maneuver::new(ManeuverTrigger::Apsis {
+            apsis: ApsisType::Apogee,
+            after: 100.0,
+        }));
+        schedule.push(Maneuver::at(200.0));
+        let order: Vec<f64> = schedule
+            .queue
+            .iter()
+            .map(|maneuver| maneuver.trigger.earliest_time())
+            .collect();
+        assert_eq!(order, vec![100.0, 200.0, 300.0]);
+
+        // An eccentric orbit at perigee, then at apogee
+        let spacecraft = SimpleSat::default();
+        let state_at = |true_anomaly: f64, time: f64| {
+            let elements =
+                na::Vector6::new(WGS84_A + 2_000_000.0, 0.1, 0.5, 0.0, 0.0, true_anomaly);
+            let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+            let mut state = State::new(
+                &spacecraft,
+                SimpleSat::inertia_tensor(),
+                position,
+                velocity,
+                Quaternion::new(1.0, 0.0, 0.0, 0.0),
+                na::Vector3::zeros(),
+                Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+            );
+            state.set_mission_elapsed_time(time);
+            state
+        };
+
+        assert!(!schedule.is_due(&state_at(0.0, 150.0)));
+        assert!(!schedule.is_due(&state_at(PI, 50.0)));
+        assert!(schedule.is_due(&state_at(PI, 150.0)));
+
+        // Nothing else is due while a maneuver is active
+        schedule.start_next(150.0);
+        assert!(!schedule.is_due(&state_at(0.0, 250.0)));
+        schedule.finish_active();
+        assert!(schedule.is_due(&state_at(0.0, 250.0)));
+        assert_eq!(schedule.pending(), 2);
+        assert_eq!(schedule.commanded_times(), &[150.0]);
+    }
+}
@@ -1,2 +1,3 @@
+pub mod maneuver_schedule;
 pub mod spacecraft_states;
 pub mod state_machine;
@@ -1,4 +1,5 @@
 use super::spacecraft_states::SpacecraftState;
+use crate::constants::TUMBLING_RATE_THRESHOLD;
 use crate::models::spacecraft::SpacecraftProperties;
 use crate::models::State as VehicleState;
 
@@ -14,7 +15,7 @@ impl SpacecraftFSM {
     pub fn new() -> Self {
         Self {
             current_state: SpacecraftState::SafeMode,
-            angular_velocity_threshold: 0.01,
+            angular_velocity_threshold: TUMBLING_RATE_THRESHOLD,
             emergency_angular_velocity: 0.5,
             last_state_change: 0.0,
             last_message_time: -1.0,
@@ -116,11 +117,24 @@ impl SpacecraftFSM {
         }
     }
 
+    /// Ends a burn and returns to nominal operation. Only a maneuver in progress can be
+    /// completed; the return value says whether one was.
+    pub fn complete_maneuver(&mut self, time: f64) -> bool {
+        if self.current_state == SpacecraftState::Maneuvering {
+            self.transition_to(SpacecraftState::NominalOperation, time);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn should_apply_control(&self) -> bool {
-        !matches!(
-            self.current_state,
-            SpacecraftState::SafeMode | SpacecraftState::Emergency
-        )
+        !matches!(self.current_state, SpacecraftState::Emergency)
+    }
+
+    /// Safe mode holds the solar panels on the Sun instead of tracking the orbit frame
+    pub fn should_point_at_sun(&self) -> bool {
+        matches!(self.current_state, SpacecraftState::SafeMode)
     }
 
     pub fn should_apply_thrust(&self) -> bool {
@@ -131,3 +145,9 @@ impl SpacecraftFSM {
         self.last_state_change
     }
 }
+
+impl Default for SpacecraftFSM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
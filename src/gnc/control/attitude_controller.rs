@@ -1,64 +1,259 @@
+use crate::coordinates::coordinate_transformation::{
+    gcrs_to_rsw_rotation, geodetic_normal, try_gcrs_to_rsw_rotation,
+};
+use crate::gnc::guidance::attitude_profile::AttitudeProfile;
+use crate::models::spacecraft::SpacecraftProperties;
 use crate::numerics::quaternion::Quaternion;
 use nalgebra as na;
 
+/// Actuator saturation limit applied to the commanded torque
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TorqueLimit {
+    /// Limit on the magnitude of the torque vector [N⋅m]
+    Magnitude(f64),
+    /// Independent limit on each body axis, e.g. for mismatched reaction wheels [N⋅m]
+    PerAxis(na::Vector3<f64>),
+}
+
+impl Default for TorqueLimit {
+    fn default() -> Self {
+        TorqueLimit::Magnitude(1.0)
+    }
+}
+
+/// Direction taken as local vertical when pointing at nadir
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NadirReference {
+    /// Along the radius vector, towards the centre of the Earth
+    #[default]
+    Geocentric,
+    /// Along the normal to the WGS84 ellipsoid, for payloads that must look straight down.
+    /// The normal is taken from the GCRS position; the ellipsoid is symmetric about the polar
+    /// axis, which GCRS and ITRS share to within precession and polar motion.
+    Geodetic,
+}
+
 pub struct GeometricAttitudeController {
     kp: f64,
     kd: f64,
     inertia: na::Matrix3<f64>,
+    torque_limit: TorqueLimit,
+    nadir_reference: NadirReference,
+    held_frame: Option<na::Matrix3<f64>>, // last nadir frame tracked
 }
 
 impl GeometricAttitudeController {
     pub fn new(kp: f64, kd: f64, inertia: na::Matrix3<f64>) -> Self {
-        Self { kp, kd, inertia }
+        Self::with_torque_limit(kp, kd, inertia, TorqueLimit::default())
+    }
+
+    pub fn with_torque_limit(
+        kp: f64,
+        kd: f64,
+        inertia: na::Matrix3<f64>,
+        torque_limit: TorqueLimit,
+    ) -> Self {
+        Self {
+            kp,
+            kd,
+            inertia,
+            torque_limit,
+            nadir_reference: NadirReference::default(),
+            held_frame: None,
+        }
+    }
+
+    /// Controller limited to the torque the spacecraft's own actuators can deliver
+    pub fn for_spacecraft<T: SpacecraftProperties>(
+        kp: f64,
+        kd: f64,
+        inertia: na::Matrix3<f64>,
+        spacecraft: &T,
+    ) -> Self {
+        Self::with_torque_limit(
+            kp,
+            kd,
+            inertia,
+            TorqueLimit::Magnitude(spacecraft.max_control_torque()),
+        )
+    }
+
+    pub fn with_nadir_reference(self, nadir_reference: NadirReference) -> Self {
+        Self {
+            nadir_reference,
+            ..self
+        }
     }
 
+    /// Torque that tracks the nadir frame, remembering the frame so that it can be held
+    /// through purely radial motion
     pub fn compute_control_torque(
+        &mut self,
+        r_gcrs: &na::Vector3<f64>,
+        v_gcrs: &na::Vector3<f64>,
+        q_gcrs2body: &Quaternion,
+        w_body: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        let nadir_frame = self.nadir_frame(r_gcrs, v_gcrs);
+        self.held_frame = Some(nadir_frame);
+        self.compute_tracking_torque(
+            &nadir_frame,
+            &Self::desired_body_rate(r_gcrs, v_gcrs),
+            q_gcrs2body,
+            w_body,
+        )
+    }
+
+    /// Desired body axes (as columns in GCRS) for nadir pointing: the RSW frame, with the
+    /// radial axis along the selected local vertical and the cross-track axis normal to it
+    /// and the velocity. Purely radial motion has no such frame, so the last frame tracked
+    /// is held instead.
+    pub fn nadir_frame(
         &self,
         r_gcrs: &na::Vector3<f64>,
         v_gcrs: &na::Vector3<f64>,
+    ) -> na::Matrix3<f64> {
+        let vertical = match self.nadir_reference {
+            NadirReference::Geocentric => *r_gcrs,
+            NadirReference::Geodetic => geodetic_normal(r_gcrs),
+        };
+        try_gcrs_to_rsw_rotation(&vertical, v_gcrs)
+            .or(self.held_frame)
+            .unwrap_or_else(|| gcrs_to_rsw_rotation(&vertical, v_gcrs))
+    }
+
+    /// Body rate that keeps the body axes aligned with the rotating RSW frame, expressed in
+    /// that frame: the instantaneous orbital rate |r × v| / r² about the orbit normal
+    pub fn desired_body_rate(
+        r_gcrs: &na::Vector3<f64>,
+        v_gcrs: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        let orbital_rate = r_gcrs.cross(v_gcrs).magnitude() / r_gcrs.magnitude_squared();
+        na::Vector3::new(0.0, 0.0, orbital_rate)
+    }
+
+    /// Single-axis pointing for safe mode: turns the body axis `axis_body` onto the unit
+    /// vector `sun_gcrs` by the smallest rotation, leaving the rotation about the Sun line
+    /// free, and brings the body to rest
+    pub fn compute_sun_pointing_torque(
+        &self,
+        sun_gcrs: &na::Vector3<f64>,
+        axis_body: &na::Vector3<f64>,
         q_gcrs2body: &Quaternion,
         w_body: &na::Vector3<f64>,
     ) -> na::Vector3<f64> {
-        // Get desired RSW frame
-        let r_unit = r_gcrs.normalize();
-        let h = r_gcrs.cross(v_gcrs);
-        let w_unit = h.normalize();
-        let s_unit = w_unit.cross(&r_unit);
+        self.compute_tracking_torque(
+            &Self::sun_pointing_frame(sun_gcrs, axis_body, q_gcrs2body),
+            &na::Vector3::zeros(),
+            q_gcrs2body,
+            w_body,
+        )
+    }
+
+    /// Desired body axes (as columns in GCRS) for safe-mode Sun pointing: the current
+    /// attitude turned by the smallest rotation that puts `axis_body` on `sun_gcrs`
+    pub fn sun_pointing_frame(
+        sun_gcrs: &na::Vector3<f64>,
+        axis_body: &na::Vector3<f64>,
+        q_gcrs2body: &Quaternion,
+    ) -> na::Matrix3<f64> {
+        let r_current = q_gcrs2body.to_rotation_matrix();
+        let axis_gcrs = r_current * axis_body.normalize();
 
-        let r_gcrs2rsw = na::Matrix3::from_columns(&[r_unit, s_unit, w_unit]);
+        rotation_between(&axis_gcrs, &sun_gcrs.normalize()) * r_current
+    }
 
+    /// Computes the torque that tracks a slew reference `t` seconds into the profile
+    pub fn compute_profile_torque(
+        &self,
+        profile: &AttitudeProfile,
+        t: f64,
+        q_gcrs2body: &Quaternion,
+        w_body: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        let (q_desired, w_desired) = profile.sample(t);
+
+        self.compute_tracking_torque(
+            &q_desired.to_rotation_matrix(),
+            &w_desired,
+            q_gcrs2body,
+            w_body,
+        )
+    }
+
+    /// Geometric tracking law on SO(3) for an arbitrary reference attitude `r_desired`
+    /// (desired body axes as columns in GCRS) and reference body rate `w_desired`,
+    /// expressed in the desired body frame
+    pub fn compute_tracking_torque(
+        &self,
+        r_desired: &na::Matrix3<f64>,
+        w_desired: &na::Vector3<f64>,
+        q_gcrs2body: &Quaternion,
+        w_body: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
         // Current rotation matrix
         let r_current = q_gcrs2body.to_rotation_matrix();
 
         // Compute attitude error in SO(3)
-        let r_error = r_current.transpose() * r_gcrs2rsw;
+        let r_error = r_current.transpose() * r_desired;
         let e = (r_error.transpose() - r_error) * 0.5;
 
         // Extract vector form of error
         let e_r = na::Vector3::new(e[(2, 1)], e[(0, 2)], e[(1, 0)]);
 
-        // Compute desired angular velocity
-        let orbital_rate = v_gcrs.magnitude() / r_gcrs.magnitude();
-        let w_desired =
-            r_current.transpose() * r_gcrs2rsw * na::Vector3::new(0.0, 0.0, -orbital_rate);
-
-        // Angular velocity error
-        let e_w = w_body - w_desired;
+        // Angular velocity error, with the reference rate brought into the current body frame
+        let e_w = w_body - r_error * w_desired;
 
         // Geometric control law on SO(3)
-        let mut control_torque = self.inertia * (-self.kp * e_r - self.kd * e_w);
+        let control_torque = self.inertia * (-self.kp * e_r - self.kd * e_w);
+
+        self.saturate(control_torque)
+    }
 
-        // Smooth saturation function
-        let max_torque = 1.0; // N⋅m
-        let torque_mag = control_torque.magnitude();
+    /// Smooth saturation that scales the whole torque vector, so the commanded direction is
+    /// kept while every limited quantity stays below its bound
+    fn saturate(&self, torque: na::Vector3<f64>) -> na::Vector3<f64> {
+        // Demand as a fraction of the limit, taken on the most heavily loaded axis when the
+        // axes are limited independently
+        let load = match self.torque_limit {
+            TorqueLimit::Magnitude(max_torque) => torque.magnitude() / max_torque,
+            TorqueLimit::PerAxis(max_torque) => torque.abs().component_div(&max_torque).max(),
+        };
 
-        if torque_mag > max_torque {
-            let scale = max_torque * (1.0 - (-torque_mag / max_torque).exp()) / torque_mag;
-            control_torque *= scale;
+        if load > 1.0 {
+            torque * ((1.0 - (-load).exp()) / load)
+        } else {
+            torque
         }
+    }
+}
+
+/// Angle of the rotation between the current and desired attitudes, in degrees: what an
+/// operator reads as the pointing error, whichever axis it is about
+pub fn pointing_error_angle(q_current: &Quaternion, q_desired: &Quaternion) -> f64 {
+    let error = q_current.conjugate().multiply(q_desired);
 
-        control_torque
+    // q and -q are the same attitude, so the shorter way round is taken
+    (2.0 * error.vector().magnitude().atan2(error.scalar().abs())).to_degrees()
+}
+
+/// Smallest rotation taking the unit vector `from` onto the unit vector `to`
+fn rotation_between(from: &na::Vector3<f64>, to: &na::Vector3<f64>) -> na::Matrix3<f64> {
+    let cos_angle = from.dot(to);
+    if cos_angle < -1.0 + 1e-12 {
+        // Opposite vectors: turn half a revolution about any perpendicular axis
+        let helper = if from.x.abs() < 0.9 {
+            na::Vector3::x()
+        } else {
+            na::Vector3::y()
+        };
+        let axis = from.cross(&helper).normalize();
+        return axis * axis.transpose() * 2.0 - na::Matrix3::identity();
     }
+
+    // Rodrigues' formula with the sine folded into the cross product
+    let k = from.cross(to).cross_matrix();
+    na::Matrix3::identity() + k + k * k / (1.0 + cos_angle)
 }
 
 #[cfg(test)]
@@ -68,15 +263,62 @@ mod tests {
     use std::f64::consts::PI;
 
     #[test]
-    #[ignore = "TODO: FIX"]
+    fn test_pointing_error_angle() {
+        let desired = Quaternion::new(0.8, 0.2, -0.4, 0.4).normalize();
+
+        // 30° off about an arbitrary axis reads as 30°, as does the same attitude given by -q
+        let axis = na::Vector3::new(1.0, 2.0, -2.0).normalize();
+        let half = 15.0_f64.to_radians();
+        let offset = Quaternion::new(
+            half.cos(),
+            axis.x * half.sin(),
+            axis.y * half.sin(),
+            axis.z * half.sin(),
+        );
+        let current = desired.multiply(&offset);
+        assert_relative_eq!(
+            pointing_error_angle(&current, &desired),
+            30.0,
+            epsilon = 1e-12
+        );
+        let flipped = Quaternion {
+            data: -current.data,
+        };
+        assert_relative_eq!(
+            pointing_error_angle(&flipped, &desired),
+            30.0,
+            epsilon = 1e-12
+        );
+
+        assert_relative_eq!(
+            pointing_error_angle(&desired, &desired),
+            0.0,
+            epsilon = 1e-12
+        );
+
+        // The error the tracking law sees: a body rotated 30° about z from the RSW frame
+        let controller = GeometricAttitudeController::new(1.0, 0.1, na::Matrix3::identity());
+        let r = na::Vector3::new(7000.0e3, 0.0, 0.0);
+        let v = na::Vector3::new(0.0, 7.8e3, 0.0);
+        let nadir = Quaternion::from_rotation_matrix(&controller.nadir_frame(&r, &v));
+        let current = Quaternion::new((PI / 12.0).cos(), 0.0, 0.0, (PI / 12.0).sin());
+        assert_relative_eq!(
+            pointing_error_angle(&current, &nadir),
+            30.0,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
     fn test_zero_error_case() {
         let inertia = na::Matrix3::identity();
-        let controller = GeometricAttitudeController::new(1.0, 0.1, inertia);
+        let mut controller = GeometricAttitudeController::new(1.0, 0.1, inertia);
 
         let r = na::Vector3::new(7000.0e3, 0.0, 0.0);
         let v = na::Vector3::new(0.0, 7.8e3, 0.0);
         let q = Quaternion::new(1.0, 0.0, 0.0, 0.0);
-        let w = na::Vector3::zeros();
+        // Aligned with RSW and turning with it
+        let w = GeometricAttitudeController::desired_body_rate(&r, &v);
 
         let torque = controller.compute_control_torque(&r, &v, &q, &w);
 
@@ -85,10 +327,94 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "TODO: FIX"]
+    fn test_desired_body_rate_circular_orbit() {
+        let radius = crate::constants::WGS84_A + 500_000.0;
+        let speed = crate::physics::orbital::OrbitalMechanics::compute_circular_velocity(radius);
+        let mean_motion =
+            2.0 * PI / crate::physics::orbital::OrbitalMechanics::compute_orbital_period(radius);
+
+        // Inclined circular orbit: the rate is the mean motion about the RSW W axis, whatever
+        // the orientation of the orbit plane
+        let r = na::Vector3::new(0.0, radius * 0.6, radius * 0.8);
+        let v = na::Vector3::new(speed, 0.0, 0.0);
+        let w_desired = GeometricAttitudeController::desired_body_rate(&r, &v);
+        assert_relative_eq!(
+            w_desired,
+            na::Vector3::new(0.0, 0.0, mean_motion),
+            max_relative = 1e-12
+        );
+
+        // In GCRS that is the orbit-normal direction r × v
+        let w_gcrs = gcrs_to_rsw_rotation(&r, &v) * w_desired;
+        assert_relative_eq!(
+            w_gcrs,
+            r.cross(&v).normalize() * mean_motion,
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_reference_rate_follows_rsw_frame() {
+        // The RSW frame of a circular equatorial orbit, differentiated numerically, turns
+        // about +W at the mean motion
+        let radius: f64 = 7000.0e3;
+        let mean_motion = (crate::constants::MU_EARTH / radius.powi(3)).sqrt();
+        let rsw_frame = |t: f64| {
+            let (sin, cos) = (mean_motion * t).sin_cos();
+            let r = na::Vector3::new(cos, sin, 0.0) * radius;
+            let v = na::Vector3::new(-sin, cos, 0.0) * radius * mean_motion;
+            (r, v, gcrs_to_rsw_rotation(&r, &v))
+        };
+        let h = 1.0;
+        let (r, v, frame) = rsw_frame(0.0);
+        let frame_rate = (rsw_frame(h).2 - rsw_frame(-h).2) / (2.0 * h);
+        let skew = frame.transpose() * frame_rate;
+        let w_frame = na::Vector3::new(skew[(2, 1)], skew[(0, 2)], skew[(1, 0)]);
+        assert!(w_frame.z > 0.0);
+
+        let w_desired = GeometricAttitudeController::desired_body_rate(&r, &v);
+        // Up to the truncation error of the central difference
+        assert_relative_eq!(w_desired, w_frame, max_relative = 1e-6);
+
+        // A body aligned with the frame, which at t = 0 is GCRS itself, and turning with it
+        // needs no torque
+        assert_relative_eq!(frame, na::Matrix3::identity(), epsilon = 1e-12);
+        let mut controller = GeometricAttitudeController::new(1.0, 0.1, na::Matrix3::identity());
+        let q = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let torque = controller.compute_control_torque(&r, &v, &q, &w_frame);
+        assert_relative_eq!(torque.magnitude(), 0.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_radial_motion_holds_last_nadir_frame() {
+        let mut controller = GeometricAttitudeController::new(1.0, 0.1, na::Matrix3::identity());
+        let q = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let w = na::Vector3::zeros();
+        let r = na::Vector3::new(7000.0e3, 0.0, 0.0);
+        let v = na::Vector3::new(0.0, 7.8e3, 0.0);
+        controller.compute_control_torque(&r, &v, &q, &w);
+        let held = controller.nadir_frame(&r, &v);
+
+        // Falling straight down further along, where there is no orbit plane to follow
+        let r_fall = na::Vector3::new(6000.0e3, 600.0e3, 0.0);
+        let v_fall = r_fall * -1e-4;
+        assert_eq!(controller.nadir_frame(&r_fall, &v_fall), held);
+        assert_relative_eq!(
+            controller.compute_control_torque(&r_fall, &v_fall, &q, &w),
+            controller.compute_tracking_torque(&held, &na::Vector3::zeros(), &q, &w),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
     fn test_90_degree_error() {
         let inertia = na::Matrix3::identity();
-        let controller = GeometricAttitudeController::new(1.0, 0.1, inertia);
+        let mut controller = GeometricAttitudeController::with_torque_limit(
+            1.0,
+            0.1,
+            inertia,
+            TorqueLimit::Magnitude(0.001),
+        );
 
         let r = na::Vector3::new(7000.0e3, 0.0, 0.0);
         let v = na::Vector3::new(0.0, 7.8e3, 0.0);
@@ -99,9 +425,171 @@ mod tests {
 
         let torque = controller.compute_control_torque(&r, &v, &q, &w);
 
-        // Should produce non-zero torque for misalignment
-        assert!(torque.magnitude() > 0.0);
+        // Should turn the body back about -Z
+        assert!(torque.z < 0.0);
         // Should not exceed maximum torque
         assert!(torque.magnitude() <= 0.001);
     }
+
+    #[test]
+    fn test_per_axis_torque_limits() {
+        let inertia = na::Matrix3::identity();
+        let limits = na::Vector3::new(0.5, 0.05, 0.2);
+        let controller = GeometricAttitudeController::with_torque_limit(
+            100.0,
+            0.0,
+            inertia,
+            TorqueLimit::PerAxis(limits),
+        );
+
+        // A large error about a skewed axis demands far more than any axis can deliver
+        let axis = na::Vector3::new(1.0, 2.0, -3.0).normalize();
+        let half_angle: f64 = 0.4;
+        let q = Quaternion::new(
+            half_angle.cos(),
+            axis.x * half_angle.sin(),
+            axis.y * half_angle.sin(),
+            axis.z * half_angle.sin(),
+        );
+        let unsaturated = GeometricAttitudeController::with_torque_limit(
+            100.0,
+            0.0,
+            inertia,
+            TorqueLimit::PerAxis(na::Vector3::repeat(f64::INFINITY)),
+        )
+        .compute_tracking_torque(
+            &na::Matrix3::identity(),
+            &na::Vector3::zeros(),
+            &q,
+            &na::Vector3::zeros(),
+        );
+        let torque = controller.compute_tracking_torque(
+            &na::Matrix3::identity(),
+            &na::Vector3::zeros(),
+            &q,
+            &na::Vector3::zeros(),
+        );
+
+        // Every component respects its own limit, and the tightest axis is nearly saturated
+        for i in 0..3 {
+            assert!(
+                torque[i].abs() <= limits[i] + 1e-12,
+                "axis {i}: {}",
+                torque[i]
+            );
+        }
+        assert!(torque.y.abs() > 0.9 * limits.y);
+
+        // The saturated command still points along the unsaturated one
+        assert_relative_eq!(torque.normalize(), unsaturated.normalize(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_rotation_between_unit_vectors() {
+        let from = na::Vector3::new(1.0, 2.0, 2.0) / 3.0;
+        for to in [na::Vector3::new(0.0, 0.6, -0.8), from, -from] {
+            let rotation = rotation_between(&from, &to);
+            assert_relative_eq!(rotation * from, to, epsilon = 1e-12);
+            assert_relative_eq!(
+                rotation.transpose() * rotation,
+                na::Matrix3::identity(),
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_profile_tracking_on_reference() {
+        let inertia = na::Matrix3::new(10.0, 0.0, 0.0, 0.0, 12.0, 0.0, 0.0, 0.0, 8.0);
+        let controller = GeometricAttitudeController::new(1.0, 0.1, inertia);
+
+        let start = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let goal = Quaternion::new((PI / 6.0).cos(), 0.0, (PI / 6.0).sin(), 0.0);
+        let profile = AttitudeProfile::new(&start, &goal, 120.0);
+
+        // Sitting exactly on the reference attitude and rate demands no torque
+        for t in [0.0, 30.0, 60.0, 90.0, 120.0] {
+            let (q_ref, w_ref) = profile.sample(t);
+            let torque = controller.compute_profile_torque(&profile, t, &q_ref, &w_ref);
+            assert_relative_eq!(torque.magnitude(), 0.0, epsilon = 1e-12);
+        }
+
+        // Lagging behind the reference mid-slew drives the body toward the goal
+        let torque =
+            controller.compute_profile_torque(&profile, 60.0, &start, &na::Vector3::zeros());
+        assert!(torque.y > 0.0);
+    }
+
+    #[test]
+    fn test_geodetic_nadir_deflection() {
+        use crate::constants::{WGS84_A, WGS84_F};
+        use crate::coordinates::coordinate_transformation::itrs_to_geodetic_signed;
+
+        let mut geocentric = GeometricAttitudeController::new(1.0, 0.1, na::Matrix3::identity());
+        let mut geodetic = GeometricAttitudeController::new(1.0, 0.1, na::Matrix3::identity())
+            .with_nadir_reference(NadirReference::Geodetic);
+
+        // Northbound through 60° geocentric latitude on a polar orbit at 500 km
+        let latitude: f64 = 60.0_f64.to_radians();
+        let longitude: f64 = 0.7;
+        let radius = WGS84_A + 500_000.0;
+        let up = na::Vector3::new(
+            latitude.cos() * longitude.cos(),
+            latitude.cos() * longitude.sin(),
+            latitude.sin(),
+        );
+        let north = na::Vector3::new(
+            -latitude.sin() * longitude.cos(),
+            -latitude.sin() * longitude.sin(),
+            latitude.cos(),
+        );
+        let r = up * radius;
+        let v = north * 7_600.0;
+
+        let geocentric_frame = geocentric.nadir_frame(&r, &v);
+        let geodetic_frame = geodetic.nadir_frame(&r, &v);
+        assert_relative_eq!(
+            geodetic_frame.transpose() * geodetic_frame,
+            na::Matrix3::identity(),
+            epsilon = 1e-12
+        );
+
+        // The radial axes differ by the deflection of the vertical, which tilts the geodetic
+        // vertical poleward, to a higher latitude
+        let (_, geodetic_latitude, _) = itrs_to_geodetic_signed(&r);
+        let deflection = geodetic_latitude.to_radians() - latitude;
+        let radial = geodetic_frame.column(0).into_owned();
+        assert_relative_eq!(
+            radial.angle(&geocentric_frame.column(0).into_owned()),
+            deflection,
+            max_relative = 1e-9
+        );
+        assert_relative_eq!(
+            radial.z.asin(),
+            geodetic_latitude.to_radians(),
+            epsilon = 1e-12
+        );
+
+        // On the surface below, the deflection follows tan φ = tan ψ / (1 - e²); from 500 km
+        // up it is a little smaller, but still about a sixth of a degree
+        let e2 = 2.0 * WGS84_F - WGS84_F * WGS84_F;
+        let surface_deflection = (latitude.tan() / (1.0 - e2)).atan() - latitude;
+        assert!(deflection < surface_deflection);
+        assert!(deflection.to_degrees() > 0.15 && deflection.to_degrees() < 0.19);
+
+        // The tilt is in the orbit plane, so the along-track axis tilts with it and the
+        // cross-track axis is unchanged
+        assert_relative_eq!(
+            geodetic_frame.column(2).into_owned(),
+            geocentric_frame.column(2).into_owned(),
+            epsilon = 1e-12
+        );
+
+        // Nadir pointing follows the selected frame
+        let q = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let w = na::Vector3::zeros();
+        let torque_geocentric = geocentric.compute_control_torque(&r, &v, &q, &w);
+        let torque_geodetic = geodetic.compute_control_torque(&r, &v, &q, &w);
+        assert!((torque_geocentric - torque_geodetic).magnitude() > 1e-4);
+    }
 }
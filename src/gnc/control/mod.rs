@@ -1 +1,3 @@
 pub mod attitude_controller;
+pub mod momentum_management;
+pub mod reaction_wheels;
@@ -0,0 +1,114 @@
+use nalgebra as na;
+
+/// Magnetorquer momentum dumping for reaction wheels.
+///
+/// Once the stored wheel momentum exceeds `threshold`, a dipole is commanded with the
+/// cross-product law m = k (h × B) / |B|², whose torque m × B = -k h⊥ opposes the part of
+/// the wheel momentum perpendicular to the local field. The component along B cannot be
+/// dumped until the field direction changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MomentumManager {
+    threshold: f64,  // N⋅m⋅s, wheel momentum above which dumping starts
+    gain: f64,       // 1/s
+    max_dipole: f64, // A⋅m², magnitude limit of the magnetorquers
+}
+
+impl MomentumManager {
+    pub fn new(threshold: f64, gain: f64, max_dipole: f64) -> Self {
+        Self {
+            threshold,
+            gain,
+            max_dipole,
+        }
+    }
+
+    pub fn needs_desaturation(&self, wheel_momentum: &na::Vector3<f64>) -> bool {
+        wheel_momentum.magnitude() > self.threshold
+    }
+
+    /// Magnetic dipole to command for the wheel momentum and magnetic field, both in the
+    /// body frame. Returns zero below the threshold or in a vanishing field.
+    pub fn commanded_dipole(
+        &self,
+        wheel_momentum: &na::Vector3<f64>,
+        magnetic_field: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        let b_squared = magnetic_field.magnitude_squared();
+        if !self.needs_desaturation(wheel_momentum) || b_squared == 0.0 {
+            return na::Vector3::zeros();
+        }
+
+        let dipole = wheel_momentum.cross(magnetic_field) * (self.gain / b_squared);
+        let dipole_mag = dipole.magnitude();
+        if dipole_mag > self.max_dipole {
+            dipole * (self.max_dipole / dipole_mag)
+        } else {
+            dipole
+        }
+    }
+
+    /// Torque the magnetorquers exert on the spacecraft for the given dipole and field
+    pub fn magnetic_torque(
+        dipole: &na::Vector3<f64>,
+        magnetic_field: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        dipole.cross(magnetic_field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::WGS84_A;
+    use crate::physics::environment::Environment;
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    #[test]
+    fn test_saturated_wheels_are_desaturated() {
+        let manager = MomentumManager::new(0.05, 0.01, 10.0);
+
+        // Inertially held spacecraft on a circular orbit, so the body and GCRS axes coincide
+        let radius = WGS84_A + 500_000.0;
+        let orbital_rate = OrbitalMechanics::compute_circular_velocity(radius) / radius;
+
+        let initial_momentum = na::Vector3::new(0.08, -0.06, 0.0);
+        let mut wheel_momentum = initial_momentum;
+        let mut desaturating_steps = 0;
+        let dt = 1.0;
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+
+        for step in 0..600 {
+            let angle = orbital_rate * step as f64 * dt;
+            let position = na::Vector3::new(angle.cos(), angle.sin(), 0.0) * radius;
+            let field = Environment::new(&position, &epoch).magnetic_field;
+
+            let dipole = manager.commanded_dipole(&wheel_momentum, &field);
+            let torque = MomentumManager::magnetic_torque(&dipole, &field);
+            if manager.needs_desaturation(&wheel_momentum) {
+                desaturating_steps += 1;
+                assert!(torque.dot(&wheel_momentum) < 0.0);
+            }
+
+            // The wheels absorb the external torque to hold the attitude
+            let previous = wheel_momentum.magnitude();
+            wheel_momentum += torque * dt;
+            assert!(wheel_momentum.magnitude() <= previous);
+        }
+
+        assert!(desaturating_steps > 0);
+        assert!(!manager.needs_desaturation(&wheel_momentum));
+        assert!(wheel_momentum.magnitude() < initial_momentum.magnitude());
+        assert_relative_eq!(wheel_momentum.magnitude(), 0.05, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_no_dipole_below_threshold() {
+        let manager = MomentumManager::new(0.05, 0.01, 10.0);
+        let field = na::Vector3::new(0.0, 0.0, 3.0e-5);
+
+        let dipole = manager.commanded_dipole(&na::Vector3::new(0.03, 0.0, 0.0), &field);
+        assert_eq!(dipole, na::Vector3::zeros());
+    }
+}
@@ -0,0 +1,156 @@
+use nalgebra as na;
+
+/// A single reaction wheel spinning about a fixed body axis
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReactionWheel {
+    axis: na::Vector3<f64>, // unit spin axis in the body frame
+    inertia: f64,           // kg⋅m², about the spin axis
+    max_torque: f64,        // N⋅m
+    max_speed: f64,         // rad/s
+    speed: f64,             // rad/s
+    commanded_torque: f64,  // N⋅m on the wheel, before limits
+    torque: f64,            // N⋅m on the wheel, as delivered
+}
+
+impl ReactionWheel {
+    pub fn new(axis: na::Vector3<f64>, inertia: f64, max_torque: f64, max_speed: f64) -> Self {
+        Self {
+            axis: axis.normalize(),
+            inertia,
+            max_torque,
+            max_speed,
+            speed: 0.0,
+            commanded_torque: 0.0,
+            torque: 0.0,
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Torque asked of the wheel motor in the last step, before the torque and speed limits
+    pub fn commanded_torque(&self) -> f64 {
+        self.commanded_torque
+    }
+
+    /// Torque the motor actually applied to the wheel in the last step
+    pub fn torque(&self) -> f64 {
+        self.torque
+    }
+
+    /// Stored angular momentum in the body frame [N⋅m⋅s]
+    pub fn momentum(&self) -> na::Vector3<f64> {
+        self.axis * (self.inertia * self.speed)
+    }
+}
+
+/// Reaction wheels that realise a commanded body torque by exchanging momentum with the
+/// spacecraft. The command is shared out by least squares over the wheel axes (nothing is
+/// delivered unless the axes span all three), then each wheel is held to its torque and
+/// speed limits, so near saturation the delivered torque falls short of the command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReactionWheelArray {
+    wheels: Vec<ReactionWheel>,
+}
+
+impl ReactionWheelArray {
+    pub fn new(wheels: Vec<ReactionWheel>) -> Self {
+        Self { wheels }
+    }
+
+    /// Three identical wheels along the body x, y and z axes
+    pub fn orthogonal(inertia: f64, max_torque: f64, max_speed: f64) -> Self {
+        Self::new(
+            [na::Vector3::x(), na::Vector3::y(), na::Vector3::z()]
+                .into_iter()
+                .map(|axis| ReactionWheel::new(axis, inertia, max_torque, max_speed))
+                .collect(),
+        )
+    }
+
+    pub fn wheels(&self) -> &[ReactionWheel] {
+        &self.wheels
+    }
+
+    /// Total stored angular momentum in the body frame [N⋅m⋅s]
+    pub fn momentum(&self) -> na::Vector3<f64> {
+        self.wheels
+            .iter()
+            .fold(na::Vector3::zeros(), |total, wheel| {
+                total + wheel.momentum()
+            })
+    }
+
+    /// Spins the wheels for `dt` seconds to produce `commanded`, a body-frame torque on the
+    /// spacecraft, and returns the torque actually delivered
+    pub fn apply(&mut self, commanded: &na::Vector3<f64>, dt: f64) -> na::Vector3<f64> {
+        // The minimum-norm split of the command over the wheel axes, A^T (A A^T)^-1. The
+        // spacecraft feels the reaction to the torque on the wheels, hence the sign.
+        let gram = self
+            .wheels
+            .iter()
+            .fold(na::Matrix3::zeros(), |gram, wheel| {
+                gram + wheel.axis * wheel.axis.transpose()
+            });
+        let weights = gram
+            .try_inverse()
+            .map_or(na::Vector3::zeros(), |inverse| inverse * -commanded);
+
+        let mut delivered = na::Vector3::zeros();
+        for wheel in self.wheels.iter_mut() {
+            let commanded_torque = wheel.axis.dot(&weights);
+            let torque = commanded_torque.clamp(-wheel.max_torque, wheel.max_torque);
+            let speed = (wheel.speed + torque / wheel.inertia * dt)
+                .clamp(-wheel.max_speed, wheel.max_speed);
+
+            wheel.commanded_torque = commanded_torque;
+            wheel.torque = if dt > 0.0 {
+                wheel.inertia * (speed - wheel.speed) / dt
+            } else {
+                0.0
+            };
+            wheel.speed = speed;
+            delivered -= wheel.axis * wheel.torque;
+        }
+        delivered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_wheels_deliver_torque_until_saturated() {
+        let mut wheels = ReactionWheelArray::orthogonal(0.01, 0.05, 600.0);
+        let commanded = na::Vector3::new(0.02, -0.01, 0.0);
+        let dt = 0.1;
+
+        // Within the limits the command is met, and the wheels take up the opposite momentum
+        for _ in 0..100 {
+            let delivered = wheels.apply(&commanded, dt);
+            assert_relative_eq!(delivered, commanded, epsilon = 1e-12);
+        }
+        assert_relative_eq!(wheels.momentum(), -commanded * 10.0, epsilon = 1e-12);
+
+        // The x wheel reaches 600 rad/s after 300 s; from then on only y still responds
+        for _ in 0..2_900 {
+            wheels.apply(&commanded, dt);
+        }
+        let delivered = wheels.apply(&commanded, dt);
+        assert_relative_eq!(wheels.wheels()[0].speed(), -600.0, epsilon = 1e-9);
+        assert_relative_eq!(delivered.x, 0.0, epsilon = 1e-9);
+        assert_relative_eq!(delivered.y, commanded.y, epsilon = 1e-12);
+        assert_relative_eq!(
+            wheels.wheels()[0].commanded_torque(),
+            -0.02,
+            epsilon = 1e-12
+        );
+
+        // Commands beyond the motor torque are clipped
+        let delivered = wheels.apply(&na::Vector3::new(0.0, 0.0, 1.0), dt);
+        assert_relative_eq!(delivered.z, 0.05, epsilon = 1e-12);
+    }
+}
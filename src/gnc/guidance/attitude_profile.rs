@@ -0,0 +1,121 @@
+use crate::numerics::quaternion::Quaternion;
+use nalgebra as na;
+
+/// Reference attitude for a rest-to-rest slew between two attitudes.
+///
+/// The reference follows the shortest-path SLERP from `start` to `goal`, with a quintic
+/// time-scaling so that the commanded body rate and angular acceleration both vanish at the
+/// ends of the slew. Quaternions follow the body-to-inertial convention of `Quaternion`.
+#[derive(Debug, Clone)]
+pub struct AttitudeProfile {
+    start: Quaternion,
+    goal: Quaternion,
+    duration: f64,          // s
+    axis: na::Vector3<f64>, // body frame
+    angle: f64,             // rad
+}
+
+impl AttitudeProfile {
+    pub fn new(start: &Quaternion, goal: &Quaternion, duration: f64) -> Self {
+        let start = start.normalize();
+        let mut goal = goal.normalize();
+
+        // q and -q are the same attitude; pick the sign that gives the shorter slew
+        if start.data.dot(&goal.data) < 0.0 {
+            goal = Quaternion { data: -goal.data };
+        }
+
+        // Relative rotation from start to goal, expressed in the start body frame
+        let relative = start.conjugate().multiply(&goal);
+        let sin_half = relative.vector().magnitude();
+        let angle = 2.0 * sin_half.atan2(relative.scalar());
+        let axis = if sin_half < 1e-11 {
+            na::Vector3::zeros()
+        } else {
+            relative.vector() / sin_half
+        };
+
+        Self {
+            start,
+            goal,
+            duration,
+            axis,
+            angle,
+        }
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Returns the reference attitude and body rate at `t` seconds after the slew starts.
+    /// Before the start and after the end the profile holds the endpoint at rest.
+    pub fn sample(&self, t: f64) -> (Quaternion, na::Vector3<f64>) {
+        if t <= 0.0 || self.duration <= 0.0 {
+            return (self.start.clone(), na::Vector3::zeros());
+        }
+        if t >= self.duration {
+            return (self.goal.clone(), na::Vector3::zeros());
+        }
+
+        let tau = t / self.duration;
+        let s = tau.powi(3) * (10.0 - 15.0 * tau + 6.0 * tau * tau);
+        let s_dot = 30.0 * tau * tau * (1.0 - tau).powi(2) / self.duration;
+
+        let half_angle = 0.5 * self.angle * s;
+        let step = Quaternion::new(
+            half_angle.cos(),
+            self.axis.x * half_angle.sin(),
+            self.axis.y * half_angle.sin(),
+            self.axis.z * half_angle.sin(),
+        );
+
+        // Rotating about a fixed body axis keeps the body rate along that axis
+        (self.start.multiply(&step), self.axis * self.angle * s_dot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_profile_endpoints_and_midpoint_rate() {
+        let start = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let goal = Quaternion::new((PI / 4.0).cos(), 0.0, 0.0, (PI / 4.0).sin());
+        let profile = AttitudeProfile::new(&start, &goal, 60.0);
+
+        let (q_start, w_start) = profile.sample(0.0);
+        assert_relative_eq!(q_start.data, start.data, epsilon = 1e-12);
+        assert_relative_eq!(w_start.magnitude(), 0.0, epsilon = 1e-12);
+
+        let (q_end, w_end) = profile.sample(60.0);
+        assert_relative_eq!(q_end.data, goal.data, epsilon = 1e-12);
+        assert_relative_eq!(w_end.magnitude(), 0.0, epsilon = 1e-12);
+
+        // Halfway through, the reference is halfway round and turning at its peak rate
+        let (q_mid, w_mid) = profile.sample(30.0);
+        let expected_mid = Quaternion::new((PI / 8.0).cos(), 0.0, 0.0, (PI / 8.0).sin());
+        assert_relative_eq!(q_mid.data, expected_mid.data, epsilon = 1e-12);
+        assert!(w_mid.z > 0.0);
+        assert_relative_eq!(w_mid.z, 1.875 * (PI / 2.0) / 60.0, epsilon = 1e-12);
+
+        // Rates just inside the ends are small, so the slew starts and stops smoothly
+        assert!(profile.sample(0.1).1.magnitude() < 1e-4 * w_mid.magnitude());
+        assert!(profile.sample(59.9).1.magnitude() < 1e-4 * w_mid.magnitude());
+    }
+
+    #[test]
+    fn test_profile_takes_shortest_path() {
+        let start = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        // -q encodes the same 90 degree rotation about x
+        let goal = Quaternion::new(-(PI / 4.0).cos(), -(PI / 4.0).sin(), 0.0, 0.0);
+        let profile = AttitudeProfile::new(&start, &goal, 10.0);
+
+        let (_, w_mid) = profile.sample(5.0);
+        assert!(w_mid.x > 0.0);
+        assert_relative_eq!(w_mid.x, 1.875 * (PI / 2.0) / 10.0, epsilon = 1e-12);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::constants::G0;
+use crate::errors::KosmossError;
+use crate::integrators::rk4::RK4;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::physics::dynamics::SpacecraftDynamics;
+use nalgebra as na;
+
+/// A constant-thrust burn of finite duration, sized with the rocket equation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FiniteBurn {
+    thrust: f64,  // N
+    isp: f64,     // s
+    delta_v: f64, // m/s
+}
+
+/// Result of executing a `FiniteBurn`
+#[derive(Debug, Clone)]
+pub struct BurnOutcome<'a, T: SpacecraftProperties> {
+    pub state: State<'a, T>,
+    pub duration: f64,         // s
+    pub fuel_consumed: f64,    // kg
+    pub delta_v_achieved: f64, // m/s, ideal rocket-equation delta-v
+    pub fuel_depleted: bool,   // burn was cut short by running out of fuel
+}
+
+impl FiniteBurn {
+    pub fn new(thrust: f64, isp: f64, delta_v: f64) -> Self {
+        Self {
+            thrust,
+            isp,
+            delta_v,
+        }
+    }
+
+    pub fn exhaust_velocity(&self) -> f64 {
+        self.isp * G0
+    }
+
+    pub fn mass_flow_rate(&self) -> f64 {
+        self.thrust / self.exhaust_velocity()
+    }
+
+    /// Propellant needed to deliver the burn's delta-v from an initial mass `m0`
+    pub fn propellant_required(&self, m0: f64) -> f64 {
+        m0 * (1.0 - (-self.delta_v / self.exhaust_velocity()).exp())
+    }
+
+    /// Burn time needed to deliver the burn's delta-v from an initial mass `m0`
+    pub fn burn_duration(&self, m0: f64) -> f64 {
+        self.propellant_required(m0) / self.mass_flow_rate()
+    }
+
+    /// Integrates the burn from `state` with thrust held along the inertial unit vector
+    /// `direction`, depleting mass as it goes. The burn ends once the target delta-v has
+    /// been delivered or the fuel runs out, whichever comes first. A zero `direction` has no
+    /// thrust axis, so nothing is burned and the state is returned unchanged. Fails unless
+    /// the step `dt` is finite and positive.
+    pub fn execute<'a, T: SpacecraftProperties>(
+        &self,
+        state: &State<'a, T>,
+        direction: &na::Vector3<f64>,
+        dt: f64,
+    ) -> Result<BurnOutcome<'a, T>, KosmossError> {
+        // A zero, negative or NaN step would never reach the end of the burn
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(KosmossError::InvalidConfig(format!(
+                "burn step must be finite and positive, got {}",
+                dt
+            )));
+        }
+
+        let mut state = state.clone();
+        let Some(direction) = direction.try_normalize(0.0) else {
+            return Ok(BurnOutcome {
+                state,
+                duration: 0.0,
+                fuel_consumed: 0.0,
+                delta_v_achieved: 0.0,
+                fuel_depleted: false,
+            });
+        };
+        let m0 = state.mass;
+        let mass_flow_rate = self.mass_flow_rate();
+        let thrust = direction * self.thrust;
+
+        // The final step is shortened so the burn ends exactly on the target propellant
+        // or on the last of the fuel
+        let fuel_budget = self.propellant_required(m0).min(state.fuel_mass);
+        let fuel_depleted = self.propellant_required(m0) > state.fuel_mass;
+        let duration = fuel_budget / mass_flow_rate;
+
+        let dynamics = SpacecraftDynamics::<T>::new(Some(thrust), None);
+        let integrator = RK4::new(dynamics);
+
+        let mut elapsed = 0.0;
+        while elapsed < duration {
+            let step = dt.min(duration - elapsed);
+            let mass_before = state.mass;
+            let mass_after = mass_before - mass_flow_rate * step;
+
+            // Thrust acceleration is evaluated at the mid-step mass so that the velocity
+            // increment matches the rocket equation to second order in the step
+            state.mass = 0.5 * (mass_before + mass_after);
+            state = integrator.integrate(&state, step);
+            state.mass = mass_after;
+            state.fuel_mass -= mass_flow_rate * step;
+
+            elapsed += step;
+            state.advance_time(step);
+        }
+
+        let fuel_consumed = m0 - state.mass;
+        Ok(BurnOutcome {
+            delta_v_achieved: self.exhaust_velocity() * (m0 / state.mass).ln(),
+            state,
+            duration,
+            fuel_consumed,
+            fuel_depleted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::WGS84_A;
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    fn circular_state(spacecraft: &SimpleSat) -> State<'_, SimpleSat> {
+        let radius = WGS84_A + 400_000.0;
+        State::new(
+            spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(radius, 0.0, 0.0),
+            na::Vector3::new(
+                0.0,
+                OrbitalMechanics::compute_circular_velocity(radius),
+                0.0,
+            ),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        )
+    }
+
+    #[test]
+    fn test_finite_burn_delivers_target_delta_v() {
+        let spacecraft = SimpleSat::default();
+        let initial = circular_state(&spacecraft);
+        let burn = FiniteBurn::new(20.0, 300.0, 15.0);
+        let direction = initial.velocity.normalize();
+
+        let outcome = burn.execute(&initial, &direction, 1.0).unwrap();
+        assert!(!outcome.fuel_depleted);
+        assert_relative_eq!(
+            outcome.duration,
+            burn.burn_duration(initial.mass),
+            max_relative = 1e-12
+        );
+
+        // Fuel consumed follows the rocket equation
+        let expected_fuel = initial.mass * (1.0 - (-15.0 / (300.0 * G0)).exp());
+        assert_relative_eq!(outcome.fuel_consumed, expected_fuel, max_relative = 1e-9);
+        assert_relative_eq!(
+            outcome.state.fuel_mass,
+            initial.fuel_mass - expected_fuel,
+            max_relative = 1e-9
+        );
+
+        // Measure the delta-v actually delivered by comparing against an unpowered
+        // propagation over the same interval
+        let coast = RK4::new(SpacecraftDynamics::<SimpleSat>::new(None, None));
+        let mut coasting = initial.clone();
+        let mut elapsed = 0.0;
+        while elapsed < outcome.duration {
+            let step = (outcome.duration - elapsed).min(1.0);
+            coasting = coast.integrate(&coasting, step);
+            elapsed += step;
+        }
+        let achieved = (outcome.state.velocity - coasting.velocity).magnitude();
+
+        assert_relative_eq!(outcome.delta_v_achieved, 15.0, max_relative = 1e-9);
+        // The powered and coasting trajectories drift apart by a few hundred meters during
+        // the burn, so the differential gravity between them accounts for the residual
+        assert_relative_eq!(achieved, 15.0, max_relative = 5e-3);
+    }
+
+    #[test]
+    fn test_finite_burn_stops_when_fuel_runs_out() {
+        let spacecraft = SimpleSat::default();
+        let mut initial = circular_state(&spacecraft);
+        initial.fuel_mass = 0.5;
+        let burn = FiniteBurn::new(20.0, 300.0, 100.0);
+
+        let outcome = burn
+            .execute(&initial, &na::Vector3::new(0.0, 1.0, 0.0), 1.0)
+            .unwrap();
+
+        assert!(outcome.fuel_depleted);
+        assert_relative_eq!(outcome.fuel_consumed, 0.5, max_relative = 1e-9);
+        assert_relative_eq!(outcome.state.fuel_mass, 0.0, epsilon = 1e-9);
+        assert!(outcome.delta_v_achieved < 100.0);
+        assert_relative_eq!(
+            outcome.delta_v_achieved,
+            300.0 * G0 * (initial.mass / (initial.mass - 0.5)).ln(),
+            max_relative = 1e-9
+        );
+    }
+
+    #[test]
+    fn test_zero_direction_burns_nothing() {
+        let spacecraft = SimpleSat::default();
+        let initial = circular_state(&spacecraft);
+        let burn = FiniteBurn::new(20.0, 300.0, 15.0);
+
+        let outcome = burn.execute(&initial, &na::Vector3::zeros(), 1.0).unwrap();
+
+        assert_eq!(outcome.duration, 0.0);
+        assert_eq!(outcome.fuel_consumed, 0.0);
+        assert_eq!(outcome.delta_v_achieved, 0.0);
+        assert!(!outcome.fuel_depleted);
+        assert_eq!(outcome.state.position, initial.position);
+        assert_eq!(outcome.state.velocity, initial.velocity);
+        assert_eq!(outcome.state.fuel_mass, initial.fuel_mass);
+    }
+
+    #[test]
+    fn test_non_positive_step_is_rejected() {
+        let spacecraft = SimpleSat::default();
+        let initial = circular_state(&spacecraft);
+        let burn = FiniteBurn::new(20.0, 300.0, 15.0);
+        let direction = initial.velocity.normalize();
+
+        // Rejected up front rather than looping forever
+        for dt in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                burn.execute(&initial, &direction, dt),
+                Err(KosmossError::InvalidConfig(_))
+            ));
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::constants::{G, M_EARTH};
+use crate::constants::MU_EARTH;
+use crate::coordinates::coordinate_transformation::gcrs_to_rsw_rotation;
 use crate::models::spacecraft::SpacecraftProperties;
 use crate::physics::orbital::OrbitalMechanics;
 use nalgebra as na;
@@ -10,11 +11,44 @@ pub enum ApsisType {
     Apogee,
 }
 
+/// Along-track sense of an apsis burn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BurnDirection {
+    /// Along the velocity, raising the opposite apsis
+    Prograde,
+    /// Against the velocity, lowering the opposite apsis
+    Retrograde,
+}
+
+impl BurnDirection {
+    /// +1 for prograde and -1 for retrograde, to scale the along-track unit vector by
+    pub fn sign(&self) -> f64 {
+        match self {
+            BurnDirection::Prograde => 1.0,
+            BurnDirection::Retrograde => -1.0,
+        }
+    }
+}
+
+/// Default distance from the burn apsis within which burns fire [m]
+const DEFAULT_APSIS_TOLERANCE: f64 = 100.0;
+
+/// Default error in the targeted apsis radius below which no burn is commanded [m]
+const DEFAULT_TARGET_TOLERANCE: f64 = 100.0;
+
+/// Error in the targeted apsis radius, relative to the target radius, at which the
+/// single-shot iteration has converged. An absolute tolerance would sit near the f64
+/// resolution of radii of thousands of kilometres.
+const SINGLE_SHOT_TOLERANCE: f64 = 1e-9;
+
 #[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ApsisTargeting {
     target_radius: f64,
     apsis_type: ApsisType,
     start_time: f64,
+    apsis_tolerance: f64,
+    target_tolerance: f64,
 }
 
 #[allow(dead_code)]
@@ -24,9 +58,26 @@ impl ApsisTargeting {
             target_radius,
             apsis_type,
             start_time,
+            apsis_tolerance: DEFAULT_APSIS_TOLERANCE,
+            target_tolerance: DEFAULT_TARGET_TOLERANCE,
         }
     }
 
+    /// Distance from the burn apsis within which burns fire [m]. The radius changes slowly
+    /// near an apsis, but with a long step the sampled states can still pass it by more than
+    /// the 100 m default, so the burn would never fire.
+    pub fn with_apsis_tolerance(mut self, apsis_tolerance: f64) -> Self {
+        self.apsis_tolerance = apsis_tolerance;
+        self
+    }
+
+    /// Error in the targeted apsis radius below which the target counts as reached [m],
+    /// 100 m by default
+    pub fn with_target_tolerance(mut self, target_tolerance: f64) -> Self {
+        self.target_tolerance = target_tolerance;
+        self
+    }
+
     // Getter methods
     pub fn get_apsis_type(&self) -> ApsisType {
         self.apsis_type
@@ -36,6 +87,94 @@ impl ApsisTargeting {
         self.target_radius
     }
 
+    /// Prograde Δv [m/s] that moves the targeted apsis onto the target radius in a single
+    /// impulsive burn from the current state, found by Newton iteration on the apsis partials.
+    /// `None` unless the state is bound and at the opposite apsis, where the burn belongs, and
+    /// the iteration converges.
+    pub fn single_shot_delta_v(
+        &self,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> Option<f64> {
+        if !OrbitalMechanics::is_bound(r_current, v_current) {
+            return None;
+        }
+        let (at_apogee, at_perigee) =
+            OrbitalMechanics::is_near_apsis(r_current, v_current, self.apsis_tolerance);
+        let at_burn_apsis = match self.apsis_type {
+            ApsisType::Apogee => at_perigee,
+            ApsisType::Perigee => at_apogee,
+        };
+        if !at_burn_apsis {
+            return None;
+        }
+
+        let prograde = v_current.normalize();
+        let targeted_apsis = |v: &na::Vector3<f64>| {
+            let (ra, rp) = OrbitalMechanics::compute_apsides(r_current, v);
+            let (d_ra, d_rp) = OrbitalMechanics::apsis_velocity_partials(r_current, v);
+            match self.apsis_type {
+                ApsisType::Apogee => (ra, d_ra.dot(&prograde)),
+                ApsisType::Perigee => (rp, d_rp.dot(&prograde)),
+            }
+        };
+
+        let mut delta_v = 0.0;
+        for _ in 0..20 {
+            let v = v_current + prograde * delta_v;
+            if !OrbitalMechanics::is_bound(r_current, &v) {
+                return None;
+            }
+            let (radius, slope) = targeted_apsis(&v);
+            let error = self.target_radius - radius;
+            if error.abs() < SINGLE_SHOT_TOLERANCE * self.target_radius {
+                return Some(delta_v);
+            }
+            let step = error / slope;
+            if !step.is_finite() {
+                return None;
+            }
+            delta_v += step;
+        }
+        None
+    }
+
+    /// True once the targeted apsis of the current orbit lies within the target tolerance
+    /// of the target radius. Never true on an escape trajectory, which has no apogee.
+    pub fn is_target_achieved(
+        &self,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> bool {
+        if !OrbitalMechanics::is_bound(r_current, v_current) {
+            return false;
+        }
+        let (ra, rp) = OrbitalMechanics::compute_apsides(r_current, v_current);
+        let apsis = match self.apsis_type {
+            ApsisType::Perigee => rp,
+            ApsisType::Apogee => ra,
+        };
+        (apsis - self.target_radius).abs() <= self.target_tolerance
+    }
+
+    /// Prograde when the targeted apsis lies below the target radius, retrograde when above
+    pub fn burn_direction(
+        &self,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> BurnDirection {
+        let (ra, rp) = OrbitalMechanics::compute_apsides(r_current, v_current);
+        let apsis = match self.apsis_type {
+            ApsisType::Perigee => rp,
+            ApsisType::Apogee => ra,
+        };
+        if self.target_radius > apsis {
+            BurnDirection::Prograde
+        } else {
+            BurnDirection::Retrograde
+        }
+    }
+
     pub fn get_desired_force<T: SpacecraftProperties>(
         &self,
         spacecraft: &T,
@@ -47,57 +186,303 @@ impl ApsisTargeting {
             return na::Vector3::zeros();
         }
 
-        // Get current apsides
-        let (ra, rp) = OrbitalMechanics::compute_apsides(r_current, v_current);
-
-        // Determine if we need to burn
-        let should_burn = match self.apsis_type {
-            ApsisType::Perigee => (rp - self.target_radius).abs() > 100.0, // 100m tolerance
-            ApsisType::Apogee => (ra - self.target_radius).abs() > 100.0,  // 100m tolerance
-        };
+        // An escape trajectory has no apogee to target, so never keep burning on one
+        if !OrbitalMechanics::is_bound(r_current, v_current) {
+            return na::Vector3::zeros();
+        }
 
-        if !should_burn {
+        if self.is_target_achieved(r_current, v_current) {
             return na::Vector3::zeros();
         }
 
         // Check if we're at the correct apsis for burning
-        let (at_apogee, at_perigee) = OrbitalMechanics::is_near_apsis(
-            r_current, v_current, 100.0, // 100m tolerance
-        );
+        let (at_apogee, at_perigee) =
+            OrbitalMechanics::is_near_apsis(r_current, v_current, self.apsis_tolerance);
 
-        // At apogee, burn prograde to raise perigee
-        // At perigee, burn prograde to raise apogee
+        // The apsis opposite the spacecraft is moved by an along-track burn at this one
         if (self.apsis_type == ApsisType::Perigee && at_apogee)
             || (self.apsis_type == ApsisType::Apogee && at_perigee)
         {
-            let burn_direction = v_current.normalize();
+            // The velocity is purely along-track at an apsis, so the S axis is prograde
+            let prograde = gcrs_to_rsw_rotation(r_current, v_current)
+                .column(1)
+                .into_owned();
 
-            // Calculate required delta-v based on current orbit
+            // Speed here that puts the opposite apsis on the target (vis-viva)
             let r = r_current.magnitude();
-            let v = v_current.magnitude();
-            let mu = G * M_EARTH;
-
-            // Calculate target velocity
-            let target_v = match self.apsis_type {
-                ApsisType::Apogee => {
-                    // At perigee, calculate required velocity for new apogee
-                    (mu * (2.0 / r - 2.0 / (self.target_radius + r))).sqrt()
-                }
-                ApsisType::Perigee => {
-                    // At apogee, calculate required velocity for new perigee
-                    (mu * (2.0 / r - 2.0 / (self.target_radius + r))).sqrt()
-                }
-            };
-
-            let delta_v = target_v - v;
-            let mut burn_magnitude = delta_v.abs().min(100.0); // Limit to 100 m/s per step
-            if delta_v < 0.0 {
-                burn_magnitude *= -1.0;
-            }
+            let target_v = (MU_EARTH * (2.0 / r - 2.0 / (self.target_radius + r))).sqrt();
+            let delta_v = (target_v - v_current.magnitude()).abs();
+            let thrust = (delta_v * spacecraft.mass()).min(spacecraft.max_thrust());
 
-            return burn_direction * burn_magnitude * spacecraft.mass();
+            return prograde * (self.burn_direction(r_current, v_current).sign() * thrust);
         }
 
         na::Vector3::zeros()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::{PI, WGS84_A};
+    use approx::assert_relative_eq;
+
+    struct HeavySat;
+
+    impl SpacecraftProperties for HeavySat {
+        fn mass(&self) -> f64 {
+            850.0
+        }
+
+        fn drag_coefficient(&self) -> f64 {
+            2.2
+        }
+
+        fn reference_area(&self) -> f64 {
+            4.0
+        }
+    }
+
+    #[test]
+    fn test_desired_force_uses_spacecraft_mass() {
+        let radius = WGS84_A + 400_000.0;
+        let r = na::Vector3::new(radius, 0.0, 0.0);
+        let v = na::Vector3::new(
+            0.0,
+            OrbitalMechanics::compute_circular_velocity(radius),
+            0.0,
+        );
+        let guidance = ApsisTargeting::new(radius + 20_000.0, ApsisType::Apogee, 0.0);
+
+        let heavy_force = guidance.get_desired_force(&HeavySat, &r, &v, 0.0);
+        let simple_force = guidance.get_desired_force(&SimpleSat::default(), &r, &v, 0.0);
+
+        // Same commanded delta-v, scaled by each spacecraft's own mass
+        let mu = MU_EARTH;
+        let target_v = (mu * (2.0 / radius - 2.0 / (2.0 * radius + 20_000.0))).sqrt();
+        let delta_v = target_v - v.magnitude();
+        assert!(delta_v > 0.0 && delta_v < 100.0);
+
+        assert_relative_eq!(
+            heavy_force,
+            v.normalize() * delta_v * 850.0,
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            heavy_force / 850.0,
+            simple_force / SimpleSat::MASS,
+            max_relative = 1e-12
+        );
+    }
+
+    struct IonSat;
+
+    impl SpacecraftProperties for IonSat {
+        fn mass(&self) -> f64 {
+            500.0
+        }
+
+        fn drag_coefficient(&self) -> f64 {
+            2.2
+        }
+
+        fn reference_area(&self) -> f64 {
+            2.0
+        }
+
+        fn max_thrust(&self) -> f64 {
+            0.2 // N, a gridded ion thruster
+        }
+    }
+
+    #[test]
+    fn test_desired_force_limited_by_max_thrust() {
+        let radius = WGS84_A + 400_000.0;
+        let r = na::Vector3::new(radius, 0.0, 0.0);
+        let v = na::Vector3::new(
+            0.0,
+            OrbitalMechanics::compute_circular_velocity(radius),
+            0.0,
+        );
+
+        // Raising and lowering burns alike are held to the thruster's own limit
+        let raise = ApsisTargeting::new(radius + 20_000.0, ApsisType::Apogee, 0.0);
+        let force = raise.get_desired_force(&IonSat, &r, &v, 0.0);
+        assert_relative_eq!(force, v.normalize() * 0.2, max_relative = 1e-12);
+
+        let lower = ApsisTargeting::new(radius - 20_000.0, ApsisType::Perigee, 0.0);
+        let force = lower.get_desired_force(&IonSat, &r, &v, 0.0);
+        assert_relative_eq!(force.magnitude(), 0.2, max_relative = 1e-12);
+        assert!(force.dot(&v) < 0.0);
+    }
+
+    #[test]
+    fn test_apsis_tolerance() {
+        // Just past perigee, about 340 m above it, as a long step might first sample the orbit
+        let elements = na::Vector6::new(WGS84_A + 450_000.0, 0.01, 0.5, 0.2, 0.7, 0.1);
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let (_, rp) = OrbitalMechanics::compute_apsides(&r, &v);
+        assert!((300.0..400.0).contains(&(r.magnitude() - rp)));
+
+        let target = WGS84_A + 600_000.0;
+        let tight = ApsisTargeting::new(target, ApsisType::Apogee, 0.0);
+        assert_eq!(
+            tight.get_desired_force(&SimpleSat::default(), &r, &v, 0.0),
+            na::Vector3::zeros()
+        );
+        assert!(tight.single_shot_delta_v(&r, &v).is_none());
+
+        let loose =
+            ApsisTargeting::new(target, ApsisType::Apogee, 0.0).with_apsis_tolerance(1_000.0);
+        let force = loose.get_desired_force(&SimpleSat::default(), &r, &v, 0.0);
+        assert!(force.dot(&v) > 0.0);
+        assert!(loose.single_shot_delta_v(&r, &v).unwrap() > 0.0);
+
+        // Within the target tolerance the apogee counts as reached, and nothing fires
+        let (ra, _) = OrbitalMechanics::compute_apsides(&r, &v);
+        let reached = ApsisTargeting::new(ra + 5_000.0, ApsisType::Apogee, 0.0)
+            .with_apsis_tolerance(1_000.0)
+            .with_target_tolerance(10_000.0);
+        assert_eq!(
+            reached.get_desired_force(&SimpleSat::default(), &r, &v, 0.0),
+            na::Vector3::zeros()
+        );
+    }
+
+    #[test]
+    fn test_burn_direction_for_raising_and_lowering() {
+        // Slightly eccentric, with distinct apsides at 400 and 600 km
+        let (rp, ra) = (WGS84_A + 400_000.0, WGS84_A + 600_000.0);
+        let at = |true_anomaly| {
+            OrbitalMechanics::keplerian_to_cartesian(&na::Vector6::new(
+                (ra + rp) / 2.0,
+                (ra - rp) / (ra + rp),
+                0.5,
+                0.2,
+                0.7,
+                true_anomaly,
+            ))
+        };
+        let (r_perigee, v_perigee) = at(0.0);
+        let (r_apogee, v_apogee) = at(PI);
+
+        let cases = [
+            // (apsis, target altitude, burn state, expected direction)
+            (
+                ApsisType::Apogee,
+                800_000.0,
+                (r_perigee, v_perigee),
+                BurnDirection::Prograde,
+            ),
+            (
+                ApsisType::Apogee,
+                500_000.0,
+                (r_perigee, v_perigee),
+                BurnDirection::Retrograde,
+            ),
+            (
+                ApsisType::Perigee,
+                500_000.0,
+                (r_apogee, v_apogee),
+                BurnDirection::Prograde,
+            ),
+            (
+                ApsisType::Perigee,
+                300_000.0,
+                (r_apogee, v_apogee),
+                BurnDirection::Retrograde,
+            ),
+        ];
+        for (apsis, altitude, (r, v), expected) in cases {
+            let guidance = ApsisTargeting::new(WGS84_A + altitude, apsis, 0.0);
+            assert_eq!(guidance.burn_direction(&r, &v), expected);
+
+            // The direction agrees with the sign of the vis-viva Δv
+            let r_mag = r.magnitude();
+            let target_v = (MU_EARTH * (2.0 / r_mag - 2.0 / (WGS84_A + altitude + r_mag))).sqrt();
+            assert_eq!((target_v - v.magnitude()).signum(), expected.sign());
+
+            // and the commanded force points that way along the velocity
+            let force = guidance.get_desired_force(&HeavySat, &r, &v, 0.0);
+            assert_relative_eq!(
+                force.normalize(),
+                v.normalize() * expected.sign(),
+                epsilon = 1e-12
+            );
+        }
+
+        // Lowering the apogee at perigee: the force opposes the velocity, by the Δv
+        // times the mass
+        let lower = ApsisTargeting::new(WGS84_A + 500_000.0, ApsisType::Apogee, 0.0);
+        let force = lower.get_desired_force(&HeavySat, &r_perigee, &v_perigee, 0.0);
+        let r_mag = r_perigee.magnitude();
+        let target_v = (MU_EARTH * (2.0 / r_mag - 2.0 / (WGS84_A + 500_000.0 + r_mag))).sqrt();
+        assert!(force.dot(&v_perigee) < 0.0);
+        assert_relative_eq!(
+            force.magnitude(),
+            (v_perigee.magnitude() - target_v) * 850.0,
+            max_relative = 1e-9
+        );
+    }
+
+    #[test]
+    fn test_no_thrust_on_escape_trajectory() {
+        // At perigee of a hyperbolic orbit, where an apogee-raising burn would otherwise fire
+        let radius = WGS84_A + 400_000.0;
+        let r = na::Vector3::new(radius, 0.0, 0.0);
+        let v = na::Vector3::new(0.0, 1.05 * (2.0 * MU_EARTH / radius).sqrt(), 0.0);
+        assert!(!OrbitalMechanics::is_bound(&r, &v));
+
+        let guidance = ApsisTargeting::new(radius + 20_000.0, ApsisType::Apogee, 0.0);
+        let force = guidance.get_desired_force(&SimpleSat::default(), &r, &v, 0.0);
+        assert_eq!(force, na::Vector3::zeros());
+    }
+
+    #[test]
+    fn test_single_shot_burn_reaches_target_apsis() {
+        // Slightly eccentric, so the apsides are distinct
+        let (rp, ra) = (WGS84_A + 400_000.0, WGS84_A + 450_000.0);
+        let elements = na::Vector6::new((ra + rp) / 2.0, (ra - rp) / (ra + rp), 0.5, 0.2, 0.7, 0.0);
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+
+        // Raise the apogee from 450 km to 2,000 km in one burn at perigee, far beyond what
+        // the force command allows in a single step
+        let target = WGS84_A + 2_000_000.0;
+        let raise = ApsisTargeting::new(target, ApsisType::Apogee, 0.0);
+        let delta_v = raise.single_shot_delta_v(&r, &v).unwrap();
+        assert!(delta_v > 300.0, "{delta_v} m/s");
+
+        let (new_ra, new_rp) =
+            OrbitalMechanics::compute_apsides(&r, &(v + v.normalize() * delta_v));
+        assert_relative_eq!(new_ra, target, epsilon = 1e-3);
+        assert_relative_eq!(new_rp, rp, epsilon = 1e-3);
+
+        // The same from the vis-viva equation
+        let r_mag = r.magnitude();
+        let expected = (MU_EARTH * (2.0 / r_mag - 2.0 / (target + r_mag))).sqrt() - v.magnitude();
+        assert_relative_eq!(delta_v, expected, max_relative = 1e-9);
+
+        // Lowering the perigee from the apogee needs a retrograde burn
+        let mut at_apogee = elements;
+        at_apogee[5] = PI;
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&at_apogee);
+        let lower = ApsisTargeting::new(WGS84_A + 200_000.0, ApsisType::Perigee, 0.0);
+        let delta_v = lower.single_shot_delta_v(&r, &v).unwrap();
+        assert!(delta_v < 0.0);
+        let (_, new_rp) = OrbitalMechanics::compute_apsides(&r, &(v + v.normalize() * delta_v));
+        assert_relative_eq!(new_rp, WGS84_A + 200_000.0, epsilon = 1e-3);
+
+        // Away from the burn apsis there is no single-shot solution
+        assert!(raise.single_shot_delta_v(&r, &v).is_none());
+        let mut in_between = elements;
+        in_between[5] = 1.0;
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&in_between);
+        assert!(lower.single_shot_delta_v(&r, &v).is_none());
+
+        // No burn at perigee puts the apogee below the perigee radius
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let below = ApsisTargeting::new(rp - 50_000.0, ApsisType::Apogee, 0.0);
+        assert!(below.single_shot_delta_v(&r, &v).is_none());
+    }
+}
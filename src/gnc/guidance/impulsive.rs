@@ -0,0 +1,132 @@
+use crate::coordinates::coordinate_transformation::{gcrs_to_ntw_rotation, gcrs_to_rsw_rotation};
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use nalgebra as na;
+
+/// Frame in which the components of an impulsive Δv are given
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ManeuverFrame {
+    /// GCRS components
+    #[default]
+    Inertial,
+    /// Radial, along-track (normal to R in the orbit plane) and orbit normal
+    Rsw,
+    /// In-plane normal to the velocity, along the velocity, and orbit normal
+    Ntw,
+}
+
+/// An instantaneous change of velocity, given in a frame attached to the orbit at the
+/// moment of the burn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImpulsiveBurn {
+    pub delta_v: na::Vector3<f64>, // m/s, components in `frame`
+    pub frame: ManeuverFrame,
+}
+
+impl ImpulsiveBurn {
+    pub fn new(delta_v: na::Vector3<f64>, frame: ManeuverFrame) -> Self {
+        Self { delta_v, frame }
+    }
+
+    /// The Δv in GCRS for a burn at position `r` and velocity `v`
+    pub fn inertial_delta_v(&self, r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> na::Vector3<f64> {
+        match self.frame {
+            ManeuverFrame::Inertial => self.delta_v,
+            ManeuverFrame::Rsw => gcrs_to_rsw_rotation(r, v) * self.delta_v,
+            ManeuverFrame::Ntw => gcrs_to_ntw_rotation(r, v) * self.delta_v,
+        }
+    }
+
+    /// Adds the Δv to the velocity of `state`. The mass is left alone; use a `FiniteBurn`
+    /// to account for the propellant.
+    pub fn apply<T: SpacecraftProperties>(&self, state: &mut State<T>) {
+        state.velocity += self.inertial_delta_v(&state.position, &state.velocity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::{MU_EARTH, WGS84_A};
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    fn state_at<'a>(
+        spacecraft: &'a SimpleSat,
+        elements: &na::Vector6<f64>,
+    ) -> State<'a, SimpleSat> {
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(elements);
+        State::new(
+            spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        )
+    }
+
+    fn semi_latus_rectum<T: SpacecraftProperties>(state: &State<T>) -> f64 {
+        OrbitalMechanics::angular_momentum_vector(&state.position, &state.velocity)
+            .magnitude_squared()
+            / MU_EARTH
+    }
+
+    #[test]
+    fn test_radial_impulse() {
+        let spacecraft = SimpleSat::default();
+        let elements = na::Vector6::new(WGS84_A + 1_000_000.0, 0.05, 0.9, 0.4, 1.0, 0.6);
+        let mut state = state_at(&spacecraft, &elements);
+        let p = semi_latus_rectum(&state);
+
+        ImpulsiveBurn::new(na::Vector3::new(20.0, 0.0, 0.0), ManeuverFrame::Rsw).apply(&mut state);
+        let after = OrbitalMechanics::cartesian_to_keplerian(&state.position, &state.velocity);
+
+        // The impulse is along r, so the angular momentum and with it the plane and the
+        // semi-latus rectum are kept, while the shape and orientation in the plane change
+        assert_relative_eq!(semi_latus_rectum(&state), p, max_relative = 1e-12);
+        assert_relative_eq!(after[2], elements[2], epsilon = 1e-12);
+        assert_relative_eq!(after[3], elements[3], epsilon = 1e-12);
+        assert!((after[1] - elements[1]).abs() > 1e-4);
+        assert!((after[4] - elements[4]).abs() > 1e-3);
+    }
+
+    #[test]
+    fn test_cross_track_impulse() {
+        let spacecraft = SimpleSat::default();
+        // At the ascending node, where a cross-track burn only turns the plane about r
+        let elements = na::Vector6::new(WGS84_A + 1_000_000.0, 0.0, 0.9, 0.4, 0.0, 0.0);
+
+        for frame in [ManeuverFrame::Rsw, ManeuverFrame::Ntw] {
+            let mut state = state_at(&spacecraft, &elements);
+            let v = state.velocity.magnitude();
+            ImpulsiveBurn::new(na::Vector3::new(0.0, 0.0, 50.0), frame).apply(&mut state);
+            let after = OrbitalMechanics::cartesian_to_keplerian(&state.position, &state.velocity);
+
+            // Burning along the orbit normal at the ascending node raises the inclination
+            assert_relative_eq!(after[2], elements[2] + (50.0 / v).atan(), epsilon = 1e-9);
+            assert_relative_eq!(after[3], elements[3], epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_frames_agree_on_tangential_burn_at_apsis() {
+        let spacecraft = SimpleSat::default();
+        let elements = na::Vector6::new(WGS84_A + 1_000_000.0, 0.1, 0.9, 0.4, 1.0, 0.0);
+        let state = state_at(&spacecraft, &elements);
+        let (r, v) = (state.position, state.velocity);
+
+        // At perigee the velocity is along-track, so RSW and NTW coincide with prograde
+        let prograde = v.normalize() * 10.0;
+        for frame in [ManeuverFrame::Rsw, ManeuverFrame::Ntw] {
+            let burn = ImpulsiveBurn::new(na::Vector3::new(0.0, 10.0, 0.0), frame);
+            assert_relative_eq!(burn.inertial_delta_v(&r, &v), prograde, epsilon = 1e-9);
+        }
+        let inertial = ImpulsiveBurn::new(prograde, ManeuverFrame::Inertial);
+        assert_relative_eq!(inertial.inertial_delta_v(&r, &v), prograde);
+    }
+}
@@ -1 +1,78 @@
+pub mod attitude_profile;
+pub mod finite_burn;
 pub mod hohmann;
+pub mod impulsive;
+pub mod station_keeping;
+pub mod thrust_ramp;
+
+use crate::models::spacecraft::SpacecraftProperties;
+use hohmann::ApsisTargeting;
+use nalgebra as na;
+use station_keeping::StationKeeping;
+
+/// Guidance law flown during maneuvers
+#[derive(Debug, Clone, PartialEq)]
+pub enum Guidance {
+    /// Burns at an apsis until the opposite apsis reaches a target radius, in maneuvers
+    /// scheduled by the mission plan
+    ApsisTargeting(ApsisTargeting),
+    /// Holds the mean semi-major axis inside a deadband, asking for a maneuver whenever it
+    /// has decayed past it
+    StationKeeping(StationKeeping),
+}
+
+impl Guidance {
+    /// Thrust in GCRS for the current state [N]
+    pub fn get_desired_force<T: SpacecraftProperties>(
+        &mut self,
+        spacecraft: &T,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+        current_time: f64,
+    ) -> na::Vector3<f64> {
+        match self {
+            Guidance::ApsisTargeting(guidance) => {
+                guidance.get_desired_force(spacecraft, r_current, v_current, current_time)
+            }
+            Guidance::StationKeeping(guidance) => {
+                guidance.get_desired_force(spacecraft, r_current, v_current)
+            }
+        }
+    }
+
+    /// True once the current orbit meets the target, ending the maneuver
+    pub fn is_target_achieved(
+        &self,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> bool {
+        match self {
+            Guidance::ApsisTargeting(guidance) => guidance.is_target_achieved(r_current, v_current),
+            Guidance::StationKeeping(guidance) => guidance.is_target_achieved(r_current, v_current),
+        }
+    }
+
+    /// True when the guidance wants a maneuver now without one being scheduled
+    pub fn requests_burn(
+        &self,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> bool {
+        match self {
+            Guidance::ApsisTargeting(_) => false,
+            Guidance::StationKeeping(guidance) => guidance.needs_correction(r_current, v_current),
+        }
+    }
+}
+
+impl From<ApsisTargeting> for Guidance {
+    fn from(guidance: ApsisTargeting) -> Self {
+        Guidance::ApsisTargeting(guidance)
+    }
+}
+
+impl From<StationKeeping> for Guidance {
+    fn from(guidance: StationKeeping) -> Self {
+        Guidance::StationKeeping(guidance)
+    }
+}
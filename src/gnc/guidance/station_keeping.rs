@@ -0,0 +1,150 @@
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::physics::orbital::OrbitalMechanics;
+use nalgebra as na;
+
+/// Holds the mean semi-major axis, and with it the ground-track repeat period, inside a
+/// deadband against drag decay. Once the mean axis falls below `target - deadband` a
+/// prograde burn is commanded until it is back up to the target, so corrections come as
+/// short, periodic burns. The mean rather than osculating axis is tested, since the J2
+/// short-period terms alone swing the osculating value by kilometres each orbit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationKeeping {
+    target_semi_major_axis: f64, // m, mean
+    deadband: f64,               // m
+    thrust: f64,                 // N
+    burning: bool,
+}
+
+impl StationKeeping {
+    pub fn new(target_semi_major_axis: f64, deadband: f64, thrust: f64) -> Self {
+        Self {
+            target_semi_major_axis,
+            deadband,
+            thrust,
+            burning: false,
+        }
+    }
+
+    pub fn target_semi_major_axis(&self) -> f64 {
+        self.target_semi_major_axis
+    }
+
+    /// True while a corrective burn is in progress
+    pub fn is_burning(&self) -> bool {
+        self.burning
+    }
+
+    /// True when the mean semi-major axis has decayed below the deadband, so a corrective
+    /// burn is due
+    pub fn needs_correction(
+        &self,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> bool {
+        OrbitalMechanics::is_bound(r_current, v_current)
+            && OrbitalMechanics::mean_semi_major_axis(r_current, v_current)
+                < self.target_semi_major_axis - self.deadband
+    }
+
+    /// True once the mean semi-major axis is back up to the target, or on an escape
+    /// trajectory, where there is nothing to hold
+    pub fn is_target_achieved(
+        &self,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> bool {
+        !OrbitalMechanics::is_bound(r_current, v_current)
+            || OrbitalMechanics::mean_semi_major_axis(r_current, v_current)
+                >= self.target_semi_major_axis
+    }
+
+    /// Thrust in GCRS for the current state [N]: along the velocity during a corrective
+    /// burn, limited to the spacecraft's `max_thrust`, and zero otherwise
+    pub fn get_desired_force<T: SpacecraftProperties>(
+        &mut self,
+        spacecraft: &T,
+        r_current: &na::Vector3<f64>,
+        v_current: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        if !OrbitalMechanics::is_bound(r_current, v_current) {
+            self.burning = false;
+            return na::Vector3::zeros();
+        }
+
+        let mean_semi_major_axis = OrbitalMechanics::mean_semi_major_axis(r_current, v_current);
+        if mean_semi_major_axis < self.target_semi_major_axis - self.deadband {
+            self.burning = true;
+        } else if mean_semi_major_axis >= self.target_semi_major_axis {
+            self.burning = false;
+        }
+
+        if self.burning {
+            v_current.normalize() * self.thrust.min(spacecraft.max_thrust())
+        } else {
+            na::Vector3::zeros()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::WGS84_A;
+    use crate::integrators::rk4::RK4;
+    use crate::models::TranslationalState;
+    use crate::physics::dynamics::TranslationalDynamics;
+    use crate::physics::environment::AtmosphereModel;
+
+    /// Mean semi-major axis once per step over `orbits` revolutions, with or without
+    /// station keeping
+    fn mean_history(guidance: Option<&mut StationKeeping>, orbits: f64) -> Vec<f64> {
+        let spacecraft = SimpleSat::default();
+        let (position, velocity) =
+            OrbitalMechanics::circular_orbit_state(350_000.0, 51.6_f64.to_radians(), 0.3, 0.0);
+        let mut state = TranslationalState::new(&spacecraft, position, velocity);
+
+        let dt = 10.0;
+        let steps = orbits * OrbitalMechanics::compute_orbital_period(WGS84_A + 350_000.0) / dt;
+        let mut guidance = guidance;
+        (0..steps as usize)
+            .map(|_| {
+                let thrust = guidance
+                    .as_deref_mut()
+                    .map_or(na::Vector3::zeros(), |guidance| {
+                        guidance.get_desired_force(&spacecraft, &state.position, &state.velocity)
+                    });
+                // Drag from the tabulated atmosphere, J2 and the station-keeping thrust
+                let dynamics = TranslationalDynamics::new(Some(thrust))
+                    .with_atmosphere(AtmosphereModel::Piecewise.into())
+                    .with_j2();
+                state = RK4::new(dynamics).integrate(&state, dt);
+                OrbitalMechanics::mean_semi_major_axis(&state.position, &state.velocity)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_station_keeping_holds_mean_semi_major_axis() {
+        let (position, velocity) =
+            OrbitalMechanics::circular_orbit_state(350_000.0, 51.6_f64.to_radians(), 0.3, 0.0);
+        let target = OrbitalMechanics::mean_semi_major_axis(&position, &velocity);
+        let deadband = 200.0;
+
+        let uncontrolled = mean_history(None, 15.0);
+        let decay = target - uncontrolled.last().unwrap();
+        assert!(decay > 3.0 * deadband, "decay {decay} m");
+
+        let mut guidance = StationKeeping::new(target, deadband, 0.5);
+        let controlled = mean_history(Some(&mut guidance), 15.0);
+        let (lowest, highest) = controlled
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), &a| (lo.min(a), hi.max(a)));
+        assert!(
+            lowest > target - deadband - 1.0,
+            "lowest {}",
+            lowest - target
+        );
+        assert!(highest < target + deadband, "highest {}", highest - target);
+    }
+}
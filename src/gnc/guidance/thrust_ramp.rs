@@ -0,0 +1,203 @@
+use nalgebra as na;
+
+/// Shape of the thrust build-up, as a function of the ramp progress s in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RampShape {
+    Linear,
+    /// Smoothstep 3s² - 2s³, which also starts and ends with zero slope
+    Smooth,
+}
+
+impl RampShape {
+    fn level(&self, s: f64) -> f64 {
+        match self {
+            RampShape::Linear => s,
+            RampShape::Smooth => s * s * (3.0 - 2.0 * s),
+        }
+    }
+}
+
+/// Spreads thrust turn-on and turn-off over `duration` seconds instead of switching the full
+/// command in a single step. Once the command drops to zero the last commanded thrust is
+/// ramped back down. The impulse lost while ramping up is returned while ramping down, so
+/// with either shape a ramped burn delivers the same total impulse as the step command,
+/// shifted later by half the ramp duration less half a step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrustRamp {
+    duration: f64, // s
+    shape: RampShape,
+    progress: f64,
+    thrust: na::Vector3<f64>, // N, most recent non-zero command
+}
+
+impl ThrustRamp {
+    pub fn new(duration: f64, shape: RampShape) -> Self {
+        Self {
+            duration,
+            shape,
+            progress: 0.0,
+            thrust: na::Vector3::zeros(),
+        }
+    }
+
+    /// Returns the thrust to apply over the next step of length `dt` for the raw command
+    /// `commanded`
+    pub fn apply(&mut self, commanded: &na::Vector3<f64>, dt: f64) -> na::Vector3<f64> {
+        let rate = if self.duration > 0.0 {
+            dt / self.duration
+        } else {
+            1.0
+        };
+
+        if commanded.magnitude() > 0.0 {
+            self.thrust = *commanded;
+            self.progress = (self.progress + rate).min(1.0);
+        } else {
+            self.progress = (self.progress - rate).max(0.0);
+        }
+
+        self.thrust * self.shape.level(self.progress)
+    }
+
+    /// Drops the thrust to zero at once, without ramping down, e.g. when a fault shuts the
+    /// engine off
+    pub fn cut_off(&mut self) {
+        self.progress = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::{MU_EARTH, WGS84_A};
+    use crate::integrators::rk4::RK4;
+    use crate::models::State;
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::dynamics::SpacecraftDynamics;
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    #[test]
+    fn test_ramp_levels() {
+        let command = na::Vector3::new(0.0, 10.0, 0.0);
+
+        let mut linear = ThrustRamp::new(4.0, RampShape::Linear);
+        let levels: Vec<f64> = (0..4).map(|_| linear.apply(&command, 1.0).y).collect();
+        assert_eq!(levels, [2.5, 5.0, 7.5, 10.0]);
+
+        // Holding at full thrust, then ramping back down once the command stops
+        assert_relative_eq!(linear.apply(&command, 1.0).y, 10.0);
+        assert_relative_eq!(linear.apply(&na::Vector3::zeros(), 1.0).y, 7.5);
+
+        let mut smooth = ThrustRamp::new(4.0, RampShape::Smooth);
+        assert_relative_eq!(smooth.apply(&command, 2.0).y, 5.0, epsilon = 1e-12);
+        assert!(smooth.apply(&command, 1.0).y > 7.5);
+
+        // Cutting off skips the ramp down
+        linear.cut_off();
+        assert_eq!(
+            linear.apply(&na::Vector3::zeros(), 1.0),
+            na::Vector3::zeros()
+        );
+
+        // A zero-length ramp passes the command straight through
+        let mut instant = ThrustRamp::new(0.0, RampShape::Linear);
+        assert_eq!(instant.apply(&command, 1.0), command);
+    }
+
+    #[test]
+    fn test_ramped_burn_delivers_commanded_impulse_later() {
+        let command = na::Vector3::new(0.0, 10.0, 0.0);
+        let (dt, duration, burn_steps) = (1.0, 4.0, 10);
+
+        for shape in [RampShape::Linear, RampShape::Smooth] {
+            // The step command is on for the burn, then off until the ramp has run down
+            let mut ramp = ThrustRamp::new(duration, shape);
+            let thrust: Vec<f64> = (0..burn_steps + 8)
+                .map(|step| {
+                    let commanded = if step < burn_steps {
+                        command
+                    } else {
+                        na::Vector3::zeros()
+                    };
+                    ramp.apply(&commanded, dt).y
+                })
+                .collect();
+            assert_eq!(*thrust.last().unwrap(), 0.0);
+
+            let impulse: f64 = thrust.iter().map(|thrust| thrust * dt).sum();
+            let centroid = thrust
+                .iter()
+                .enumerate()
+                .map(|(step, thrust)| (step as f64 + 0.5) * dt * thrust * dt)
+                .sum::<f64>()
+                / impulse;
+
+            let step_impulse = command.y * burn_steps as f64 * dt;
+            let step_centroid = burn_steps as f64 * dt / 2.0;
+            assert_relative_eq!(impulse, step_impulse, epsilon = 1e-12);
+            assert_relative_eq!(
+                centroid - step_centroid,
+                (duration - dt) / 2.0,
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_ramp_reduces_energy_error_at_burn_start() {
+        let spacecraft = SimpleSat::default();
+        let radius = WGS84_A + 400_000.0;
+        let initial = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(radius, 0.0, 0.0),
+            na::Vector3::new(
+                0.0,
+                OrbitalMechanics::compute_circular_velocity(radius),
+                0.0,
+            ),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let specific_energy = |s: &State<SimpleSat>| {
+            s.velocity.magnitude_squared() / 2.0 - MU_EARTH / s.position.magnitude()
+        };
+
+        // Error in specific energy of each 10 s RK4 step over the first steps of a prograde
+        // burn, against the same step taken in 1000 substeps
+        let dt = 10.0;
+        let energy_errors = |mut ramp: Option<ThrustRamp>| {
+            let mut state = initial.clone();
+            let mut errors = Vec::new();
+            for _ in 0..3 {
+                let command = state.velocity.normalize() * 500.0;
+                let thrust = ramp
+                    .as_mut()
+                    .map_or(na::Vector3::zeros(), |ramp| ramp.apply(&command, dt));
+                let integrator = RK4::new(SpacecraftDynamics::<SimpleSat>::new(Some(thrust), None));
+                let next = integrator.integrate(&state, dt);
+                let mut reference = state.clone();
+                for _ in 0..1000 {
+                    reference = integrator.integrate(&reference, dt / 1000.0);
+                }
+                errors.push((specific_energy(&next) - specific_energy(&reference)).abs());
+                state = next;
+            }
+            errors
+        };
+
+        let coasting = energy_errors(None);
+        let instant = energy_errors(Some(ThrustRamp::new(0.0, RampShape::Linear)));
+        let ramped = energy_errors(Some(ThrustRamp::new(30.0, RampShape::Smooth)));
+
+        // Switching the full 5 m/s² on at once spikes the step error far above coasting,
+        // while the ramp lets it build up over the ramp instead
+        assert!(instant[0] > 10.0 * coasting[0]);
+        assert!(ramped[0] < 0.5 * instant[0]);
+        assert!(ramped[0] < ramped[1] && ramped[1] < ramped[2]);
+    }
+}
@@ -0,0 +1,193 @@
+use super::backward_euler::DerivativeVector;
+use super::rk4::RK4;
+use crate::physics::dynamics::EquationsOfMotion;
+
+/// Work done by an adaptive integration run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntegrationStats {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub min_dt: f64,  // s, smallest accepted step
+    pub max_dt: f64,  // s, largest accepted step
+    pub mean_dt: f64, // s, over accepted steps
+}
+
+impl IntegrationStats {
+    /// Attempted steps, accepted or not
+    pub fn steps(&self) -> usize {
+        self.accepted + self.rejected
+    }
+}
+
+/// RK4 with step-size control from the step-doubling error estimate.
+///
+/// Each step is accepted when the largest component of its estimated local error is within
+/// `tolerance`, and the next step is sized so the error would land at 90% of it. The step
+/// size carries over from one run to the next.
+pub struct AdaptiveRK4<T: EquationsOfMotion> {
+    rk4: RK4<T>,
+    tolerance: f64,
+    min_dt: f64,
+    max_dt: f64,
+    dt: f64,
+}
+
+impl<T: EquationsOfMotion> AdaptiveRK4<T>
+where
+    T::State: Clone + std::ops::Add<T::Derivative, Output = T::State>,
+    T::Derivative: Clone
+        + DerivativeVector
+        + std::ops::Add<Output = T::Derivative>
+        + std::ops::Mul<f64, Output = T::Derivative>,
+{
+    /// `tolerance` is in the units of the state components, e.g. metres for an orbit
+    pub fn new(eom: T, tolerance: f64, min_dt: f64, max_dt: f64) -> Self {
+        Self {
+            rk4: RK4::new(eom),
+            tolerance,
+            min_dt,
+            max_dt,
+            dt: max_dt,
+        }
+    }
+
+    /// Size of the next step attempt
+    pub fn next_dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// Integrates `state` by `duration` seconds, landing exactly on the end time. A negative
+    /// duration integrates backward; the step sizes in the statistics are magnitudes.
+    pub fn run_for(&mut self, state: &T::State, duration: f64) -> (T::State, IntegrationStats) {
+        let direction = duration.signum();
+        let duration = duration.abs();
+        let mut state = state.clone();
+        let mut elapsed = 0.0;
+        let mut stats = IntegrationStats {
+            accepted: 0,
+            rejected: 0,
+            min_dt: f64::INFINITY,
+            max_dt: 0.0,
+            mean_dt: 0.0,
+        };
+
+        while duration - elapsed > 1e-9 * duration {
+            let remaining = duration - elapsed;
+            let dt = self.dt.min(remaining);
+            let (next, error) = self.rk4.integrate_with_error(&state, direction * dt);
+            let error = error.to_vector().amax();
+
+            // The local error scales as dt^5
+            let scale = if error > 0.0 {
+                (0.9 * (self.tolerance / error).powf(0.2)).clamp(0.2, 5.0)
+            } else {
+                5.0
+            };
+
+            if error <= self.tolerance || dt <= self.min_dt {
+                state = next;
+                elapsed += dt;
+                stats.accepted += 1;
+                stats.min_dt = stats.min_dt.min(dt);
+                stats.max_dt = stats.max_dt.max(dt);
+                stats.mean_dt += dt;
+
+                // A step shortened to hit the end time says nothing about the step size
+                if dt == self.dt {
+                    self.dt = (dt * scale).clamp(self.min_dt, self.max_dt);
+                }
+            } else {
+                stats.rejected += 1;
+                self.dt = (dt * scale).clamp(self.min_dt, self.max_dt);
+            }
+        }
+
+        if stats.accepted > 0 {
+            stats.mean_dt /= stats.accepted as f64;
+        }
+        (state, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::{PI, WGS84_A};
+    use crate::integrators::two_body::TwoBodyPropagator;
+    use crate::integrators::Integrator;
+    use crate::models::TranslationalState;
+    use crate::physics::dynamics::TranslationalDynamics;
+    use crate::physics::orbital::OrbitalMechanics;
+    use nalgebra as na;
+
+    #[test]
+    fn test_rejections_concentrate_near_perigee() {
+        let spacecraft = SimpleSat::default();
+
+        // Perigee at 500 km, where the modelled drag is negligible, and apogee at 40,000 km
+        let rp = WGS84_A + 500_000.0;
+        let ra = WGS84_A + 40_000_000.0;
+        let elements = na::Vector6::new((ra + rp) / 2.0, (ra - rp) / (ra + rp), 0.6, 0.2, 0.9, PI);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let initial = TranslationalState::new(&spacecraft, position, velocity);
+        let period = OrbitalMechanics::compute_orbital_period(elements[0]);
+
+        let mut integrator = AdaptiveRK4::new(TranslationalDynamics::new(None), 1e-2, 0.1, 600.0);
+
+        // Tally the work in 40 windows, split by whether the window starts inside or outside
+        // the mean distance
+        let windows = 40;
+        let mut state = initial.clone();
+        let (mut inner, mut outer) = (Vec::new(), Vec::new());
+        for _ in 0..windows {
+            let near = state.position.magnitude() < elements[0];
+            let (next, stats) = integrator.run_for(&state, period / windows as f64);
+            if near {
+                inner.push(stats);
+            } else {
+                outer.push(stats);
+            }
+            state = next;
+        }
+
+        let rejected = |windows: &[IntegrationStats]| -> usize {
+            windows.iter().map(|stats| stats.rejected).sum()
+        };
+        let mean_dt = |windows: &[IntegrationStats]| -> f64 {
+            windows.iter().map(|stats| stats.mean_dt).sum::<f64>() / windows.len() as f64
+        };
+
+        // The orbit spends far less time near perigee, yet that is where most steps fail
+        assert!(inner.len() < outer.len());
+        assert!(rejected(&inner) > 0);
+        assert!(
+            rejected(&inner) > 2 * rejected(&outer),
+            "rejections near perigee {}, far {}",
+            rejected(&inner),
+            rejected(&outer)
+        );
+        assert!(mean_dt(&inner) < mean_dt(&outer) / 3.0);
+
+        // With 1 cm allowed per step, one period stays within metres of the analytic orbit
+        let exact = TwoBodyPropagator::new().integrate(&initial, period);
+        assert!((state.position - exact.position).magnitude() < 10.0);
+    }
+
+    #[test]
+    fn test_backward_run_retraces_forward_run() {
+        let spacecraft = SimpleSat::default();
+        let elements = na::Vector6::new(8_000_000.0, 0.1, 0.5, 0.3, 1.0, 0.2);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let initial = TranslationalState::new(&spacecraft, position, velocity);
+        let mut integrator = AdaptiveRK4::new(TranslationalDynamics::new(None), 1e-3, 0.1, 120.0);
+
+        let (forward, _) = integrator.run_for(&initial, 3000.0);
+        let (round_trip, stats) = integrator.run_for(&forward, -3000.0);
+
+        assert!(stats.accepted > 0 && stats.min_dt > 0.0);
+        assert!((forward.position - position).magnitude() > 1e6);
+        assert!((round_trip.position - position).magnitude() < 0.1);
+        assert!((round_trip.velocity - velocity).magnitude() < 1e-4);
+    }
+}
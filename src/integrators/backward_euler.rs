@@ -0,0 +1,242 @@
+use super::Integrator;
+use crate::models::{StateDerivative, TranslationalDerivative};
+use crate::numerics::jacobian::{numerical_jacobian, Differencing};
+use crate::physics::dynamics::EquationsOfMotion;
+use nalgebra as na;
+
+/// Flat view of a derivative's components, used to set up the Newton solve of an implicit step
+pub trait DerivativeVector: Sized {
+    fn to_vector(&self) -> na::DVector<f64>;
+    fn from_vector(vector: &na::DVector<f64>) -> Self;
+}
+
+impl DerivativeVector for StateDerivative {
+    fn to_vector(&self) -> na::DVector<f64> {
+        na::DVector::from_iterator(
+            15,
+            self.position
+                .iter()
+                .chain(self.velocity.iter())
+                .chain(self.quaternion.iter())
+                .chain(self.angular_velocity.iter())
+                .chain([&self.mass, &self.fuel_mass])
+                .copied(),
+        )
+    }
+
+    fn from_vector(vector: &na::DVector<f64>) -> Self {
+        let v = vector.as_slice();
+        StateDerivative {
+            position: na::Vector3::from_column_slice(&v[0..3]),
+            velocity: na::Vector3::from_column_slice(&v[3..6]),
+            quaternion: na::Vector4::from_column_slice(&v[6..10]),
+            angular_velocity: na::Vector3::from_column_slice(&v[10..13]),
+            mass: v[13],
+            fuel_mass: v[14],
+        }
+    }
+}
+
+impl DerivativeVector for TranslationalDerivative {
+    fn to_vector(&self) -> na::DVector<f64> {
+        na::DVector::from_iterator(6, self.position.iter().chain(self.velocity.iter()).copied())
+    }
+
+    fn from_vector(vector: &na::DVector<f64>) -> Self {
+        let v = vector.as_slice();
+        TranslationalDerivative {
+            position: na::Vector3::from_column_slice(&v[0..3]),
+            velocity: na::Vector3::from_column_slice(&v[3..6]),
+        }
+    }
+}
+
+/// Implicit (backward) Euler integrator for stiff problems, such as attitude dynamics under
+/// strong rate damping, where explicit methods need steps far below the time scales of
+/// interest to stay stable.
+///
+/// Each step solves Δ = dt·f(x + Δ) for the increment Δ by Newton iteration, with the
+/// Jacobian of f taken by finite differences (forward unless set by `with_differencing`).
+/// It is only first-order accurate, but remains stable for any step size on decaying dynamics.
+pub struct BackwardEuler<T: EquationsOfMotion> {
+    eom: T,
+    tolerance: f64,
+    max_iterations: usize,
+    differencing: Differencing,
+}
+
+impl<T: EquationsOfMotion> BackwardEuler<T>
+where
+    T::State: Clone + std::ops::Add<T::Derivative, Output = T::State>,
+    T::Derivative: DerivativeVector,
+{
+    pub fn new(eom: T) -> Self {
+        Self::with_tolerance(eom, 1e-12, 20)
+    }
+
+    /// `tolerance` bounds the last Newton correction relative to the size of the increment
+    pub fn with_tolerance(eom: T, tolerance: f64, max_iterations: usize) -> Self {
+        BackwardEuler {
+            eom,
+            tolerance,
+            max_iterations,
+            differencing: Differencing::default(),
+        }
+    }
+
+    /// Differencing scheme for the Newton Jacobian. Central differences cost twice the
+    /// derivative evaluations per iteration but give a more accurate Jacobian, which can
+    /// save iterations on strongly nonlinear dynamics.
+    pub fn with_differencing(mut self, differencing: Differencing) -> Self {
+        self.differencing = differencing;
+        self
+    }
+
+    /// Takes one implicit step. If Newton has not converged after `max_iterations`, or the
+    /// Jacobian turns singular, the latest iterate is used.
+    pub fn integrate(&self, state: &T::State, dt: f64) -> T::State {
+        // Explicit Euler predictor
+        let mut delta = self.eom.compute_derivative(state).to_vector() * dt;
+
+        for _ in 0..self.max_iterations {
+            let f = self.derivative_at(state, &delta);
+            let residual = &delta - &f * dt;
+
+            // Jacobian of the residual, I - dt·∂f/∂Δ
+            let jacobian = na::DMatrix::identity(delta.len(), delta.len())
+                - numerical_jacobian(
+                    |perturbed| self.derivative_at(state, perturbed),
+                    &delta,
+                    self.differencing,
+                    self.differencing.default_step(),
+                ) * dt;
+
+            let Some(correction) = jacobian.lu().solve(&residual) else {
+                break;
+            };
+            delta -= &correction;
+
+            if correction.amax() <= self.tolerance * delta.amax().max(1.0) {
+                break;
+            }
+        }
+
+        state.clone() + T::Derivative::from_vector(&delta)
+    }
+
+    /// Derivative at `state` advanced by the increment `delta`
+    fn derivative_at(&self, state: &T::State, delta: &na::DVector<f64>) -> na::DVector<f64> {
+        self.eom
+            .compute_derivative(&(state.clone() + T::Derivative::from_vector(delta)))
+            .to_vector()
+    }
+}
+
+impl<T: EquationsOfMotion> Integrator for BackwardEuler<T>
+where
+    T::State: Clone + std::ops::Add<T::Derivative, Output = T::State>,
+    T::Derivative: DerivativeVector,
+{
+    type State = T::State;
+
+    fn integrate(&self, state: &T::State, dt: f64) -> T::State {
+        BackwardEuler::integrate(self, state, dt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::integrators::rk4::RK4;
+    use crate::models::State;
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::dynamics::SpacecraftDynamics;
+    use hifitime::Epoch;
+    use std::marker::PhantomData;
+
+    /// Rate damping strong enough to make the attitude dynamics stiff: the commanded torque
+    /// -gain·I·ω decays the body rate with time constant 1/gain
+    struct RateDampedAttitude<'a> {
+        gain: f64,
+        _phantom: PhantomData<&'a SimpleSat>,
+    }
+
+    impl<'a> RateDampedAttitude<'a> {
+        fn new(gain: f64) -> Self {
+            Self {
+                gain,
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<'a> EquationsOfMotion for RateDampedAttitude<'a> {
+        type State = State<'a, SimpleSat>;
+        type Derivative = StateDerivative;
+
+        fn compute_derivative(&self, state: &Self::State) -> Self::Derivative {
+            let torque = state.inertia_tensor * state.angular_velocity * -self.gain;
+            SpacecraftDynamics::<SimpleSat>::new(None, Some(torque)).compute_derivative(state)
+        }
+    }
+
+    #[test]
+    fn test_backward_euler_stable_where_rk4_diverges() {
+        let spacecraft = SimpleSat::default();
+        let initial = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_668.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.05, -0.02, 0.03),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let initial_rate = initial.angular_velocity.magnitude();
+
+        // gain·dt = 5 lies well outside RK4's stability region, which ends near 2.8
+        let dt = 0.1;
+        let gain = 50.0;
+
+        let rk4 = RK4::new(RateDampedAttitude::new(gain));
+        let implicit = BackwardEuler::new(RateDampedAttitude::new(gain));
+        let implicit_central = BackwardEuler::new(RateDampedAttitude::new(gain))
+            .with_differencing(Differencing::Central);
+
+        let mut explicit_state = initial.clone();
+        let mut implicit_state = initial.clone();
+        let mut central_state = initial.clone();
+        for _ in 0..50 {
+            explicit_state = rk4.integrate(&explicit_state, dt);
+            implicit_state = implicit.integrate(&implicit_state, dt);
+            central_state = implicit_central.integrate(&central_state, dt);
+        }
+
+        let explicit_rate = explicit_state.angular_velocity.magnitude();
+        assert!(
+            !explicit_rate.is_finite() || explicit_rate > 1e6 * initial_rate,
+            "RK4 rate {explicit_rate}"
+        );
+
+        // Backward Euler shrinks the rate by 1/(1 + gain·dt) each step
+        let implicit_rate = implicit_state.angular_velocity.magnitude();
+        assert!(
+            implicit_rate < 1e-9 * initial_rate,
+            "implicit rate {implicit_rate}"
+        );
+
+        // The orbit, which is not stiff, stays within the first-order error of about
+        // |g|·dt·t/2 ≈ 2 m of the RK4 reference
+        let orbit_only = RK4::new(SpacecraftDynamics::<SimpleSat>::new(None, None));
+        let mut reference = initial.clone();
+        for _ in 0..50 {
+            reference = orbit_only.integrate(&reference, dt);
+        }
+        assert!((implicit_state.position - reference.position).magnitude() < 3.0);
+
+        // Both Jacobians converge Newton to the same implicit step
+        assert!((central_state.position - implicit_state.position).magnitude() < 1e-6);
+        assert!(central_state.angular_velocity.magnitude() < 1e-9 * initial_rate);
+    }
+}
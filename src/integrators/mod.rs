@@ -1 +1,11 @@
+pub mod adaptive;
+pub mod backward_euler;
 pub mod rk4;
+pub mod two_body;
+
+/// Advances a state by a fixed time step, so propagators can be swapped behind one interface
+pub trait Integrator {
+    type State;
+
+    fn integrate(&self, state: &Self::State, dt: f64) -> Self::State;
+}
@@ -1,3 +1,4 @@
+use super::Integrator;
 use crate::physics::dynamics::EquationsOfMotion;
 
 pub struct RK4<T: EquationsOfMotion> {
@@ -6,13 +7,64 @@ pub struct RK4<T: EquationsOfMotion> {
 
 impl<T: EquationsOfMotion> RK4<T>
 where
-    T::State: Clone + std::ops::Add<Output = T::State> + std::ops::Mul<f64, Output = T::State>,
+    T::State: Clone + std::ops::Add<T::Derivative, Output = T::State>,
+    T::Derivative:
+        Clone + std::ops::Add<Output = T::Derivative> + std::ops::Mul<f64, Output = T::Derivative>,
 {
     pub fn new(eom: T) -> Self {
         RK4 { eom }
     }
 
+    /// Takes one step of `dt` seconds; a negative `dt` steps backward in time
     pub fn integrate(&self, state: &T::State, dt: f64) -> T::State {
+        state.clone() + self.increment(state, dt)
+    }
+
+    /// Takes the step as two half steps and also as one full step, returning the half-step
+    /// result together with a Richardson estimate of its local error, (half - full) / 15.
+    /// The estimate is expressed as a state increment, component by component.
+    pub fn integrate_with_error(&self, state: &T::State, dt: f64) -> (T::State, T::Derivative) {
+        let full = self.increment(state, dt);
+
+        let first_half = self.increment(state, dt / 2.0);
+        let midpoint = state.clone() + first_half.clone();
+        let second_half = self.increment(&midpoint, dt / 2.0);
+
+        let error = (first_half + second_half.clone() + full * -1.0) * (1.0 / 15.0);
+        (midpoint + second_half, error)
+    }
+
+    /// Takes one step like `integrate`, keeping the four stage derivatives and the
+    /// derivative at the end of the step so that states inside the step can be recovered
+    /// afterwards without integrating again. Costs one derivative evaluation more than
+    /// `integrate`.
+    pub fn integrate_dense(&self, state: &T::State, dt: f64) -> DenseStep<T::State, T::Derivative> {
+        let stages = self.stages(state, dt);
+        let increment = Self::combine(&stages, dt);
+        let end = state.clone() + increment.clone();
+        let end_derivative = self.eom.compute_derivative(&end);
+        DenseStep {
+            start: state.clone(),
+            end,
+            dt,
+            increment,
+            stages,
+            end_derivative,
+        }
+    }
+
+    /// Change in state over one RK4 step of size `dt`
+    fn increment(&self, state: &T::State, dt: f64) -> T::Derivative {
+        Self::combine(&self.stages(state, dt), dt)
+    }
+
+    fn combine(stages: &[T::Derivative; 4], dt: f64) -> T::Derivative {
+        let [k1, k2, k3, k4] = stages.clone();
+        (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (dt / 6.0)
+    }
+
+    /// The derivatives k1..k4 at the start, the two midpoint estimates and the end
+    fn stages(&self, state: &T::State, dt: f64) -> [T::Derivative; 4] {
         let k1 = self.eom.compute_derivative(state);
 
         let state2 = state.clone() + k1.clone() * (dt / 2.0);
@@ -24,6 +76,302 @@ where
         let state4 = state.clone() + k3.clone() * dt;
         let k4 = self.eom.compute_derivative(&state4);
 
-        state.clone() + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (dt / 6.0)
+        [k1, k2, k3, k4]
+    }
+}
+
+/// Restores constraints on a state that the dense-output interpolant, being linear in the
+/// components, does not keep
+pub trait Renormalize: Sized {
+    fn renormalize(self) -> Self {
+        self
+    }
+}
+
+/// One RK4 step with its stages retained, for dense output
+pub struct DenseStep<S, D> {
+    start: S,
+    end: S,
+    dt: f64,
+    increment: D,
+    stages: [D; 4],
+    end_derivative: D,
+}
+
+impl<S, D> DenseStep<S, D>
+where
+    S: Clone + std::ops::Add<D, Output = S>,
+    D: Clone + std::ops::Add<Output = D> + std::ops::Mul<f64, Output = D>,
+{
+    pub fn start(&self) -> &S {
+        &self.start
+    }
+
+    /// The state at the end of the step, as `RK4::integrate` returns it
+    pub fn end(&self) -> &S {
+        &self.end
+    }
+
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// The stage derivatives k1..k4: at the start, the two midpoint estimates and the end
+    /// estimate
+    pub fn stages(&self) -> &[D; 4] {
+        &self.stages
+    }
+
+    /// State at the fraction `theta` in [0, 1] of the step, from the cubic Hermite fit to
+    /// the states and derivatives at both ends, with an interpolation error of order dt⁴.
+    /// Like `integrate`, it leaves the time fields of the state alone. The attitude quaternion
+    /// of a full state is normalized, as the interpolant shortens it inside the step.
+    pub fn interpolate_within_step(&self, theta: f64) -> S
+    where
+        S: Renormalize,
+    {
+        let (theta2, theta3) = (theta * theta, theta * theta * theta);
+        let h01 = -2.0 * theta3 + 3.0 * theta2;
+        let h10 = theta3 - 2.0 * theta2 + theta;
+        let h11 = theta3 - theta2;
+
+        // h00 + h01 = 1, so the end state enters through the step's increment
+        (self.start.clone()
+            + (self.increment.clone() * h01
+                + self.stages[0].clone() * (self.dt * h10)
+                + self.end_derivative.clone() * (self.dt * h11)))
+            .renormalize()
+    }
+}
+
+impl<T: EquationsOfMotion> Integrator for RK4<T>
+where
+    T::State: Clone + std::ops::Add<T::Derivative, Output = T::State>,
+    T::Derivative:
+        Clone + std::ops::Add<Output = T::Derivative> + std::ops::Mul<f64, Output = T::Derivative>,
+{
+    type State = T::State;
+
+    fn integrate(&self, state: &T::State, dt: f64) -> T::State {
+        RK4::integrate(self, state, dt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::integrators::two_body::TwoBodyPropagator;
+    use crate::models::{State, StateDerivative, TranslationalState};
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::dynamics::{SpacecraftDynamics, TranslationalDynamics};
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+    use nalgebra as na;
+    use std::cell::Cell;
+
+    /// Wraps the spacecraft dynamics and counts derivative evaluations
+    struct CountingDynamics<'a> {
+        inner: SpacecraftDynamics<'a, SimpleSat>,
+        calls: Cell<usize>,
+    }
+
+    impl<'a> EquationsOfMotion for CountingDynamics<'a> {
+        type State = State<'a, SimpleSat>;
+        type Derivative = StateDerivative;
+
+        fn compute_derivative(&self, state: &Self::State) -> Self::Derivative {
+            self.calls.set(self.calls.get() + 1);
+            self.inner.compute_derivative(state)
+        }
+    }
+
+    #[test]
+    fn test_rk4_step_work() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let mut state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_668.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.05, 0.02, 0.01),
+            epoch,
+        );
+
+        let integrator = RK4::new(CountingDynamics {
+            inner: SpacecraftDynamics::new(None, None),
+            calls: Cell::new(0),
+        });
+
+        let steps = 1000;
+        for _ in 0..steps {
+            state = integrator.integrate(&state, 0.1);
+        }
+
+        // Exactly four derivative evaluations per step
+        assert_eq!(integrator.eom.calls.get(), 4 * steps);
+
+        // Stage arithmetic only touches the 13 dynamical components and the two masses, not
+        // the inertia tensor or the clock
+        assert_eq!(
+            std::mem::size_of::<StateDerivative>(),
+            15 * std::mem::size_of::<f64>()
+        );
+        assert!(
+            std::mem::size_of::<StateDerivative>() + std::mem::size_of::<na::Matrix3<f64>>()
+                < std::mem::size_of::<State<SimpleSat>>()
+        );
+
+        // Bookkeeping fields are carried through untouched
+        assert_eq!(state.epoch, epoch);
+        assert_eq!(state.mass, SimpleSat::MASS);
+    }
+
+    #[test]
+    fn test_rk4_integrates_fuel_mass() {
+        /// The spacecraft dynamics with propellant flowing out at a fixed rate
+        struct Depleting<'a> {
+            inner: SpacecraftDynamics<'a, SimpleSat>,
+            flow_rate: f64, // kg/s
+        }
+
+        impl<'a> EquationsOfMotion for Depleting<'a> {
+            type State = State<'a, SimpleSat>;
+            type Derivative = StateDerivative;
+
+            fn compute_derivative(&self, state: &Self::State) -> Self::Derivative {
+                StateDerivative {
+                    mass: -self.flow_rate,
+                    fuel_mass: -self.flow_rate,
+                    ..self.inner.compute_derivative(state)
+                }
+            }
+        }
+
+        let spacecraft = SimpleSat::default();
+        let mut initial = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_668.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        initial.fuel_mass = 20.0;
+
+        let integrator = RK4::new(Depleting {
+            inner: SpacecraftDynamics::new(None, None),
+            flow_rate: 0.05,
+        });
+        let state = integrator.integrate(&initial, 10.0);
+
+        // Both masses drop by the flow rate times the step
+        assert_relative_eq!(state.fuel_mass, 19.5, epsilon = 1e-12);
+        assert_relative_eq!(state.mass, initial.mass - 0.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_step_doubling_error_scales_with_fifth_power() {
+        let spacecraft = SimpleSat::default();
+        let elements = na::Vector6::new(8_000_000.0, 0.1, 0.5, 0.3, 1.0, 0.2);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let state = TranslationalState::new(&spacecraft, position, velocity);
+        let integrator = RK4::new(TranslationalDynamics::<SimpleSat>::new(None));
+
+        let (_, coarse_error) = integrator.integrate_with_error(&state, 60.0);
+        let (_, fine_error) = integrator.integrate_with_error(&state, 30.0);
+
+        // RK4's local error scales as dt^5, so halving the step shrinks each step's error
+        // ~32x; covering the same time span takes twice as many steps, leaving ~16x
+        let ratio = coarse_error.position.magnitude() / (2.0 * fine_error.position.magnitude());
+        assert!(ratio > 12.0 && ratio < 20.0, "error ratio {ratio}");
+    }
+
+    #[test]
+    fn test_dense_output_within_step() {
+        let spacecraft = SimpleSat::default();
+        let elements = na::Vector6::new(8_000_000.0, 0.1, 0.5, 0.3, 1.0, 0.2);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let state = TranslationalState::new(&spacecraft, position, velocity);
+        let integrator = RK4::new(TranslationalDynamics::<SimpleSat>::new(None));
+        let reference = TwoBodyPropagator::new();
+
+        let dt = 60.0;
+        let step = integrator.integrate_dense(&state, dt);
+        let end = integrator.integrate(&state, dt);
+        assert_eq!(step.end().position, end.position);
+
+        // The interpolant meets both ends of the step
+        let at_start = step.interpolate_within_step(0.0);
+        assert_eq!(at_start.position, position);
+        assert_eq!(at_start.velocity, velocity);
+        let at_end = step.interpolate_within_step(1.0);
+        assert_relative_eq!(at_end.position, end.position, max_relative = 1e-15);
+        assert_relative_eq!(at_end.velocity, end.velocity, max_relative = 1e-15);
+
+        // and follows the true orbit in between, to within a metre over the 420 km step
+        for i in 1..10 {
+            let theta = i as f64 / 10.0;
+            let interpolated = step.interpolate_within_step(theta);
+            let exact = reference.integrate(&state, theta * dt);
+            assert_relative_eq!(interpolated.position, exact.position, epsilon = 0.5);
+            assert_relative_eq!(interpolated.velocity, exact.velocity, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_dense_output_keeps_unit_quaternion() {
+        let spacecraft = SimpleSat::default();
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_668.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.3, -0.2, 0.1),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let integrator = RK4::new(SpacecraftDynamics::new(None, None));
+
+        // A long step at a fast tumble, so the cubic in the components cuts well inside
+        // the unit sphere
+        let step = integrator.integrate_dense(&state, 5.0);
+        for i in 1..10 {
+            let interpolated = step.interpolate_within_step(i as f64 / 10.0);
+            assert_relative_eq!(interpolated.quaternion.data.norm(), 1.0, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_backward_integration_round_trip() {
+        let spacecraft = SimpleSat::default();
+        // Perigee above 800 km, where the modelled drag is below 1e-40 N, so the motion is
+        // two-body to machine precision
+        let elements = na::Vector6::new(8_000_000.0, 0.1, 0.5, 0.3, 1.0, 0.2);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let initial = TranslationalState::new(&spacecraft, position, velocity);
+        let integrator = RK4::new(TranslationalDynamics::<SimpleSat>::new(None));
+        let reference = TwoBodyPropagator::new();
+
+        let (dt, steps) = (10.0, 300);
+        let propagate =
+            |state, dt: f64| (0..steps).fold(state, |state, _| integrator.integrate(&state, dt));
+
+        // Backward steps retrace the forward ones
+        let forward = propagate(initial.clone(), dt);
+        let round_trip = propagate(forward, -dt);
+        assert_relative_eq!(round_trip.position, position, epsilon = 1e-3);
+        assert_relative_eq!(round_trip.velocity, velocity, epsilon = 1e-6);
+
+        // and land on the analytic earlier state
+        let backward = propagate(initial.clone(), -dt);
+        let earlier = reference.integrate(&initial, -dt * steps as f64);
+        assert_relative_eq!(backward.position, earlier.position, epsilon = 5e-2);
+        assert_relative_eq!(backward.velocity, earlier.velocity, epsilon = 1e-5);
+        assert!((backward.position - position).magnitude() > 1e6);
     }
 }
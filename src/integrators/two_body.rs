@@ -0,0 +1,132 @@
+use super::Integrator;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::TranslationalState;
+use crate::physics::orbital::OrbitalMechanics;
+use crate::physics::orbital_errors::OrbitalErrors;
+use std::marker::PhantomData;
+
+/// Exact Keplerian propagation of the translational state, as a reference for checking the
+/// numerical integrators against point-mass gravity.
+///
+/// Through the `Integrator` trait it panics if the state is not on an elliptical orbit; use
+/// `try_integrate` where the state may be unbound.
+pub struct TwoBodyPropagator<'a, T: SpacecraftProperties> {
+    _phantom: PhantomData<&'a T>,
+}
+
+impl<'a, T: SpacecraftProperties> TwoBodyPropagator<'a, T> {
+    pub fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Advances `state` by `dt` seconds, failing if it is not on an elliptical orbit or
+    /// Kepler's equation does not converge
+    pub fn try_integrate(
+        &self,
+        state: &TranslationalState<'a, T>,
+        dt: f64,
+    ) -> Result<TranslationalState<'a, T>, OrbitalErrors> {
+        let (position, velocity) =
+            OrbitalMechanics::propagate_kepler(&state.position, &state.velocity, dt)?;
+
+        Ok(TranslationalState {
+            position,
+            velocity,
+            ..state.clone()
+        })
+    }
+}
+
+impl<'a, T: SpacecraftProperties> Default for TwoBodyPropagator<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: SpacecraftProperties> Integrator for TwoBodyPropagator<'a, T> {
+    type State = TranslationalState<'a, T>;
+
+    fn integrate(&self, state: &Self::State, dt: f64) -> Self::State {
+        self.try_integrate(state, dt)
+            .expect("two-body propagation needs an elliptical orbit")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::{MU_EARTH, WGS84_A};
+    use crate::integrators::rk4::RK4;
+    use crate::physics::dynamics::TranslationalDynamics;
+    use approx::assert_relative_eq;
+    use nalgebra as na;
+
+    fn propagate<I: Integrator>(integrator: &I, state: &I::State, dt: f64, steps: usize) -> I::State
+    where
+        I::State: Clone,
+    {
+        (0..steps).fold(state.clone(), |state, _| integrator.integrate(&state, dt))
+    }
+
+    #[test]
+    fn test_two_body_reference() {
+        let spacecraft = SimpleSat::default();
+        let elements = na::Vector6::new(
+            WGS84_A + 3_000_000.0,
+            0.2,
+            51.6_f64.to_radians(),
+            1.1,
+            0.7,
+            0.4,
+        );
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let initial = TranslationalState::new(&spacecraft, position, velocity);
+        let period = OrbitalMechanics::compute_orbital_period(elements[0]);
+        let reference = TwoBodyPropagator::new();
+
+        // A full period returns to the starting state, whether in one step or many
+        for steps in [1, 7] {
+            let state = propagate(&reference, &initial, period / steps as f64, steps);
+            assert_relative_eq!(state.position, position, max_relative = 1e-10);
+            assert_relative_eq!(state.velocity, velocity, max_relative = 1e-10);
+        }
+
+        // RK4 converges on the analytic state at fourth order. Gravity is point-mass, and
+        // the perigee at 1100 km is clear of any modelled drag.
+        let duration = period / 3.0;
+        let exact = reference.integrate(&initial, duration);
+        let rk4 = RK4::new(TranslationalDynamics::new(None));
+        let errors: Vec<f64> = [60.0, 30.0, 15.0]
+            .iter()
+            .map(|&dt| {
+                let steps = (duration / dt).round() as usize;
+                let state = propagate(&rk4, &initial, duration / steps as f64, steps);
+                (state.position - exact.position).magnitude()
+            })
+            .collect();
+
+        assert!(errors[0] > errors[1] && errors[1] > errors[2]);
+        for pair in errors.windows(2) {
+            let ratio = pair[0] / pair[1];
+            assert!(ratio > 12.0 && ratio < 20.0, "error ratio {ratio}");
+        }
+    }
+
+    #[test]
+    fn test_escape_state_is_an_error() {
+        let spacecraft = SimpleSat::default();
+        let radius = WGS84_A + 400_000.0;
+        let escape_speed = (2.0 * MU_EARTH / radius).sqrt();
+        let initial = TranslationalState::new(
+            &spacecraft,
+            na::Vector3::new(radius, 0.0, 0.0),
+            na::Vector3::new(0.0, 1.1 * escape_speed, 0.0),
+        );
+
+        let result = TwoBodyPropagator::new().try_integrate(&initial, 60.0);
+        assert!(matches!(result, Err(OrbitalErrors::UnboundOrbit { .. })));
+    }
+}
@@ -0,0 +1,34 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod config;
+pub mod constants;
+#[cfg(feature = "std")]
+pub mod constellation;
+#[cfg(feature = "std")]
+pub mod coordinates;
+#[cfg(feature = "std")]
+pub mod errors;
+#[cfg(feature = "std")]
+pub mod fsm;
+#[cfg(feature = "std")]
+pub mod gnc;
+#[cfg(feature = "std")]
+pub mod integrators;
+#[cfg(feature = "std")]
+pub mod models;
+pub mod numerics;
+pub mod physics;
+#[cfg(feature = "std")]
+pub mod scenarios;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(feature = "std")]
+pub mod telemetry;
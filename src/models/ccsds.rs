@@ -0,0 +1,290 @@
+use super::ccsds_errors::CcsdsErrors;
+use crate::constants::{MU_EARTH, PI};
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::numerics::quaternion::Quaternion;
+use crate::physics::orbital::OrbitalMechanics;
+use hifitime::{Epoch, TimeScale};
+use nalgebra as na;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// CCSDS Orbit Mean-Elements Message (CCSDS 502.0-B) in KVN form.
+///
+/// The elements are converted as if osculating. Messages from SGP4 (`MEAN_ELEMENT_THEORY`
+/// SGP/SGP4, `REF_FRAME` TEME) carry Brouwer mean elements, which differ from the
+/// osculating orbit by kilometres, and the frame is taken as GCRS; check `ref_frame` and
+/// `mean_element_theory` before relying on the result for more than a starting point.
+#[derive(Debug, Clone)]
+pub struct OmmFile {
+    pub object_name: Option<String>,
+    pub object_id: Option<String>,
+    pub ref_frame: String,
+    pub mean_element_theory: String,
+    pub epoch: Epoch,
+    /// Keplerian elements in the order `keplerian_to_cartesian` takes: semi-major axis [m],
+    /// eccentricity, inclination, RAAN, argument of periapsis and true anomaly [rad]
+    pub elements: na::Vector6<f64>,
+}
+
+impl OmmFile {
+    pub fn parse<P: AsRef<Path>>(path: P) -> Result<Self, CcsdsErrors> {
+        Self::from_kvn(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses the text of a KVN message. Keywords outside the header, metadata and mean
+    /// elements (e.g. the TLE parameters and covariance) are ignored.
+    pub fn from_kvn(text: &str) -> Result<Self, CcsdsErrors> {
+        let mut values = HashMap::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("COMMENT") {
+                continue;
+            }
+            if let Some((keyword, value)) = line.split_once('=') {
+                // Units may follow the value in square brackets
+                let value = value.split('[').next().unwrap_or_default().trim();
+                values.entry(keyword.trim()).or_insert(value);
+            }
+        }
+
+        let text_value = |keyword: &'static str| {
+            values
+                .get(keyword)
+                .copied()
+                .ok_or(CcsdsErrors::MissingKeyword(keyword))
+        };
+        let number = |keyword: &'static str| {
+            text_value(keyword).and_then(|value| parse_number(keyword, value))
+        };
+
+        let center = text_value("CENTER_NAME")?;
+        if !center.eq_ignore_ascii_case("EARTH") {
+            return Err(CcsdsErrors::UnsupportedCenter(center.to_string()));
+        }
+        let epoch = parse_epoch(text_value("EPOCH")?, text_value("TIME_SYSTEM")?)?;
+
+        // GM defaults to the crate's value when the message does not state the one it used
+        let mu = match values.get("GM") {
+            Some(value) => parse_number("GM", value)? * 1e9,
+            None => MU_EARTH,
+        };
+        let semi_major_axis = match values.get("SEMI_MAJOR_AXIS") {
+            Some(value) => parse_number("SEMI_MAJOR_AXIS", value)? * 1e3,
+            None => {
+                let mean_motion = number("MEAN_MOTION")? * 2.0 * PI / 86400.0; // rev/day to rad/s
+                (mu / (mean_motion * mean_motion)).cbrt()
+            }
+        };
+
+        let e = number("ECCENTRICITY")?;
+        let mean_anomaly = number("MEAN_ANOMALY")?.to_radians().rem_euclid(2.0 * PI);
+        let ecc_anomaly = OrbitalMechanics::mean_to_eccentric_anomaly(mean_anomaly, e, 1e-14, 50)?;
+        let true_anomaly = 2.0
+            * ((1.0 + e).sqrt() * (ecc_anomaly / 2.0).sin())
+                .atan2((1.0 - e).sqrt() * (ecc_anomaly / 2.0).cos());
+
+        Ok(Self {
+            object_name: values.get("OBJECT_NAME").map(|name| name.to_string()),
+            object_id: values.get("OBJECT_ID").map(|id| id.to_string()),
+            ref_frame: text_value("REF_FRAME")?.to_string(),
+            mean_element_theory: text_value("MEAN_ELEMENT_THEORY")?.to_string(),
+            epoch,
+            elements: na::Vector6::new(
+                semi_major_axis,
+                e,
+                number("INCLINATION")?.to_radians(),
+                number("RA_OF_ASC_NODE")?.to_radians(),
+                number("ARG_OF_PERICENTER")?.to_radians(),
+                true_anomaly,
+            ),
+        })
+    }
+
+    /// Position and velocity at `epoch` [m, m/s]
+    pub fn position_velocity(&self) -> (na::Vector3<f64>, na::Vector3<f64>) {
+        OrbitalMechanics::keplerian_to_cartesian(&self.elements)
+    }
+
+    /// Initial state on the message's orbit at its epoch, with the body frame aligned to
+    /// GCRS and at rest
+    pub fn initial_state<'a, T: SpacecraftProperties>(
+        &self,
+        spacecraft: &'a T,
+        inertia: na::Matrix3<f64>,
+    ) -> State<'a, T> {
+        let (position, velocity) = self.position_velocity();
+        State::new(
+            spacecraft,
+            inertia,
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            self.epoch,
+        )
+    }
+}
+
+fn parse_number(keyword: &str, value: &str) -> Result<f64, CcsdsErrors> {
+    value.parse().map_err(|_| invalid(keyword, value))
+}
+
+fn invalid(keyword: &str, value: &str) -> CcsdsErrors {
+    CcsdsErrors::InvalidValue {
+        keyword: keyword.to_string(),
+        value: value.to_string(),
+    }
+}
+
+/// Parses a CCSDS epoch, `YYYY-MM-DDThh:mm:ss[.d…]` or by day of year `YYYY-DDDThh:mm:ss[.d…]`
+fn parse_epoch(value: &str, time_system: &str) -> Result<Epoch, CcsdsErrors> {
+    let error = || invalid("EPOCH", value);
+    // Calendar fields out of the u8 range are rejected here, the rest by hifitime
+    let field = |text: &str| text.parse::<u8>().map_err(|_| error());
+
+    let (date, time) = value
+        .trim_end_matches('Z')
+        .split_once('T')
+        .unwrap_or((value, "00:00:00"));
+
+    let date: Vec<&str> = date.split('-').collect();
+    let year = date[0].parse::<i32>().map_err(|_| error())?;
+    let (month, day) = match date[1..] {
+        [month, day] => (field(month)?, field(day)?),
+        [day_of_year] => {
+            let day_of_year = day_of_year.parse::<u32>().map_err(|_| error())?;
+            month_and_day(year, day_of_year).ok_or_else(error)?
+        }
+        _ => return Err(error()),
+    };
+
+    let time: Vec<&str> = time.split(':').collect();
+    let [hour, minute, seconds] = time[..] else {
+        return Err(error());
+    };
+    let (whole_seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    let fraction = format!("0.{fraction}")
+        .parse::<f64>()
+        .map_err(|_| error())?;
+    let nanos = ((fraction * 1e9).round() as u32).min(999_999_999);
+
+    let (hour, minute, second) = (field(hour)?, field(minute)?, field(whole_seconds)?);
+    let time_scale = match time_system {
+        "UTC" => TimeScale::UTC,
+        "TAI" => TimeScale::TAI,
+        other => return Err(CcsdsErrors::UnsupportedTimeSystem(other.to_string())),
+    };
+    Epoch::maybe_from_gregorian(year, month, day, hour, minute, second, nanos, time_scale)
+        .map_err(|_| error())
+}
+
+/// Calendar month and day of the `day_of_year`th day of `year`, counting 1 January as 1
+fn month_and_day(year: i32, day_of_year: u32) -> Option<(u8, u8)> {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let month_lengths = [31, 28 + leap as u32, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+    let mut day = day_of_year;
+    for (month, length) in month_lengths.into_iter().enumerate() {
+        if day == 0 {
+            break;
+        }
+        if day <= length {
+            return Some((month as u8 + 1, day as u8));
+        }
+        day -= length;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use approx::assert_relative_eq;
+
+    /// The GOES-9 example from CCSDS 502.0-B-2, Figure 4-1
+    const GOES_9: &str = "\
+CCSDS_OMM_VERS = 2.0
+CREATION_DATE = 2007-065T16:00:00
+ORIGINATOR = NOAA/USA
+OBJECT_NAME = GOES-9
+OBJECT_ID = 1995-025A
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+TIME_SYSTEM = UTC
+MEAN_ELEMENT_THEORY = SGP/SGP4
+EPOCH = 2007-064T10:34:41.4264
+MEAN_MOTION = 1.00273272
+ECCENTRICITY = 0.0005013
+INCLINATION = 3.0539
+RA_OF_ASC_NODE = 81.7939
+ARG_OF_PERICENTER = 249.2363
+MEAN_ANOMALY = 150.1602
+GM = 398600.8 [km**3/s**2]
+EPHEMERIS_TYPE = 0
+CLASSIFICATION_TYPE = U
+NORAD_CAT_ID = 23581
+ELEMENT_SET_NO = 0925
+REV_AT_EPOCH = 4316
+BSTAR = 0.0001
+MEAN_MOTION_DOT = -0.00000113
+MEAN_MOTION_DDOT = 0.0
+";
+
+    #[test]
+    fn test_parse_omm() {
+        let omm = OmmFile::from_kvn(GOES_9).unwrap();
+
+        assert_eq!(omm.object_name.as_deref(), Some("GOES-9"));
+        assert_eq!(omm.ref_frame, "TEME");
+        // Day 64 of 2007 is 5 March
+        assert_eq!(
+            omm.epoch,
+            Epoch::from_gregorian_utc(2007, 3, 5, 10, 34, 41, 426_400_000)
+        );
+
+        // Geostationary: one revolution per sidereal day under the message's own GM
+        assert_relative_eq!(omm.elements[0], 42_164_327.736, epsilon = 1e-3);
+        assert_relative_eq!(omm.elements[2], 3.0539_f64.to_radians(), epsilon = 1e-15);
+
+        let spacecraft = SimpleSat::default();
+        let state = omm.initial_state(&spacecraft, SimpleSat::inertia_tensor());
+        assert_eq!(state.epoch, omm.epoch);
+        let elements = OrbitalMechanics::cartesian_to_keplerian(&state.position, &state.velocity);
+        assert_relative_eq!(elements[5], omm.elements[5], epsilon = 1e-9);
+
+        // On the way out to apoapsis the true anomaly runs ahead of the mean anomaly
+        assert!(omm.elements[5] > 150.1602_f64.to_radians());
+    }
+
+    #[test]
+    fn test_omm_errors() {
+        let without_elements = GOES_9.replace("ECCENTRICITY = 0.0005013\n", "");
+        assert!(matches!(
+            OmmFile::from_kvn(&without_elements),
+            Err(CcsdsErrors::MissingKeyword("ECCENTRICITY"))
+        ));
+
+        let lunar = GOES_9.replace("CENTER_NAME = EARTH", "CENTER_NAME = MOON");
+        assert!(matches!(
+            OmmFile::from_kvn(&lunar),
+            Err(CcsdsErrors::UnsupportedCenter(center)) if center == "MOON"
+        ));
+
+        // Impossible dates and times are rejected rather than wrapped or panicking
+        for bad_epoch in [
+            "2007-366T10:34:41",
+            "2007-13-05T10:34:41",
+            "2007-064T10:34:300",
+        ] {
+            let message = GOES_9.replace("2007-064T10:34:41", bad_epoch);
+            assert!(
+                matches!(
+                    OmmFile::from_kvn(&message),
+                    Err(CcsdsErrors::InvalidValue { keyword, .. }) if keyword == "EPOCH"
+                ),
+                "{bad_epoch}"
+            );
+        }
+    }
+}
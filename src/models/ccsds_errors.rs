@@ -0,0 +1,59 @@
+use crate::physics::orbital_errors::OrbitalErrors;
+use std::{error::Error, fmt, io};
+
+#[derive(Debug)]
+pub enum CcsdsErrors {
+    Io(io::Error),
+    MissingKeyword(&'static str),
+    InvalidValue {
+        keyword: String,
+        value: String,
+    },
+    /// Only Earth-centred messages can seed a simulation
+    UnsupportedCenter(String),
+    UnsupportedTimeSystem(String),
+    Orbital(OrbitalErrors),
+}
+
+impl fmt::Display for CcsdsErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CcsdsErrors::Io(e) => write!(f, "I/O error: {}", e),
+            CcsdsErrors::MissingKeyword(keyword) => {
+                write!(f, "Required keyword {} is missing", keyword)
+            }
+            CcsdsErrors::InvalidValue { keyword, value } => {
+                write!(f, "Invalid value for {}: {:?}", keyword, value)
+            }
+            CcsdsErrors::UnsupportedCenter(center) => {
+                write!(f, "Unsupported CENTER_NAME {} (only EARTH)", center)
+            }
+            CcsdsErrors::UnsupportedTimeSystem(time_system) => {
+                write!(f, "Unsupported TIME_SYSTEM {} (UTC or TAI)", time_system)
+            }
+            CcsdsErrors::Orbital(e) => write!(f, "Orbital error: {}", e),
+        }
+    }
+}
+
+impl Error for CcsdsErrors {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CcsdsErrors::Io(e) => Some(e),
+            CcsdsErrors::Orbital(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CcsdsErrors {
+    fn from(err: io::Error) -> Self {
+        CcsdsErrors::Io(err)
+    }
+}
+
+impl From<OrbitalErrors> for CcsdsErrors {
+    fn from(err: OrbitalErrors) -> Self {
+        CcsdsErrors::Orbital(err)
+    }
+}
@@ -0,0 +1,26 @@
+use std::{error::Error, fmt};
+
+#[derive(Debug, PartialEq)]
+pub enum InertiaErrors {
+    NotSymmetric { asymmetry: f64 },
+    NotPositiveDefinite { min_eigenvalue: f64 },
+}
+
+impl fmt::Display for InertiaErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InertiaErrors::NotSymmetric { asymmetry } => write!(
+                f,
+                "Inertia tensor is not symmetric (largest off-diagonal mismatch {:e} kg·m²)",
+                asymmetry
+            ),
+            InertiaErrors::NotPositiveDefinite { min_eigenvalue } => write!(
+                f,
+                "Inertia tensor is not positive definite (smallest principal moment {:e} kg·m²)",
+                min_eigenvalue
+            ),
+        }
+    }
+}
+
+impl Error for InertiaErrors {}
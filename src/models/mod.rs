@@ -1,3 +1,8 @@
+pub mod ccsds;
+pub mod ccsds_errors;
+pub mod inertia_errors;
 pub mod spacecraft;
 pub mod state;
-pub use state::State;
+pub mod translational_state;
+pub use state::{State, StateDerivative};
+pub use translational_state::{TranslationalDerivative, TranslationalState};
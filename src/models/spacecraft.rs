@@ -1,5 +1,29 @@
+use nalgebra as na;
+
 pub trait SpacecraftProperties {
     fn mass(&self) -> f64;
     fn drag_coefficient(&self) -> f64;
     fn reference_area(&self) -> f64;
+
+    /// Area projected onto the plane normal to the unit vector `direction`, given in the body
+    /// frame. Defaults to `reference_area` for shapes whose projection does not vary.
+    fn projected_area(&self, _direction: &na::Vector3<f64>) -> f64 {
+        self.reference_area()
+    }
+
+    /// Projected area averaged over all orientations, as seen by a tumbling body
+    fn mean_projected_area(&self) -> f64 {
+        self.reference_area()
+    }
+
+    /// Largest thrust the propulsion system can deliver [N]. Defaults to the force that
+    /// gives 100 m/s² at the current mass, the limit guidance used before it was configurable.
+    fn max_thrust(&self) -> f64 {
+        100.0 * self.mass()
+    }
+
+    /// Largest torque magnitude the attitude actuators can deliver [N⋅m]
+    fn max_control_torque(&self) -> f64 {
+        1.0
+    }
 }
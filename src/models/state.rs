@@ -1,7 +1,11 @@
+use crate::integrators::rk4::Renormalize;
+use crate::models::inertia_errors::InertiaErrors;
 use crate::models::spacecraft::SpacecraftProperties;
 use crate::numerics::quaternion::Quaternion;
-use hifitime::Epoch;
+use crate::physics::orbital::OrbitalMechanics;
+use hifitime::{Duration, Epoch};
 use nalgebra as na;
+use std::fmt;
 
 #[derive(Debug)]
 pub struct State<'a, T: SpacecraftProperties> {
@@ -17,13 +21,32 @@ pub struct State<'a, T: SpacecraftProperties> {
     pub quaternion: Quaternion,
     pub angular_velocity: na::Vector3<f64>,
 
-    // Time properties
+    // Time properties: `epoch` is always `start_epoch` plus `mission_elapsed_time`, so both
+    // should be changed through `advance_time` or `set_mission_elapsed_time`
+    pub start_epoch: Epoch,
     pub epoch: Epoch,
     pub mission_elapsed_time: f64,
     pub fuel_mass: f64,
 }
 
+/// Checks that `inertia` is a physical inertia tensor: symmetric, to a relative tolerance of
+/// 1e-9, and positive definite
+pub fn validate_inertia(inertia: &na::Matrix3<f64>) -> Result<(), InertiaErrors> {
+    let asymmetry = (inertia - inertia.transpose()).amax();
+    if asymmetry.is_nan() || asymmetry > 1e-9 * inertia.amax() {
+        return Err(InertiaErrors::NotSymmetric { asymmetry });
+    }
+
+    let min_eigenvalue = inertia.symmetric_eigenvalues().min();
+    if min_eigenvalue.is_nan() || min_eigenvalue <= 0.0 {
+        return Err(InertiaErrors::NotPositiveDefinite { min_eigenvalue });
+    }
+    Ok(())
+}
+
 impl<'a, T: SpacecraftProperties> State<'a, T> {
+    /// Builds the initial state. The inertia tensor is only checked in debug builds; use
+    /// `try_new` when it comes from user input.
     pub fn new(
         spacecraft: &'a T,
         inertia: na::Matrix3<f64>,
@@ -33,6 +56,11 @@ impl<'a, T: SpacecraftProperties> State<'a, T> {
         angular_velocity: na::Vector3<f64>,
         epoch: Epoch,
     ) -> Self {
+        debug_assert!(
+            validate_inertia(&inertia).is_ok(),
+            "invalid inertia tensor: {}",
+            validate_inertia(&inertia).unwrap_err()
+        );
         let mass = spacecraft.mass();
         State {
             spacecraft,
@@ -42,13 +70,136 @@ impl<'a, T: SpacecraftProperties> State<'a, T> {
             velocity,
             quaternion,
             angular_velocity,
+            start_epoch: epoch,
             epoch,
             mission_elapsed_time: 0.0,
             fuel_mass: mass * 0.1, // 10% of total mass is fuel
         }
     }
 
-    pub fn zero(spacecraft: &'a T) -> Self {
+    /// Same as `new`, but rejects an inertia tensor that is not symmetric and positive
+    /// definite, since it would silently corrupt the attitude dynamics
+    pub fn try_new(
+        spacecraft: &'a T,
+        inertia: na::Matrix3<f64>,
+        position: na::Vector3<f64>,
+        velocity: na::Vector3<f64>,
+        quaternion: Quaternion,
+        angular_velocity: na::Vector3<f64>,
+        epoch: Epoch,
+    ) -> Result<Self, InertiaErrors> {
+        validate_inertia(&inertia)?;
+        Ok(Self::new(
+            spacecraft,
+            inertia,
+            position,
+            velocity,
+            quaternion,
+            angular_velocity,
+            epoch,
+        ))
+    }
+
+    /// Moves the mission clock forward by `dt` seconds, keeping the epoch in step
+    pub fn advance_time(&mut self, dt: f64) {
+        self.set_mission_elapsed_time(self.mission_elapsed_time + dt);
+    }
+
+    /// Sets the mission elapsed time and derives the epoch from the start epoch, so that
+    /// repeated steps cannot make the two drift apart
+    pub fn set_mission_elapsed_time(&mut self, time: f64) {
+        self.mission_elapsed_time = time;
+        self.epoch = self.start_epoch + Duration::from_seconds(time);
+    }
+
+    /// Copy of the state starting afresh at `epoch`: the start epoch and epoch are both set
+    /// to it and the mission elapsed time to zero, while the dynamical state is kept, so one
+    /// initial state can seed runs at several epochs
+    pub fn with_epoch(&self, epoch: Epoch) -> Self {
+        State {
+            start_epoch: epoch,
+            epoch,
+            mission_elapsed_time: 0.0,
+            ..self.clone()
+        }
+    }
+
+    /// True when the mass and every dynamical component are finite, i.e. the propagation
+    /// has not blown up
+    pub fn is_finite(&self) -> bool {
+        self.mass.is_finite()
+            && self.fuel_mass.is_finite()
+            && self
+                .position
+                .iter()
+                .chain(self.velocity.iter())
+                .chain(self.quaternion.data.iter())
+                .chain(self.angular_velocity.iter())
+                .all(|x| x.is_finite())
+    }
+
+    /// Interpolates between this state and a later state `other`, with `fraction` running from
+    /// 0 (this state) to 1 (`other`). Position and velocity use a cubic Hermite fit through
+    /// both endpoints' positions and velocities; the attitude is normalized-linear.
+    pub fn interpolate(&self, other: &Self, fraction: f64) -> Self {
+        let h = other.mission_elapsed_time - self.mission_elapsed_time;
+        let s = fraction;
+        let s2 = s * s;
+        let s3 = s2 * s;
+
+        // Hermite basis functions and their derivatives with respect to s
+        let (h00, h10, h01, h11) = (
+            2.0 * s3 - 3.0 * s2 + 1.0,
+            s3 - 2.0 * s2 + s,
+            -2.0 * s3 + 3.0 * s2,
+            s3 - s2,
+        );
+        let (d00, d10, d01, d11) = (
+            6.0 * s2 - 6.0 * s,
+            3.0 * s2 - 4.0 * s + 1.0,
+            -6.0 * s2 + 6.0 * s,
+            3.0 * s2 - 2.0 * s,
+        );
+
+        let position = self.position * h00
+            + self.velocity * (h * h10)
+            + other.position * h01
+            + other.velocity * (h * h11);
+        let velocity = if h.abs() > 0.0 {
+            (self.position * d00 + other.position * d01) / h
+                + self.velocity * d10
+                + other.velocity * d11
+        } else {
+            self.velocity
+        };
+
+        // Take the shorter way round if the quaternions are in opposite hemispheres
+        let q_other = if self.quaternion.data.dot(&other.quaternion.data) < 0.0 {
+            -other.quaternion.data
+        } else {
+            other.quaternion.data
+        };
+
+        State {
+            spacecraft: self.spacecraft,
+            mass: self.mass + (other.mass - self.mass) * s,
+            inertia_tensor: self.inertia_tensor + (other.inertia_tensor - self.inertia_tensor) * s,
+            position,
+            velocity,
+            quaternion: Quaternion {
+                data: (self.quaternion.data * (1.0 - s) + q_other * s).normalize(),
+            },
+            angular_velocity: self.angular_velocity
+                + (other.angular_velocity - self.angular_velocity) * s,
+            start_epoch: self.start_epoch,
+            epoch: self.epoch + (other.epoch - self.epoch) * s,
+            mission_elapsed_time: self.mission_elapsed_time + h * s,
+            fuel_mass: self.fuel_mass + (other.fuel_mass - self.fuel_mass) * s,
+        }
+    }
+
+    /// Creates a state with all dynamical quantities zeroed at the given epoch
+    pub fn zero(spacecraft: &'a T, epoch: Epoch) -> Self {
         State {
             spacecraft,
             mass: spacecraft.mass(),
@@ -57,61 +208,84 @@ impl<'a, T: SpacecraftProperties> State<'a, T> {
             velocity: na::Vector3::zeros(),
             quaternion: Quaternion::new(1.0, 0.0, 0.0, 0.0),
             angular_velocity: na::Vector3::zeros(),
-            epoch: Epoch::now().expect("Failed to get current time"),
+            start_epoch: epoch,
+            epoch,
             mission_elapsed_time: 0.0,
             fuel_mass: 0.0,
         }
     }
 }
 
-impl<'a, T: SpacecraftProperties> std::ops::Add for State<'a, T> {
+/// Time derivative of the integrated part of a `State`. Each field holds the rate of
+/// change of the `State` field with the same name, so only the 13 dynamical components and
+/// the two masses are carried through the integrator's stage arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateDerivative {
+    pub position: na::Vector3<f64>,
+    pub velocity: na::Vector3<f64>,
+    pub quaternion: na::Vector4<f64>,
+    pub angular_velocity: na::Vector3<f64>,
+    pub mass: f64,      // kg/s
+    pub fuel_mass: f64, // kg/s
+}
+
+impl std::ops::Add for StateDerivative {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        State {
-            spacecraft: self.spacecraft,
-            mass: self.mass,
-            inertia_tensor: self.inertia_tensor,
+        StateDerivative {
             position: self.position + other.position,
             velocity: self.velocity + other.velocity,
-            quaternion: Quaternion::new(
-                self.quaternion.scalar() + other.quaternion.scalar(),
-                self.quaternion.vector()[0] + other.quaternion.vector()[0],
-                self.quaternion.vector()[1] + other.quaternion.vector()[1],
-                self.quaternion.vector()[2] + other.quaternion.vector()[2],
-            ),
+            quaternion: self.quaternion + other.quaternion,
             angular_velocity: self.angular_velocity + other.angular_velocity,
-            epoch: self.epoch,
-            mission_elapsed_time: self.mission_elapsed_time + other.mission_elapsed_time,
-            fuel_mass: self.fuel_mass,
+            mass: self.mass + other.mass,
+            fuel_mass: self.fuel_mass + other.fuel_mass,
         }
     }
 }
 
-impl<'a, T: SpacecraftProperties> std::ops::Mul<f64> for State<'a, T> {
+impl std::ops::Mul<f64> for StateDerivative {
     type Output = Self;
 
     fn mul(self, scalar: f64) -> Self {
-        State {
-            spacecraft: self.spacecraft,
-            mass: self.mass,
-            inertia_tensor: self.inertia_tensor,
+        StateDerivative {
             position: self.position * scalar,
             velocity: self.velocity * scalar,
-            quaternion: Quaternion::new(
-                self.quaternion.scalar() * scalar,
-                self.quaternion.vector()[0] * scalar,
-                self.quaternion.vector()[1] * scalar,
-                self.quaternion.vector()[2] * scalar,
-            ),
+            quaternion: self.quaternion * scalar,
             angular_velocity: self.angular_velocity * scalar,
-            epoch: self.epoch,
-            mission_elapsed_time: self.mission_elapsed_time * scalar,
-            fuel_mass: self.fuel_mass,
+            mass: self.mass * scalar,
+            fuel_mass: self.fuel_mass * scalar,
+        }
+    }
+}
+
+impl<'a, T: SpacecraftProperties> std::ops::Add<StateDerivative> for State<'a, T> {
+    type Output = Self;
+
+    /// Applies an already time-scaled derivative increment to the state
+    fn add(self, increment: StateDerivative) -> Self {
+        State {
+            position: self.position + increment.position,
+            velocity: self.velocity + increment.velocity,
+            quaternion: Quaternion {
+                data: self.quaternion.data + increment.quaternion,
+            },
+            angular_velocity: self.angular_velocity + increment.angular_velocity,
+            mass: self.mass + increment.mass,
+            fuel_mass: self.fuel_mass + increment.fuel_mass,
+            ..self
         }
     }
 }
 
+impl<T: SpacecraftProperties> Renormalize for State<'_, T> {
+    /// Brings the attitude quaternion back to unit length
+    fn renormalize(mut self) -> Self {
+        self.quaternion = self.quaternion.normalize();
+        self
+    }
+}
+
 impl<'a, T: SpacecraftProperties> Clone for State<'a, T> {
     fn clone(&self) -> Self {
         State {
@@ -122,9 +296,154 @@ impl<'a, T: SpacecraftProperties> Clone for State<'a, T> {
             velocity: self.velocity,
             quaternion: self.quaternion.clone(),
             angular_velocity: self.angular_velocity,
+            start_epoch: self.start_epoch,
             epoch: self.epoch,
             mission_elapsed_time: self.mission_elapsed_time,
             fuel_mass: self.fuel_mass,
         }
     }
 }
+
+/// One-line summary of the orbit and attitude, with the orbital elements computed on demand
+impl<'a, T: SpacecraftProperties> fmt::Display for State<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements = OrbitalMechanics::cartesian_to_keplerian(&self.position, &self.velocity);
+        let (axis, angle) = self.quaternion.to_axis_angle();
+        write!(
+            f,
+            "altitude {:.3} km, speed {:.3} km/s, a {:.3} km, e {:.6}, i {:.3}°, \
+             attitude {:.3}° about [{:.4}, {:.4}, {:.4}]",
+            OrbitalMechanics::current_altitude(&self.position) / 1e3,
+            self.velocity.magnitude() / 1e3,
+            elements[0] / 1e3,
+            elements[1],
+            elements[2].to_degrees(),
+            angle.to_degrees(),
+            axis.x,
+            axis.y,
+            axis.z,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use approx::assert_relative_eq;
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    #[test]
+    fn test_advance_time_keeps_epoch_and_met_consistent() {
+        let spacecraft = SimpleSat::default();
+        let start = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let mut state = State::zero(&spacecraft, start);
+
+        // A step that is not exactly representable in binary, a million times over
+        for _ in 0..1_000_000 {
+            state.advance_time(0.01);
+        }
+
+        assert_relative_eq!(state.mission_elapsed_time, 10_000.0, epsilon = 1e-6);
+        assert_eq!(
+            state.epoch,
+            start + Duration::from_seconds(state.mission_elapsed_time)
+        );
+        assert_eq!(state.start_epoch, start);
+
+        state.set_mission_elapsed_time(60.0);
+        assert_eq!(state.epoch, start + Duration::from_seconds(60.0));
+    }
+
+    #[test]
+    fn test_with_epoch_changes_only_time() {
+        let spacecraft = SimpleSat::default();
+        let start = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let mut state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_668.0, 0.0),
+            Quaternion::new(0.5, 0.5, -0.5, 0.5),
+            na::Vector3::new(0.01, 0.02, -0.03),
+            start,
+        );
+        state.fuel_mass = 12.0;
+        state.advance_time(90.0);
+
+        let rerun_epoch = Epoch::from_gregorian_utc(2025, 6, 21, 12, 0, 0, 0);
+        let rerun = state.with_epoch(rerun_epoch);
+        assert_eq!(rerun.start_epoch, rerun_epoch);
+        assert_eq!(rerun.epoch, rerun_epoch);
+        assert_eq!(rerun.mission_elapsed_time, 0.0);
+
+        // The dynamical state, mass and fuel are untouched
+        assert_eq!(rerun.position, state.position);
+        assert_eq!(rerun.velocity, state.velocity);
+        assert_eq!(rerun.quaternion.data, state.quaternion.data);
+        assert_eq!(rerun.angular_velocity, state.angular_velocity);
+        assert_eq!(rerun.inertia_tensor, state.inertia_tensor);
+        assert_eq!((rerun.mass, rerun.fuel_mass), (state.mass, state.fuel_mass));
+    }
+
+    #[test]
+    fn test_inertia_validation() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let state = |inertia| {
+            State::try_new(
+                &spacecraft,
+                inertia,
+                na::Vector3::new(6_778_137.0, 0.0, 0.0),
+                na::Vector3::new(0.0, 7_668.0, 0.0),
+                Quaternion::new(1.0, 0.0, 0.0, 0.0),
+                na::Vector3::zeros(),
+                epoch,
+            )
+        };
+
+        assert!(state(SimpleSat::inertia_tensor()).is_ok());
+
+        // A products-of-inertia term entered on one side of the diagonal only
+        let mut lopsided = SimpleSat::inertia_tensor();
+        lopsided[(0, 1)] = 0.5;
+        assert!(matches!(
+            state(lopsided),
+            Err(InertiaErrors::NotSymmetric { .. })
+        ));
+
+        // Symmetric, but one principal moment is negative
+        let indefinite = na::Matrix3::new(1.0, 2.0, 0.0, 2.0, 1.0, 0.0, 0.0, 0.0, 1.0);
+        assert_eq!(
+            state(indefinite).unwrap_err(),
+            InertiaErrors::NotPositiveDefinite {
+                min_eigenvalue: indefinite.symmetric_eigenvalues().min()
+            }
+        );
+        assert!(state(na::Matrix3::zeros()).is_err());
+    }
+
+    #[test]
+    fn test_display_summary() {
+        let spacecraft = SimpleSat::default();
+        let (position, velocity) =
+            OrbitalMechanics::circular_orbit_state(400_000.0, 51.6_f64.to_radians(), 0.3, 1.1);
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(FRAC_1_SQRT_2, 0.0, 0.0, FRAC_1_SQRT_2),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+
+        let summary = state.to_string();
+        assert!(summary.contains("altitude 400.000 km"), "{summary}");
+        assert!(summary.contains("i 51.600°"), "{summary}");
+        assert!(
+            summary.contains("attitude 90.000° about [0.0000, 0.0000, 1.0000]"),
+            "{summary}"
+        );
+    }
+}
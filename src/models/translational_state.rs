@@ -0,0 +1,102 @@
+use super::State;
+use crate::constants::TUMBLING_RATE_THRESHOLD;
+use crate::integrators::rk4::Renormalize;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::physics::drag::attitude_drag_area;
+use nalgebra as na;
+
+/// Orbit-only state for propagation without attitude
+#[derive(Debug)]
+pub struct TranslationalState<'a, T: SpacecraftProperties> {
+    pub spacecraft: &'a T,
+    pub mass: f64,
+    pub position: na::Vector3<f64>,
+    pub velocity: na::Vector3<f64>,
+    /// Area facing the flow for drag [m²], held fixed since the attitude is not propagated
+    pub drag_area: f64,
+}
+
+impl<'a, T: SpacecraftProperties> TranslationalState<'a, T> {
+    /// A state with no attitude to go by, so drag sees the orientation-averaged area
+    pub fn new(spacecraft: &'a T, position: na::Vector3<f64>, velocity: na::Vector3<f64>) -> Self {
+        TranslationalState {
+            spacecraft,
+            mass: spacecraft.mass(),
+            position,
+            velocity,
+            drag_area: spacecraft.mean_projected_area(),
+        }
+    }
+}
+
+/// Keeps the drag area the full state's attitude presents, as `SpacecraftDynamics` would
+/// use it
+impl<'a, T: SpacecraftProperties> From<&State<'a, T>> for TranslationalState<'a, T> {
+    fn from(state: &State<'a, T>) -> Self {
+        TranslationalState {
+            spacecraft: state.spacecraft,
+            mass: state.mass,
+            position: state.position,
+            velocity: state.velocity,
+            drag_area: attitude_drag_area(state, TUMBLING_RATE_THRESHOLD),
+        }
+    }
+}
+
+impl<'a, T: SpacecraftProperties> Clone for TranslationalState<'a, T> {
+    fn clone(&self) -> Self {
+        TranslationalState {
+            spacecraft: self.spacecraft,
+            mass: self.mass,
+            position: self.position,
+            velocity: self.velocity,
+            drag_area: self.drag_area,
+        }
+    }
+}
+
+/// Time derivative of a `TranslationalState`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TranslationalDerivative {
+    pub position: na::Vector3<f64>,
+    pub velocity: na::Vector3<f64>,
+}
+
+impl std::ops::Add for TranslationalDerivative {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        TranslationalDerivative {
+            position: self.position + other.position,
+            velocity: self.velocity + other.velocity,
+        }
+    }
+}
+
+impl std::ops::Mul<f64> for TranslationalDerivative {
+    type Output = Self;
+
+    fn mul(self, scalar: f64) -> Self {
+        TranslationalDerivative {
+            position: self.position * scalar,
+            velocity: self.velocity * scalar,
+        }
+    }
+}
+
+impl<'a, T: SpacecraftProperties> std::ops::Add<TranslationalDerivative>
+    for TranslationalState<'a, T>
+{
+    type Output = Self;
+
+    /// Applies an already time-scaled derivative increment to the state
+    fn add(self, increment: TranslationalDerivative) -> Self {
+        TranslationalState {
+            position: self.position + increment.position,
+            velocity: self.velocity + increment.velocity,
+            ..self
+        }
+    }
+}
+
+impl<T: SpacecraftProperties> Renormalize for TranslationalState<'_, T> {}
@@ -0,0 +1,100 @@
+use nalgebra as na;
+
+/// Finite-difference scheme for `numerical_jacobian`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Differencing {
+    /// (f(x + h) - f(x)) / h: one extra evaluation per column, error of order h
+    #[default]
+    Forward,
+    /// (f(x + h) - f(x - h)) / 2h: two evaluations per column, error of order h²
+    Central,
+}
+
+impl Differencing {
+    /// Relative step balancing truncation against rounding error for this scheme: √ε for
+    /// forward differences and ∛ε for central differences
+    pub fn default_step(&self) -> f64 {
+        match self {
+            Differencing::Forward => f64::EPSILON.sqrt(),
+            Differencing::Central => f64::EPSILON.cbrt(),
+        }
+    }
+}
+
+/// Jacobian of `f` at `x` by finite differences. Component j is perturbed by
+/// `relative_step`·max(|x_j|, 1), so large and small components see comparable relative
+/// perturbations.
+pub fn numerical_jacobian<F>(
+    f: F,
+    x: &na::DVector<f64>,
+    differencing: Differencing,
+    relative_step: f64,
+) -> na::DMatrix<f64>
+where
+    F: Fn(&na::DVector<f64>) -> na::DVector<f64>,
+{
+    let f_x = match differencing {
+        Differencing::Forward => Some(f(x)),
+        Differencing::Central => None,
+    };
+
+    let columns: Vec<na::DVector<f64>> = (0..x.len())
+        .map(|j| {
+            let h = relative_step * x[j].abs().max(1.0);
+            let shifted = |offset: f64| {
+                let mut shifted = x.clone();
+                shifted[j] += offset;
+                f(&shifted)
+            };
+            match &f_x {
+                Some(f_x) => (shifted(h) - f_x) / h,
+                None => (shifted(h) - shifted(-h)) / (2.0 * h),
+            }
+        })
+        .collect();
+
+    let rows = columns.first().map_or(0, |column| column.len());
+    na::DMatrix::from_fn(rows, x.len(), |i, j| columns[j][i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::MU_EARTH;
+    use crate::physics::gravity::gravity_acceleration;
+
+    #[test]
+    fn test_central_differences_beat_forward() {
+        let r = na::Vector3::<f64>::new(5_000_000.0, -3_500_000.0, 2_400_000.0);
+
+        // Analytic gravity gradient, μ/r³ (3 r̂ r̂ᵀ - I)
+        let r_unit = r.normalize();
+        let analytic = (r_unit * r_unit.transpose() * 3.0 - na::Matrix3::identity())
+            * (MU_EARTH / r.magnitude().powi(3));
+
+        let gravity = |x: &na::DVector<f64>| {
+            let acceleration = gravity_acceleration(&na::Vector3::new(x[0], x[1], x[2]));
+            na::DVector::from_column_slice(acceleration.as_slice())
+        };
+        let x = na::DVector::from_column_slice(r.as_slice());
+        let error = |differencing: Differencing, step: f64| {
+            let jacobian = numerical_jacobian(gravity, &x, differencing, step);
+            let jacobian = na::Matrix3::from_fn(|i, j| jacobian[(i, j)]);
+            (jacobian - analytic).norm() / analytic.norm()
+        };
+
+        // At the same step, central differences remove the first-order truncation error
+        let step = 1e-4;
+        let forward = error(Differencing::Forward, step);
+        let central = error(Differencing::Central, step);
+        assert!(forward < 1e-3, "forward {forward:e}");
+        assert!(
+            central < 1e-3 * forward,
+            "central {central:e} vs forward {forward:e}"
+        );
+
+        // And each scheme is accurate at its own default step
+        assert!(error(Differencing::Forward, Differencing::Forward.default_step()) < 1e-6);
+        assert!(error(Differencing::Central, Differencing::Central.default_step()) < 1e-9);
+    }
+}
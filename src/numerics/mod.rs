@@ -1 +1,3 @@
+#[cfg(feature = "std")]
+pub mod jacobian;
 pub mod quaternion;
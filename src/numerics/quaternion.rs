@@ -1,4 +1,7 @@
 use nalgebra as na;
+// Float methods come from ComplexField without std; the test harness links std anyway
+#[cfg(not(any(feature = "std", test)))]
+use nalgebra::{ComplexField, RealField};
 
 /// Quaternion utilities for spacecraft attitude dynamics
 /// Following scalar-first convention: q = [q0; q1; q2; q3] = [w; x; y; z]
@@ -29,6 +32,11 @@ impl Quaternion {
         }
     }
 
+    /// Inverse rotation for a unit quaternion
+    pub fn conjugate(&self) -> Self {
+        Quaternion::new(self.data[0], -self.data[1], -self.data[2], -self.data[3])
+    }
+
     pub fn to_rotation_matrix(&self) -> na::Matrix3<f64> {
         let q0 = self.data[0];
         let q1 = self.data[1];
@@ -48,6 +56,70 @@ impl Quaternion {
         )
     }
 
+    /// Quaternion for the rotation matrix `m`, the inverse of `to_rotation_matrix`. Follows
+    /// Shepperd's method, taking the square root of whichever component is largest so the
+    /// division stays well conditioned. The scalar part is kept non-negative.
+    pub fn from_rotation_matrix(m: &na::Matrix3<f64>) -> Self {
+        let trace = m.trace();
+        let q = if trace > m[(0, 0)].max(m[(1, 1)]).max(m[(2, 2)]) {
+            let s = 2.0 * (1.0 + trace).sqrt(); // 4 q0
+            Quaternion::new(
+                s / 4.0,
+                (m[(2, 1)] - m[(1, 2)]) / s,
+                (m[(0, 2)] - m[(2, 0)]) / s,
+                (m[(1, 0)] - m[(0, 1)]) / s,
+            )
+        } else if m[(0, 0)] >= m[(1, 1)] && m[(0, 0)] >= m[(2, 2)] {
+            let s = 2.0 * (1.0 + m[(0, 0)] - m[(1, 1)] - m[(2, 2)]).sqrt(); // 4 q1
+            Quaternion::new(
+                (m[(2, 1)] - m[(1, 2)]) / s,
+                s / 4.0,
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                (m[(0, 2)] + m[(2, 0)]) / s,
+            )
+        } else if m[(1, 1)] >= m[(2, 2)] {
+            let s = 2.0 * (1.0 + m[(1, 1)] - m[(0, 0)] - m[(2, 2)]).sqrt(); // 4 q2
+            Quaternion::new(
+                (m[(0, 2)] - m[(2, 0)]) / s,
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                s / 4.0,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+            )
+        } else {
+            let s = 2.0 * (1.0 + m[(2, 2)] - m[(0, 0)] - m[(1, 1)]).sqrt(); // 4 q3
+            Quaternion::new(
+                (m[(1, 0)] - m[(0, 1)]) / s,
+                (m[(0, 2)] + m[(2, 0)]) / s,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+                s / 4.0,
+            )
+        };
+
+        if q.scalar() < 0.0 {
+            Quaternion { data: -q.data }
+        } else {
+            q
+        }
+    }
+
+    /// Unit rotation axis and angle [rad] of the shortest rotation, so the angle lies in
+    /// [0, π]. The axis is arbitrary (body x) for the identity.
+    pub fn to_axis_angle(&self) -> (na::Vector3<f64>, f64) {
+        let q = self.normalize();
+        let vector = q.vector();
+        let sin_half = vector.magnitude();
+        if sin_half < 1e-15 {
+            return (na::Vector3::x(), 0.0);
+        }
+
+        let axis = vector / sin_half;
+        if q.scalar() < 0.0 {
+            (-axis, 2.0 * sin_half.atan2(-q.scalar()))
+        } else {
+            (axis, 2.0 * sin_half.atan2(q.scalar()))
+        }
+    }
+
     #[allow(dead_code)]
     pub fn multiply(&self, other: &Quaternion) -> Self {
         let q1 = self;
@@ -69,6 +141,10 @@ impl Quaternion {
     }
 }
 
+/// Attitude kinematics q̇ = ½ q ⊗ [0, w] (Hamilton product), with `w` the angular velocity
+/// of the body relative to the inertial frame, expressed in body axes. `q` rotates body
+/// vectors into the inertial frame (see `to_rotation_matrix`), so this is the quaternion
+/// form of Ṙ = R [w]×. An inertial-frame rate would enter on the other side, ½ [0, w] ⊗ q.
 pub fn compute_quaternion_derivative(q: &Quaternion, w: &na::Vector3<f64>) -> Quaternion {
     let wx = w[0];
     let wy = w[1];
@@ -81,3 +157,157 @@ pub fn compute_quaternion_derivative(q: &Quaternion, w: &na::Vector3<f64>) -> Qu
         0.5 * (q.data[0] * wz + q.data[1] * wy - q.data[2] * wx),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use core::f64::consts::{FRAC_1_SQRT_2, PI};
+
+    fn pure(w: &na::Vector3<f64>) -> Quaternion {
+        Quaternion::new(0.0, w.x, w.y, w.z)
+    }
+
+    /// Integrates the kinematics at a constant body rate with RK4
+    fn propagate(q: &Quaternion, w: &na::Vector3<f64>, duration: f64, steps: usize) -> Quaternion {
+        let dt = duration / steps as f64;
+        let mut q = q.clone();
+        let rate =
+            |data: na::Vector4<f64>| compute_quaternion_derivative(&Quaternion { data }, w).data;
+        for _ in 0..steps {
+            let k1 = rate(q.data);
+            let k2 = rate(q.data + k1 * (dt / 2.0));
+            let k3 = rate(q.data + k2 * (dt / 2.0));
+            let k4 = rate(q.data + k3 * dt);
+            q.data += (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (dt / 6.0);
+        }
+        q
+    }
+
+    #[test]
+    fn test_rotation_matrix_round_trip() {
+        // Rotations that exercise each branch: small, and near half turns about x, y and z
+        let rotations = [
+            Quaternion::new(0.9, 0.1, -0.2, 0.3),
+            Quaternion::new(0.05, 0.9, 0.3, -0.2),
+            Quaternion::new(0.05, -0.3, 0.9, 0.2),
+            Quaternion::new(0.05, 0.2, -0.3, 0.9),
+        ];
+        for q in rotations {
+            let q = q.normalize();
+            let recovered = Quaternion::from_rotation_matrix(&q.to_rotation_matrix());
+            assert_relative_eq!(recovered.data, q.data, epsilon = 1e-14);
+        }
+
+        // q and -q are the same rotation; the positive scalar is returned
+        let q = Quaternion::new(-0.5, 0.5, 0.5, 0.5);
+        let recovered = Quaternion::from_rotation_matrix(&q.to_rotation_matrix());
+        assert_relative_eq!(recovered.data, -q.data, epsilon = 1e-14);
+    }
+
+    #[test]
+    fn test_axis_angle() {
+        let axis = na::Vector3::new(1.0, -2.0, 2.0) / 3.0;
+        let half = 0.6_f64;
+        let q = Quaternion::new(
+            half.cos(),
+            axis.x * half.sin(),
+            axis.y * half.sin(),
+            axis.z * half.sin(),
+        );
+        let (recovered_axis, angle) = q.to_axis_angle();
+        assert_relative_eq!(recovered_axis, axis, epsilon = 1e-15);
+        assert_relative_eq!(angle, 1.2, epsilon = 1e-15);
+
+        // -q is the same rotation, reported the same way
+        let (recovered_axis, angle) = Quaternion { data: -q.data }.to_axis_angle();
+        assert_relative_eq!(recovered_axis, axis, epsilon = 1e-15);
+        assert_relative_eq!(angle, 1.2, epsilon = 1e-15);
+
+        // A turn of more than π comes back as the shorter one the other way
+        let q = Quaternion::new((2.0_f64).cos(), 0.0, 0.0, (2.0_f64).sin());
+        let (recovered_axis, angle) = q.to_axis_angle();
+        assert_relative_eq!(recovered_axis, -na::Vector3::z(), epsilon = 1e-15);
+        assert_relative_eq!(angle, 2.0 * PI - 4.0, epsilon = 1e-14);
+
+        assert_eq!(Quaternion::new(1.0, 0.0, 0.0, 0.0).to_axis_angle().1, 0.0);
+    }
+
+    #[test]
+    fn test_derivative_is_body_rate_form() {
+        // 90 degrees about z, turning about z
+        let q = Quaternion::new(FRAC_1_SQRT_2, 0.0, 0.0, FRAC_1_SQRT_2);
+        let derivative = compute_quaternion_derivative(&q, &na::Vector3::z());
+        assert_relative_eq!(
+            derivative.data,
+            na::Vector4::new(-0.5 * FRAC_1_SQRT_2, 0.0, 0.0, 0.5 * FRAC_1_SQRT_2),
+            epsilon = 1e-15
+        );
+
+        // In general the rate multiplies from the right, ½ q ⊗ [0, w], not from the left
+        // (`multiply` normalizes its product, so compare directions)
+        let q = Quaternion::new(0.8, 0.1, -0.5, 0.3).normalize();
+        let w = na::Vector3::new(0.3, -0.2, 0.5);
+        let derivative = compute_quaternion_derivative(&q, &w);
+        assert_relative_eq!(derivative.data.norm(), 0.5 * w.norm(), epsilon = 1e-15);
+        assert_relative_eq!(
+            derivative.normalize().data,
+            q.multiply(&pure(&w)).data,
+            epsilon = 1e-15
+        );
+        assert!((derivative.normalize().data - pure(&w).multiply(&q).data).norm() > 0.1);
+
+        // ... which matches the rotation matrix kinematics Ṙ = R [w]× by central differences
+        let h = 1e-6;
+        let r_dot = (Quaternion {
+            data: q.data + derivative.data * h,
+        }
+        .to_rotation_matrix()
+            - Quaternion {
+                data: q.data - derivative.data * h,
+            }
+            .to_rotation_matrix())
+            / (2.0 * h);
+        assert_relative_eq!(
+            r_dot,
+            q.to_rotation_matrix() * w.cross_matrix(),
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn test_full_rotation_returns_to_start() {
+        let start = Quaternion::new(0.8, 0.1, -0.5, 0.3).normalize();
+        let axis = na::Vector3::new(1.0, 2.0, -2.0) / 3.0;
+        let rate = 0.1; // rad/s
+        let w = axis * rate;
+        let period = 2.0 * PI / rate;
+
+        // A body rate turns the body about its own axis, whose inertial direction stays put
+        let quarter = propagate(&start, &w, period / 4.0, 500);
+        assert_relative_eq!(
+            quarter.to_rotation_matrix() * axis,
+            start.to_rotation_matrix() * axis,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            quarter.to_rotation_matrix(),
+            start.to_rotation_matrix()
+                * na::Rotation3::from_axis_angle(&na::Unit::new_normalize(axis), PI / 2.0).matrix(),
+            epsilon = 1e-12
+        );
+
+        // One turn brings the attitude back, with the quaternion on the other sheet of the
+        // double cover; a second turn brings the quaternion back too
+        let one_turn = propagate(&start, &w, period, 2000);
+        assert_relative_eq!(
+            one_turn.to_rotation_matrix(),
+            start.to_rotation_matrix(),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(one_turn.data, -start.data, epsilon = 1e-12);
+
+        let two_turns = propagate(&one_turn, &w, period, 2000);
+        assert_relative_eq!(two_turns.data, start.data, epsilon = 1e-12);
+    }
+}
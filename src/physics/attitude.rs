@@ -1,25 +1,60 @@
+use crate::coordinates::coordinate_transformation::gcrs_to_rsw_rotation;
 use crate::models::spacecraft::SpacecraftProperties;
 use crate::models::State;
 use crate::numerics::quaternion::{compute_quaternion_derivative, Quaternion};
 use nalgebra as na;
 
+/// Gravity-gradient torque in the body frame, T = 3μ/r³ (r̂_b × I r̂_b).
+///
+/// `to_rotation_matrix` maps body vectors into the inertial frame, so its transpose takes
+/// the inertial nadir-to-spacecraft direction into body coordinates.
 pub fn calculate_torque<T: SpacecraftProperties>(state: &State<T>) -> na::Vector3<f64> {
-    // Gravity gradient torque
     let r = state.position;
     let r_mag = r.magnitude();
     let r_unit = r.normalize();
 
     let inertia = &state.inertia_tensor;
 
-    // Transform body frame to inertial frame
+    // Transform the inertial position direction into the body frame
     let rot_matrix = state.quaternion.to_rotation_matrix();
     let z_body = rot_matrix.transpose() * r_unit;
 
     // Calculate gravity gradient torque
-    (3.0 * crate::constants::G * crate::constants::M_EARTH / (2.0 * r_mag.powi(3)))
-        * z_body.cross(&(inertia * z_body))
+    (3.0 * crate::constants::MU_EARTH / r_mag.powi(3)) * z_body.cross(&(inertia * z_body))
 }
 
+/// Stable gravity-gradient equilibrium attitude for `inertia` at the GCRS state `r`, `v`,
+/// as the quaternion rotating body vectors into GCRS.
+///
+/// The principal axes are laid along the orbit frame in the Lagrange-stable arrangement:
+/// minor axis on nadir, intermediate along track and major on the orbit normal (the body
+/// keeping a right-handed triad, it points opposite the angular momentum). Held at orbit
+/// rate about the normal, the attitude feels no gravity-gradient torque, and small pitch or
+/// roll offsets are pulled back; yaw about nadir is only held gyroscopically.
+pub fn gravity_gradient_equilibrium(
+    inertia: &na::Matrix3<f64>,
+    r: &na::Vector3<f64>,
+    v: &na::Vector3<f64>,
+) -> Quaternion {
+    let eigen = inertia.symmetric_eigen();
+    let mut order = [0, 1, 2];
+    order.sort_by(|&a, &b| eigen.eigenvalues[a].total_cmp(&eigen.eigenvalues[b]));
+
+    let minor = eigen.eigenvectors.column(order[0]).into_owned();
+    let intermediate = eigen.eigenvectors.column(order[1]).into_owned();
+    let body_axes = na::Matrix3::from_columns(&[minor, intermediate, minor.cross(&intermediate)]);
+
+    // Nadir, along track and the negative orbit normal, also a right-handed triad
+    let rsw = gcrs_to_rsw_rotation(r, v);
+    let orbit_axes =
+        na::Matrix3::from_columns(&[-rsw.column(0), rsw.column(1).into_owned(), -rsw.column(2)]);
+
+    Quaternion::from_rotation_matrix(&(orbit_axes * body_axes.transpose()))
+}
+
+/// Angular acceleration from Euler's equation, driven by the gravity-gradient torque plus
+/// any `external_torque` (e.g. the control torque). A singular inertia tensor (e.g. the zeroed
+/// tensor of `State::zero`) has no defined response, so it yields zero acceleration.
 pub fn angular_acceleration<T: SpacecraftProperties>(
     state: &State<T>,
     external_torque: Option<na::Vector3<f64>>,
@@ -27,13 +62,184 @@ pub fn angular_acceleration<T: SpacecraftProperties>(
     let inertia = state.inertia_tensor;
     let w = state.angular_velocity;
 
-    let torque = external_torque.unwrap_or_else(|| calculate_torque(state));
+    // Control and other external torques add to the environmental torques
+    let torque = calculate_torque(state) + external_torque.unwrap_or_else(na::Vector3::zeros);
     let gyro = w.cross(&(inertia * w));
 
-    inertia.try_inverse().unwrap() * (torque - gyro)
+    match inertia.try_inverse() {
+        Some(inertia_inv) => inertia_inv * (torque - gyro),
+        None => na::Vector3::zeros(),
+    }
 }
 
 pub fn quaternion_derivative<T: SpacecraftProperties>(state: &State<T>) -> Quaternion {
     // Only use body angular velocity for quaternion propagation
     compute_quaternion_derivative(&state.quaternion, &state.angular_velocity)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::MU_EARTH;
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    fn asymmetric_state(
+        spacecraft: &SimpleSat,
+        position: na::Vector3<f64>,
+        quaternion: Quaternion,
+    ) -> State<'_, SimpleSat> {
+        State::new(
+            spacecraft,
+            na::Matrix3::from_diagonal(&na::Vector3::new(1.0, 2.0, 3.0)),
+            position,
+            na::Vector3::zeros(),
+            quaternion,
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        )
+    }
+
+    #[test]
+    fn test_gravity_gradient_torque_analytic() {
+        let spacecraft = SimpleSat::default();
+        let r_mag: f64 = 7_000_000.0;
+        let scale = 3.0 * MU_EARTH / r_mag.powi(3);
+
+        // Body aligned with inertial, position along (1, 1, 0)/√2:
+        // r̂ × I r̂ = (1, 1, 0) × (1, 2, 0) / 2 = (0, 0, 1/2)
+        let position = na::Vector3::new(1.0, 1.0, 0.0).normalize() * r_mag;
+        let state = asymmetric_state(&spacecraft, position, Quaternion::new(1.0, 0.0, 0.0, 0.0));
+        let torque = calculate_torque(&state);
+        assert_relative_eq!(
+            torque,
+            na::Vector3::new(0.0, 0.0, 0.5 * scale),
+            epsilon = 1e-18
+        );
+
+        // Body rotated 30° about z, position along inertial x. The position seen from the
+        // body is (cos 30°, -sin 30°, 0), giving r̂ × I r̂ = (0, 0, -sin 30° cos 30°). Using
+        // the untransposed rotation would flip the sign, so this pins the frame convention.
+        let half = (15.0_f64).to_radians();
+        let state = asymmetric_state(
+            &spacecraft,
+            na::Vector3::new(r_mag, 0.0, 0.0),
+            Quaternion::new(half.cos(), 0.0, 0.0, half.sin()),
+        );
+        let torque = calculate_torque(&state);
+        let expected = -(30.0_f64).to_radians().sin() * (30.0_f64).to_radians().cos() * scale;
+        assert_relative_eq!(
+            torque,
+            na::Vector3::new(0.0, 0.0, expected),
+            epsilon = 1e-18
+        );
+    }
+
+    #[test]
+    fn test_gravity_gradient_equilibrium() {
+        let spacecraft = SimpleSat::default();
+        // A boom-like body, long along a skewed axis so the principal axes are not the body axes
+        let skew = na::Rotation3::from_axis_angle(
+            &na::Unit::new_normalize(na::Vector3::new(1.0, -2.0, 0.5)),
+            0.7,
+        );
+        let inertia = skew.matrix()
+            * na::Matrix3::from_diagonal(&na::Vector3::new(40.0, 25.0, 5.0))
+            * skew.matrix().transpose();
+
+        let (r, v) = OrbitalMechanics::circular_orbit_state(500_000.0, 0.9, 0.4, 1.3);
+        let q = gravity_gradient_equilibrium(&inertia, &r, &v);
+
+        let state_at = |quaternion: Quaternion| {
+            State::new(
+                &spacecraft,
+                inertia,
+                r,
+                v,
+                quaternion,
+                na::Vector3::zeros(),
+                Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+            )
+        };
+
+        // The minor axis points at nadir and no torque acts at equilibrium
+        let state = state_at(q.clone());
+        let minor_axis = skew * na::Vector3::z();
+        let nadir = q.to_rotation_matrix() * minor_axis;
+        assert_relative_eq!(nadir.dot(&-r.normalize()).abs(), 1.0, epsilon = 1e-12);
+        let scale = 3.0 * MU_EARTH / r.magnitude().powi(3) * 40.0;
+        assert!(calculate_torque(&state).magnitude() < 1e-12 * scale);
+
+        // Tilting about the major (pitch) or intermediate (roll) axis draws a torque back
+        for axis in [skew * na::Vector3::x(), skew * na::Vector3::y()] {
+            for angle in [0.02, -0.02] {
+                let half = angle / 2.0;
+                let tilt = Quaternion::new(
+                    f64::cos(half),
+                    axis.x * f64::sin(half),
+                    axis.y * f64::sin(half),
+                    axis.z * f64::sin(half),
+                );
+                let torque = calculate_torque(&state_at(q.multiply(&tilt)));
+                assert!(torque.dot(&axis) * angle < 0.0, "{torque} about {axis}");
+                assert!(torque.dot(&axis).abs() > 1e-3 * scale * angle.abs());
+            }
+        }
+    }
+
+    #[test]
+    fn test_angular_acceleration_zero_inertia() {
+        let spacecraft = SimpleSat::default();
+        let mut state = State::zero(
+            &spacecraft,
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        state.position = na::Vector3::new(6_778_137.0, 0.0, 0.0);
+        state.angular_velocity = na::Vector3::new(0.1, -0.2, 0.3);
+
+        let with_torque = angular_acceleration(&state, Some(na::Vector3::new(1.0, 2.0, 3.0)));
+        let without_torque = angular_acceleration(&state, None);
+
+        assert_eq!(with_torque, na::Vector3::zeros());
+        assert_eq!(without_torque, na::Vector3::zeros());
+    }
+
+    #[test]
+    fn test_control_torque_adds_to_gravity_gradient() {
+        let spacecraft = SimpleSat::default();
+        let half = (15.0_f64).to_radians();
+        let mut state = asymmetric_state(
+            &spacecraft,
+            na::Vector3::new(7_000_000.0, 0.0, 0.0),
+            Quaternion::new(half.cos(), 0.0, 0.0, half.sin()),
+        );
+        state.angular_velocity = na::Vector3::new(0.01, -0.02, 0.03);
+
+        let gravity_gradient = calculate_torque(&state);
+        assert!(gravity_gradient.magnitude() > 0.0);
+
+        let control = na::Vector3::new(1e-3, -2e-3, 5e-4);
+        let inertia = state.inertia_tensor;
+        let gyro = state
+            .angular_velocity
+            .cross(&(inertia * state.angular_velocity));
+        let expected = inertia.try_inverse().unwrap() * (gravity_gradient + control - gyro);
+
+        assert_relative_eq!(
+            angular_acceleration(&state, Some(control)),
+            expected,
+            max_relative = 1e-12
+        );
+
+        // Removing the control torque leaves exactly the gravity-gradient response
+        let difference =
+            angular_acceleration(&state, Some(control)) - angular_acceleration(&state, None);
+        assert_relative_eq!(
+            difference,
+            inertia.try_inverse().unwrap() * control,
+            max_relative = 1e-9
+        );
+    }
+}
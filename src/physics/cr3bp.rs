@@ -0,0 +1,198 @@
+use crate::constants::EARTH_MOON_MASS_RATIO;
+use crate::integrators::rk4::Renormalize;
+use crate::models::TranslationalDerivative;
+use crate::physics::dynamics::{ConservedQuantity, EquationsOfMotion};
+use nalgebra as na;
+
+/// Nondimensional state in the rotating frame of the circular restricted three-body
+/// problem. Lengths are in units of the primaries' separation and time in units of
+/// 1/(their mean motion), so the primaries orbit once every 2π.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cr3bpState {
+    pub position: na::Vector3<f64>,
+    pub velocity: na::Vector3<f64>,
+}
+
+impl Cr3bpState {
+    pub fn new(position: na::Vector3<f64>, velocity: na::Vector3<f64>) -> Self {
+        Self { position, velocity }
+    }
+}
+
+impl std::ops::Add<TranslationalDerivative> for Cr3bpState {
+    type Output = Self;
+
+    /// Applies an already time-scaled derivative increment to the state
+    fn add(self, increment: TranslationalDerivative) -> Self {
+        Cr3bpState {
+            position: self.position + increment.position,
+            velocity: self.velocity + increment.velocity,
+        }
+    }
+}
+
+impl Renormalize for Cr3bpState {}
+
+/// Jacobi constant C = x² + y² + 2(1 - μ)/r₁ + 2μ/r₂ - v² of a CR3BP state, for the
+/// smaller primary's share of the mass `mass_ratio`. It is the one integral of the motion.
+pub fn jacobi_constant(state: &Cr3bpState, mass_ratio: f64) -> f64 {
+    let mu = mass_ratio;
+    let (r1, r2) = primary_distances(&state.position, mu);
+    let (x, y) = (state.position.x, state.position.y);
+    x * x + y * y + 2.0 * (1.0 - mu) / r1 + 2.0 * mu / r2 - state.velocity.magnitude_squared()
+}
+
+/// Distances to the larger and smaller primary
+fn primary_distances(position: &na::Vector3<f64>, mass_ratio: f64) -> (f64, f64) {
+    let r1 = (position - na::Vector3::new(-mass_ratio, 0.0, 0.0)).magnitude();
+    let r2 = (position - na::Vector3::new(1.0 - mass_ratio, 0.0, 0.0)).magnitude();
+    (r1, r2)
+}
+
+/// Motion of a massless spacecraft about two primaries on circular orbits, in the frame
+/// rotating with them. The barycenter is at the origin with the larger primary at
+/// (-μ, 0, 0) and the smaller at (1 - μ, 0, 0), μ being the smaller's share of the mass.
+pub struct Cr3bpDynamics {
+    mass_ratio: f64,
+}
+
+impl Cr3bpDynamics {
+    pub fn new(mass_ratio: f64) -> Self {
+        Self { mass_ratio }
+    }
+
+    /// The Earth-Moon system
+    pub fn earth_moon() -> Self {
+        Self::new(EARTH_MOON_MASS_RATIO)
+    }
+
+    pub fn mass_ratio(&self) -> f64 {
+        self.mass_ratio
+    }
+
+    /// See `jacobi_constant`
+    pub fn jacobi_constant(&self, state: &Cr3bpState) -> f64 {
+        jacobi_constant(state, self.mass_ratio)
+    }
+
+    /// The leading triangular Lagrange point, ahead of the smaller primary
+    pub fn l4(&self) -> na::Vector3<f64> {
+        na::Vector3::new(0.5 - self.mass_ratio, 3.0_f64.sqrt() / 2.0, 0.0)
+    }
+
+    /// The trailing triangular Lagrange point, behind the smaller primary
+    pub fn l5(&self) -> na::Vector3<f64> {
+        na::Vector3::new(0.5 - self.mass_ratio, -(3.0_f64.sqrt()) / 2.0, 0.0)
+    }
+}
+
+impl EquationsOfMotion for Cr3bpDynamics {
+    type State = Cr3bpState;
+    type Derivative = TranslationalDerivative;
+
+    fn compute_derivative(&self, state: &Self::State) -> Self::Derivative {
+        let mu = self.mass_ratio;
+        let (r1, r2) = primary_distances(&state.position, mu);
+        let (x, y, z) = (state.position.x, state.position.y, state.position.z);
+        let (vx, vy) = (state.velocity.x, state.velocity.y);
+
+        let k1 = (1.0 - mu) / r1.powi(3);
+        let k2 = mu / r2.powi(3);
+
+        // Gravity of both primaries plus the Coriolis and centrifugal terms of the
+        // rotating frame
+        let acceleration = na::Vector3::new(
+            2.0 * vy + x - k1 * (x + mu) - k2 * (x - 1.0 + mu),
+            -2.0 * vx + y - k1 * y - k2 * y,
+            -k1 * z - k2 * z,
+        );
+
+        TranslationalDerivative {
+            position: state.velocity,
+            velocity: acceleration,
+        }
+    }
+}
+
+impl ConservedQuantity for Cr3bpDynamics {
+    fn invariant(&self, state: &Self::State) -> f64 {
+        self.jacobi_constant(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::PI;
+    use crate::integrators::rk4::RK4;
+    use crate::physics::dynamics::invariant;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_lagrange_points_are_equilibria() {
+        let dynamics = Cr3bpDynamics::earth_moon();
+        for point in [dynamics.l4(), dynamics.l5()] {
+            let derivative =
+                dynamics.compute_derivative(&Cr3bpState::new(point, na::Vector3::zeros()));
+            assert_relative_eq!(derivative.velocity, na::Vector3::zeros(), epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_jacobi_constant_conserved_near_l4() {
+        let dynamics = Cr3bpDynamics::earth_moon();
+        let initial = Cr3bpState::new(
+            dynamics.l4() + na::Vector3::new(0.01, -0.005, 0.002),
+            na::Vector3::new(0.001, 0.002, 0.0),
+        );
+        let jacobi = dynamics.jacobi_constant(&initial);
+        let rk4 = RK4::new(dynamics);
+
+        // Ten revolutions of the Moon
+        let dt = 2.0 * PI / 1000.0;
+        let mut state = initial;
+        for _ in 0..10_000 {
+            state = rk4.integrate(&state, dt);
+        }
+
+        // L4 is linearly stable for the Earth-Moon mass ratio, so the spacecraft stays near it
+        let dynamics = Cr3bpDynamics::earth_moon();
+        assert!((state.position - dynamics.l4()).magnitude() < 0.1);
+        assert_relative_eq!(
+            dynamics.jacobi_constant(&state),
+            jacobi,
+            max_relative = 1e-10
+        );
+    }
+
+    #[test]
+    fn test_invariant_is_the_jacobi_constant() {
+        let dynamics = Cr3bpDynamics::earth_moon();
+        let mu = dynamics.mass_ratio();
+        // Energy of the two-body problem about the Earth alone, in the same units
+        let two_body_energy = |state: &Cr3bpState| {
+            let (r1, _) = primary_distances(&state.position, mu);
+            0.5 * state.velocity.magnitude_squared() - (1.0 - mu) / r1
+        };
+
+        // Between the Earth and the Moon, where both pull on the spacecraft
+        let initial = Cr3bpState::new(
+            na::Vector3::new(0.5, 0.1, 0.0),
+            na::Vector3::new(0.0, 0.3, 0.0),
+        );
+        let (jacobi, energy) = (invariant(&dynamics, &initial), two_body_energy(&initial));
+        assert_eq!(jacobi, jacobi_constant(&initial, mu));
+
+        let rk4 = RK4::new(Cr3bpDynamics::earth_moon());
+        let dt = 2.0 * PI / 10_000.0;
+        let mut state = initial;
+        for _ in 0..2_000 {
+            state = rk4.integrate(&state, dt);
+        }
+
+        // The Jacobi constant holds to integration error, while the Moon and the rotating
+        // frame visibly change the two-body energy
+        assert_relative_eq!(invariant(&dynamics, &state), jacobi, max_relative = 1e-8);
+        assert!((two_body_energy(&state) - energy).abs() > 1e-3 * energy.abs());
+    }
+}
@@ -1,16 +1,250 @@
-use super::environment::Environment;
+use super::environment::Atmosphere;
 use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
 use nalgebra as na;
 
 pub fn drag_force<T: SpacecraftProperties>(
     spacecraft: &T,
     position: &na::Vector3<f64>,
     velocity: &na::Vector3<f64>,
+) -> na::Vector3<f64> {
+    drag_force_with_atmosphere(spacecraft, position, velocity, Atmosphere::default())
+}
+
+/// Same as `drag_force`, with the density taken from `atmosphere`
+pub fn drag_force_with_atmosphere<T: SpacecraftProperties>(
+    spacecraft: &T,
+    position: &na::Vector3<f64>,
+    velocity: &na::Vector3<f64>,
+    atmosphere: Atmosphere,
+) -> na::Vector3<f64> {
+    let area = spacecraft.reference_area();
+    drag_force_with_area(spacecraft, area, position, velocity, atmosphere)
+}
+
+/// Drag with the area set by the attitude, see `attitude_drag_area`
+pub fn attitude_drag_force<T: SpacecraftProperties>(
+    state: &State<T>,
+    tumbling_rate_threshold: f64,
+    atmosphere: Atmosphere,
+) -> na::Vector3<f64> {
+    drag_force_with_area(
+        state.spacecraft,
+        attitude_drag_area(state, tumbling_rate_threshold),
+        &state.position,
+        &state.velocity,
+        atmosphere,
+    )
+}
+
+/// Area the attitude presents to the flow: while the body rate exceeds
+/// `tumbling_rate_threshold` the orientation-averaged area, otherwise the area projected
+/// along the flow direction in the body frame
+pub fn attitude_drag_area<T: SpacecraftProperties>(
+    state: &State<T>,
+    tumbling_rate_threshold: f64,
+) -> f64 {
+    if state.angular_velocity.magnitude() > tumbling_rate_threshold {
+        state.spacecraft.mean_projected_area()
+    } else {
+        let flow_body = state
+            .quaternion
+            .normalize()
+            .to_rotation_matrix()
+            .transpose()
+            * state.velocity;
+        state.spacecraft.projected_area(&flow_body)
+    }
+}
+
+/// Drag on `area` square metres facing the flow
+pub fn drag_force_with_area<T: SpacecraftProperties>(
+    spacecraft: &T,
+    area: f64,
+    position: &na::Vector3<f64>,
+    velocity: &na::Vector3<f64>,
+    atmosphere: Atmosphere,
 ) -> na::Vector3<f64> {
     let v_po: f64 = velocity.magnitude();
-    let rho: f64 = Environment::new(position).density;
+    let rho: f64 = atmosphere.density_at(position);
 
-    let force_magnitude: f64 =
-        -0.5 * spacecraft.drag_coefficient() * spacecraft.reference_area() * rho * v_po.powi(2);
+    let force_magnitude: f64 = -0.5 * spacecraft.drag_coefficient() * area * rho * v_po.powi(2);
     velocity.normalize() * force_magnitude
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::{BoxSat, SimpleSat};
+    use crate::constants::{TUMBLING_RATE_THRESHOLD, WGS84_A};
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::environment::AtmosphereModel;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    #[test]
+    fn test_drag_force_scales_with_drag_coefficient() {
+        let nominal = SimpleSat::default();
+        let draggy = SimpleSat {
+            c_d: 2.0 * SimpleSat::C_D,
+            ..SimpleSat::default()
+        };
+
+        let position = na::Vector3::new(6_571_000.0, 0.0, 0.0);
+        let velocity = na::Vector3::new(0.0, 7_800.0, 0.0);
+
+        let nominal_force = drag_force(&nominal, &position, &velocity);
+        let draggy_force = drag_force(&draggy, &position, &velocity);
+
+        assert!(nominal_force.magnitude() > 0.0);
+        assert_relative_eq!(draggy_force, nominal_force * 2.0, max_relative = 1e-12);
+    }
+
+    #[test]
+    fn test_density_scale_scales_drag() {
+        let spacecraft = SimpleSat::default();
+        let position = na::Vector3::new(WGS84_A + 400_000.0, 0.0, 0.0);
+        let velocity = na::Vector3::new(0.0, 7_700.0, 0.0);
+
+        for model in [AtmosphereModel::Exponential, AtmosphereModel::Piecewise] {
+            let nominal = drag_force_with_atmosphere(
+                &spacecraft,
+                &position,
+                &velocity,
+                Atmosphere::new(model),
+            );
+            let doubled = drag_force_with_atmosphere(
+                &spacecraft,
+                &position,
+                &velocity,
+                Atmosphere::new(model).with_density_scale(2.0),
+            );
+            assert!(nominal.magnitude() > 0.0);
+            assert_eq!(doubled, nominal * 2.0);
+        }
+        assert_eq!(
+            drag_force_with_atmosphere(&spacecraft, &position, &velocity, Atmosphere::default()),
+            drag_force(&spacecraft, &position, &velocity)
+        );
+    }
+
+    #[test]
+    fn test_solar_maximum_increases_drag() {
+        let spacecraft = SimpleSat::default();
+        let position = na::Vector3::new(WGS84_A + 400_000.0, 0.0, 0.0);
+        let velocity = na::Vector3::new(0.0, 7_700.0, 0.0);
+        let drag = |f107: f64| {
+            let atmosphere =
+                Atmosphere::new(AtmosphereModel::Piecewise).with_solar_activity(f107, 15.0);
+            drag_force_with_atmosphere(&spacecraft, &position, &velocity, atmosphere)
+        };
+
+        let (low, high) = (drag(70.0), drag(250.0));
+        assert!(high.magnitude() > low.magnitude());
+        assert_relative_eq!(high.normalize(), low.normalize(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_drag_force_opposes_multi_axis_velocity() {
+        let spacecraft = SimpleSat::default();
+        let position = na::Vector3::new(6_471_000.0, 0.0, 0.0);
+        let rho = Atmosphere::default().density_at(&position);
+        let k = 0.5 * SimpleSat::C_D * std::f64::consts::PI * rho;
+
+        // Diagonal velocity of 3 km/s along each axis: |v|² = 27e6 and v̂ = (1, 1, 1)/√3,
+        // so F = -k |v|² v̂ = -k √27e6 · 3e3 (1, 1, 1)
+        let velocity = na::Vector3::new(3_000.0, 3_000.0, 3_000.0);
+        let expected = -k * 27.0e6_f64.sqrt() * na::Vector3::new(3_000.0, 3_000.0, 3_000.0);
+        let force = drag_force(&spacecraft, &position, &velocity);
+        assert_relative_eq!(force, expected, max_relative = 1e-12);
+
+        // Retrograde with an out-of-plane component: v = (0, -6, 8) km/s, |v| = 10 km/s,
+        // so F = -k · 1e4 · (0, -6e3, 8e3) = k · (0, 6e7, -8e7)
+        let velocity = na::Vector3::new(0.0, -6_000.0, 8_000.0);
+        let expected = k * na::Vector3::new(0.0, 6.0e7, -8.0e7);
+        let force = drag_force(&spacecraft, &position, &velocity);
+        assert_relative_eq!(force, expected, max_relative = 1e-12);
+
+        // The force stays exactly antiparallel to the velocity
+        assert_relative_eq!(
+            force.normalize().dot(&velocity.normalize()),
+            -1.0,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_tumbling_drag_uses_mean_projected_area() {
+        let spacecraft = BoxSat::new(2.2, na::Vector3::new(0.5, 1.0, 2.0), 100.0);
+        let position = na::Vector3::new(6_571_000.0, 0.0, 0.0);
+        let velocity = na::Vector3::new(0.0, 7_800.0, 0.0);
+        let state = |angular_velocity| {
+            State::new(
+                &spacecraft,
+                spacecraft.inertia_tensor(),
+                position,
+                velocity,
+                Quaternion::new(1.0, 0.0, 0.0, 0.0),
+                angular_velocity,
+                Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+            )
+        };
+        let per_square_meter = drag_force_with_area(
+            &spacecraft,
+            1.0,
+            &position,
+            &velocity,
+            Atmosphere::default(),
+        );
+
+        // Stabilized with the flow along body y, the 0.5 m × 2 m face is presented
+        let stabilized = attitude_drag_force(
+            &state(na::Vector3::zeros()),
+            TUMBLING_RATE_THRESHOLD,
+            Atmosphere::default(),
+        );
+        assert_relative_eq!(stabilized, per_square_meter * 1.0, max_relative = 1e-12);
+
+        // Tumbling presents a quarter of the 7 m² surface on average
+        let tumbling = attitude_drag_force(
+            &state(na::Vector3::new(0.1, 0.0, 0.0)),
+            TUMBLING_RATE_THRESHOLD,
+            Atmosphere::default(),
+        );
+        assert_relative_eq!(tumbling, per_square_meter * 1.75, max_relative = 1e-12);
+
+        // Both lie between the smallest face and the face-on maximum
+        let maximum = drag_force(&spacecraft, &position, &velocity);
+        assert_relative_eq!(maximum, per_square_meter * 2.0, max_relative = 1e-12);
+        assert!(tumbling.magnitude() < maximum.magnitude());
+    }
+
+    #[test]
+    fn test_stabilized_drag_follows_attitude() {
+        let spacecraft = BoxSat::new(2.2, na::Vector3::new(0.5, 1.0, 2.0), 100.0);
+        let position = na::Vector3::new(6_571_000.0, 0.0, 0.0);
+        let velocity = na::Vector3::new(0.0, 7_800.0, 0.0);
+
+        // A 90 degree roll about x turns the 0.5 m × 1 m face into the flow along inertial y
+        let half_angle = std::f64::consts::FRAC_PI_4;
+        let state = State::new(
+            &spacecraft,
+            spacecraft.inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(half_angle.cos(), half_angle.sin(), 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+
+        let force = attitude_drag_force(&state, TUMBLING_RATE_THRESHOLD, Atmosphere::default());
+        let expected = drag_force_with_area(
+            &spacecraft,
+            0.5,
+            &position,
+            &velocity,
+            Atmosphere::default(),
+        );
+        assert_relative_eq!(force, expected, max_relative = 1e-12);
+    }
+}
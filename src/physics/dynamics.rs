@@ -1,19 +1,62 @@
 use super::attitude::{angular_acceleration, quaternion_derivative};
-use super::drag::drag_force;
-use super::gravity::gravity_acceleration;
+use super::drag::{attitude_drag_force, drag_force_with_area};
+use super::energy::calculate_energy;
+use super::environment::Atmosphere;
+use super::gravity::{gravity_acceleration, j2_acceleration, j2_potential};
+use crate::constants::{MU_EARTH, TUMBLING_RATE_THRESHOLD};
 use crate::models::spacecraft::SpacecraftProperties;
-use crate::models::State;
+use crate::models::{State, StateDerivative, TranslationalDerivative, TranslationalState};
 use nalgebra as na;
 use std::marker::PhantomData;
 
 pub trait EquationsOfMotion {
     type State;
-    fn compute_derivative(&self, state: &Self::State) -> Self::State;
+    type Derivative;
+    fn compute_derivative(&self, state: &Self::State) -> Self::Derivative;
+}
+
+/// Equations of motion with a scalar integral, the quantity a propagation should hold
+/// constant and so the one to check it against
+pub trait ConservedQuantity: EquationsOfMotion {
+    fn invariant(&self, state: &Self::State) -> f64;
+}
+
+/// The conserved quantity of `model` at `state`: the orbital energy for the two-body
+/// models, the Jacobi constant for the CR3BP
+pub fn invariant<M: ConservedQuantity>(model: &M, state: &M::State) -> f64 {
+    model.invariant(state)
+}
+
+/// Frame in which a thrust vector is expressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrustFrame {
+    /// Thrust is given directly in GCRS, as if the engine could point anywhere instantly
+    Inertial,
+    /// Thrust is fixed in the body frame, e.g. a fixed nozzle, and turns with the attitude
+    Body,
+}
+
+/// Individual contributions to the translational acceleration [m/s²]. Gravity is the
+/// point-mass term, the only gravity the dynamics model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccelerationComponents {
+    pub gravity: na::Vector3<f64>,
+    pub drag: na::Vector3<f64>,
+    pub thrust: na::Vector3<f64>,
+}
+
+impl AccelerationComponents {
+    /// Net acceleration, as used for the velocity derivative
+    pub fn total(&self) -> na::Vector3<f64> {
+        self.gravity + self.drag + self.thrust
+    }
 }
 
 pub struct SpacecraftDynamics<'a, T: SpacecraftProperties> {
     thrust: Option<na::Vector3<f64>>,
+    thrust_frame: ThrustFrame,
     torque: Option<na::Vector3<f64>>,
+    atmosphere: Atmosphere,
     _phantom: PhantomData<&'a T>,
 }
 
@@ -21,34 +64,371 @@ impl<'a, T: SpacecraftProperties> SpacecraftDynamics<'a, T> {
     pub fn new(thrust: Option<na::Vector3<f64>>, torque: Option<na::Vector3<f64>>) -> Self {
         Self {
             thrust,
+            thrust_frame: ThrustFrame::Inertial,
             torque,
+            atmosphere: Atmosphere::default(),
             _phantom: PhantomData,
         }
     }
+
+    /// Dynamics with a body-frame thrust, rotated into GCRS by the attitude of each
+    /// evaluated state so that pointing errors show up as thrust direction errors
+    pub fn with_body_thrust(
+        thrust_body: Option<na::Vector3<f64>>,
+        torque: Option<na::Vector3<f64>>,
+    ) -> Self {
+        Self {
+            thrust_frame: ThrustFrame::Body,
+            ..Self::new(thrust_body, torque)
+        }
+    }
+
+    /// Uses `atmosphere` for the drag density instead of the default model
+    pub fn with_atmosphere(self, atmosphere: Atmosphere) -> Self {
+        Self { atmosphere, ..self }
+    }
+
+    /// Each term of the acceleration acting on `state`, for checking the force models
+    /// against each other
+    pub fn acceleration_breakdown(&self, state: &State<'a, T>) -> AccelerationComponents {
+        self.breakdown_with_gravity(state, gravity_acceleration(&state.position))
+    }
+
+    /// Derivative at `state` given the point-mass gravity there, so that a caller stepping
+    /// many spacecraft together can evaluate their gravity in one batch
+    pub fn compute_derivative_with_gravity(
+        &self,
+        state: &State<'a, T>,
+        gravity: na::Vector3<f64>,
+    ) -> StateDerivative {
+        // Velocity derivative (gravity + thrust + drag)
+        let acceleration = self.breakdown_with_gravity(state, gravity).total();
+
+        StateDerivative {
+            // Position derivative is velocity
+            position: state.velocity,
+            velocity: acceleration,
+            // Quaternion derivative
+            quaternion: quaternion_derivative(state).data,
+            // Angular acceleration (Euler's equation)
+            angular_velocity: angular_acceleration(state, self.torque),
+            // Thrust does not consume propellant here; `FiniteBurn` accounts for it
+            mass: 0.0,
+            fuel_mass: 0.0,
+        }
+    }
+
+    fn breakdown_with_gravity(
+        &self,
+        state: &State<'a, T>,
+        gravity: na::Vector3<f64>,
+    ) -> AccelerationComponents {
+        AccelerationComponents {
+            gravity,
+            drag: attitude_drag_force(state, TUMBLING_RATE_THRESHOLD, self.atmosphere) / state.mass,
+            thrust: self
+                .inertial_thrust(state)
+                .map_or_else(na::Vector3::zeros, |thrust| thrust / state.mass),
+        }
+    }
+
+    /// Thrust in GCRS for the given state
+    pub fn inertial_thrust(&self, state: &State<'a, T>) -> Option<na::Vector3<f64>> {
+        self.thrust.map(|thrust| match self.thrust_frame {
+            ThrustFrame::Inertial => thrust,
+            ThrustFrame::Body => state.quaternion.normalize().to_rotation_matrix() * thrust,
+        })
+    }
 }
 
 impl<'a, T: SpacecraftProperties> EquationsOfMotion for SpacecraftDynamics<'a, T> {
     type State = State<'a, T>;
+    type Derivative = StateDerivative;
 
-    fn compute_derivative(&self, state: &Self::State) -> Self::State {
-        let mut derivative = State::zero(state.spacecraft);
+    fn compute_derivative(&self, state: &Self::State) -> Self::Derivative {
+        self.compute_derivative_with_gravity(state, gravity_acceleration(&state.position))
+    }
+}
 
-        // Position derivative is velocity
-        derivative.position = state.velocity;
+/// Orbital energy [J], NaN at the Earth's center. Only conserved while coasting outside the
+/// atmosphere.
+impl<'a, T: SpacecraftProperties> ConservedQuantity for SpacecraftDynamics<'a, T> {
+    fn invariant(&self, state: &Self::State) -> f64 {
+        calculate_energy(state).unwrap_or(f64::NAN)
+    }
+}
 
-        // Velocity derivative (gravity + thrust + drag)
-        derivative.velocity = gravity_acceleration(&state.position)
-            + drag_force(state.spacecraft, &state.position, &state.velocity) / state.mass;
+/// Orbit-only equations of motion (gravity, optionally J2, drag on the state's `drag_area`
+/// and thrust) that skip all attitude terms, for studies that only need the trajectory
+pub struct TranslationalDynamics<'a, T: SpacecraftProperties> {
+    thrust: Option<na::Vector3<f64>>,
+    atmosphere: Atmosphere,
+    j2: bool,
+    _phantom: PhantomData<&'a T>,
+}
+
+impl<'a, T: SpacecraftProperties> TranslationalDynamics<'a, T> {
+    pub fn new(thrust: Option<na::Vector3<f64>>) -> Self {
+        Self {
+            thrust,
+            atmosphere: Atmosphere::default(),
+            j2: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Uses `atmosphere` for the drag density instead of the default model
+    pub fn with_atmosphere(self, atmosphere: Atmosphere) -> Self {
+        Self { atmosphere, ..self }
+    }
+
+    /// Adds the J2 oblateness perturbation to the point-mass gravity
+    pub fn with_j2(self) -> Self {
+        Self { j2: true, ..self }
+    }
+}
+
+impl<'a, T: SpacecraftProperties> EquationsOfMotion for TranslationalDynamics<'a, T> {
+    type State = TranslationalState<'a, T>;
+    type Derivative = TranslationalDerivative;
+
+    fn compute_derivative(&self, state: &Self::State) -> Self::Derivative {
+        let mut acceleration = gravity_acceleration(&state.position)
+            + drag_force_with_area(
+                state.spacecraft,
+                state.drag_area,
+                &state.position,
+                &state.velocity,
+                self.atmosphere,
+            ) / state.mass;
         if let Some(thrust) = &self.thrust {
-            derivative.velocity += thrust / state.mass;
+            acceleration += thrust / state.mass;
+        }
+        if self.j2 {
+            acceleration += j2_acceleration(&state.position);
+        }
+
+        TranslationalDerivative {
+            position: state.velocity,
+            velocity: acceleration,
+        }
+    }
+}
+
+/// Orbital energy [J], as for `SpacecraftDynamics`, including the J2 potential when J2 is on
+impl<'a, T: SpacecraftProperties> ConservedQuantity for TranslationalDynamics<'a, T> {
+    fn invariant(&self, state: &Self::State) -> f64 {
+        let r = state.position.magnitude();
+        let mut potential = -MU_EARTH / r;
+        if self.j2 {
+            potential += j2_potential(&state.position);
+        }
+        state.mass * (0.5 * state.velocity.magnitude_squared() + potential)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::{BoxSat, SimpleSat};
+    use crate::constants::{MU_EARTH, WGS84_A};
+    use crate::integrators::rk4::RK4;
+    use crate::numerics::quaternion::Quaternion;
+    use crate::physics::orbital::OrbitalMechanics;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    #[test]
+    fn test_compute_derivative_is_deterministic() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_668.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.05, 0.02, 0.01),
+            epoch,
+        );
+        let dynamics = SpacecraftDynamics::<SimpleSat>::new(
+            Some(na::Vector3::new(1.0, 2.0, 3.0)),
+            Some(na::Vector3::new(0.1, 0.0, -0.1)),
+        );
+
+        let first = dynamics.compute_derivative(&state);
+        let second = dynamics.compute_derivative(&state);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_acceleration_breakdown_sums_to_derivative() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        // Low enough for drag to matter, and yawed 90° so body +x thrust points along GCRS +y
+        let half = std::f64::consts::FRAC_PI_4;
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(WGS84_A + 200_000.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_790.0, 0.0),
+            Quaternion::new(half.cos(), 0.0, 0.0, half.sin()),
+            na::Vector3::zeros(),
+            epoch,
+        );
+        let dynamics = SpacecraftDynamics::<SimpleSat>::with_body_thrust(
+            Some(na::Vector3::new(10.0, 0.0, 0.0)),
+            None,
+        );
+
+        let components = dynamics.acceleration_breakdown(&state);
+        let derivative = dynamics.compute_derivative(&state);
+        assert_eq!(components.total(), derivative.velocity);
+
+        let radius = state.position.magnitude();
+        assert_relative_eq!(
+            components.gravity,
+            -state.position / radius * MU_EARTH / radius.powi(2),
+            max_relative = 1e-12
+        );
+        assert!(components.drag.magnitude() > 0.0);
+        assert_relative_eq!(
+            components.drag.normalize(),
+            -state.velocity.normalize(),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            components.thrust,
+            na::Vector3::new(0.0, 10.0 / state.mass, 0.0),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_translational_dynamics_matches_full_dynamics() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let mut full_state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_000.0, 3_000.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.05, 0.02, 0.01),
+            epoch,
+        );
+        let mut orbit_state = TranslationalState::from(&full_state);
+        let thrust = Some(na::Vector3::new(0.5, -0.2, 0.1));
+
+        let steps = 2000;
+        let dt = 1.0;
+
+        let full = RK4::new(SpacecraftDynamics::<SimpleSat>::new(thrust, None));
+        for _ in 0..steps {
+            full_state = full.integrate(&full_state, dt);
+        }
+
+        let orbit_only = RK4::new(TranslationalDynamics::<SimpleSat>::new(thrust));
+        for _ in 0..steps {
+            orbit_state = orbit_only.integrate(&orbit_state, dt);
+        }
+
+        assert_relative_eq!(orbit_state.position, full_state.position, epsilon = 1e-6);
+        assert_relative_eq!(orbit_state.velocity, full_state.velocity, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_translational_dynamics_matches_full_dynamics_for_tumbling_box() {
+        // Low enough for drag to matter, on a body whose mean projected area (1.75 m²) is
+        // well below its largest face (2 m²)
+        let spacecraft = BoxSat::new(2.2, na::Vector3::new(0.5, 1.0, 2.0), 100.0);
+        let mut full_state = State::new(
+            &spacecraft,
+            spacecraft.inertia_tensor(),
+            na::Vector3::new(WGS84_A + 250_000.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_750.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.1, 0.0, 0.05),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let mut orbit_state = TranslationalState::from(&full_state);
+        assert_eq!(orbit_state.drag_area, spacecraft.mean_projected_area());
+        let coasting =
+            TranslationalState::new(&spacecraft, orbit_state.position, orbit_state.velocity);
+
+        let full = RK4::new(SpacecraftDynamics::<BoxSat>::new(None, None));
+        let orbit_only = RK4::new(TranslationalDynamics::<BoxSat>::new(None));
+        for _ in 0..600 {
+            assert!(full_state.angular_velocity.magnitude() > TUMBLING_RATE_THRESHOLD);
+            full_state = full.integrate(&full_state, 1.0);
+            orbit_state = orbit_only.integrate(&orbit_state, 1.0);
         }
 
-        // Angular acceleration (Euler's equation)
-        derivative.angular_velocity = angular_acceleration(state, self.torque);
+        assert_relative_eq!(orbit_state.position, full_state.position, epsilon = 1e-6);
+        assert_relative_eq!(orbit_state.velocity, full_state.velocity, epsilon = 1e-9);
+
+        // Without an attitude to go by, the orbit-only state also flies the mean area
+        assert_eq!(coasting.drag_area, orbit_state.drag_area);
+    }
+
+    #[test]
+    fn test_translational_invariant_includes_j2_potential() {
+        // Inclined and above the atmosphere, so J2 is the only perturbation
+        let spacecraft = SimpleSat::default();
+        let elements = na::Vector6::new(WGS84_A + 2_000_000.0, 0.01, 1.0, 0.0, 0.0, 0.0);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let mut state = TranslationalState::new(&spacecraft, position, velocity);
+
+        let two_body = TranslationalDynamics::<SimpleSat>::new(None);
+        let with_j2 = TranslationalDynamics::<SimpleSat>::new(None).with_j2();
+        let (initial, initial_two_body) =
+            (invariant(&with_j2, &state), invariant(&two_body, &state));
+        let integrator = RK4::new(TranslationalDynamics::<SimpleSat>::new(None).with_j2());
+        let (mut drift, mut two_body_drift) = (0.0_f64, 0.0_f64);
+        for _ in 0..600 {
+            state = integrator.integrate(&state, 10.0);
+            drift = drift.max(((invariant(&with_j2, &state) - initial) / initial).abs());
+            two_body_drift = two_body_drift
+                .max(((invariant(&two_body, &state) - initial_two_body) / initial_two_body).abs());
+        }
+
+        // The point-mass energy swings with J2, the full energy holds to integration error
+        assert!(two_body_drift > 1e-5, "two-body drift {two_body_drift}");
+        assert!(drift < 1e-10, "drift {drift}");
+    }
+
+    #[test]
+    fn test_body_thrust_follows_attitude_error() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let prograde = na::Vector3::y();
+        let pointing_error = 10.0_f64.to_radians();
+
+        // A 90 degree yaw would put the body x nozzle on prograde; overshoot it by the error
+        let yaw = std::f64::consts::FRAC_PI_2 + pointing_error;
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(6_778_137.0, 0.0, 0.0),
+            prograde * 7_668.0,
+            Quaternion::new((yaw / 2.0).cos(), 0.0, 0.0, (yaw / 2.0).sin()),
+            na::Vector3::zeros(),
+            epoch,
+        );
+
+        let thrust_body = na::Vector3::new(2.0, 0.0, 0.0);
+        let thrusting = SpacecraftDynamics::<SimpleSat>::with_body_thrust(Some(thrust_body), None);
+        let coasting = SpacecraftDynamics::<SimpleSat>::new(None, None);
 
-        // Quaternion derivative
-        derivative.quaternion = quaternion_derivative(state);
+        let thrust = thrusting.inertial_thrust(&state).unwrap();
+        assert_relative_eq!(thrust.magnitude(), 2.0, epsilon = 1e-12);
+        assert_relative_eq!(thrust.angle(&prograde), pointing_error, epsilon = 1e-12);
+        assert!(prograde.cross(&thrust).z > 0.0);
 
-        derivative
+        // The dynamics see exactly that rotated thrust
+        let thrust_acceleration = thrusting.compute_derivative(&state).velocity
+            - coasting.compute_derivative(&state).velocity;
+        assert_relative_eq!(thrust_acceleration, thrust / state.mass, epsilon = 1e-15);
     }
 }
@@ -1,18 +1,159 @@
-use crate::constants::{G, M_EARTH};
+use crate::constants::MU_EARTH;
 use crate::models::spacecraft::SpacecraftProperties;
 use crate::models::state::State;
 use nalgebra as na;
+use std::fmt;
 
-pub fn calculate_energy<T: SpacecraftProperties>(state: &State<T>) -> f64 {
+/// Total orbital energy (kinetic plus point-mass potential). Returns `None` at the Earth's
+/// center, where the potential is unbounded.
+pub fn calculate_energy<T: SpacecraftProperties>(state: &State<T>) -> Option<f64> {
     let r = state.position.magnitude();
+    if r < 1e-11 {
+        return None;
+    }
     let v = state.velocity.magnitude();
 
     let kinetic = 0.5 * state.mass * v * v;
-    let potential = -G * M_EARTH * state.mass / r;
+    let potential = -MU_EARTH * state.mass / r;
 
-    kinetic + potential
+    Some(kinetic + potential)
 }
 
 pub fn calculate_angular_momentum<T: SpacecraftProperties>(state: &State<T>) -> na::Vector3<f64> {
     state.position.cross(&(state.velocity * state.mass))
 }
+
+/// The quantities a coasting point-mass orbit conserves, evaluated at one state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Invariants {
+    pub energy: Option<f64>,                // J, `None` at the Earth's center
+    pub angular_momentum: na::Vector3<f64>, // kg m²/s
+    pub semi_major_axis: f64,               // m, negative when unbound
+}
+
+impl Invariants {
+    pub fn of<T: SpacecraftProperties>(state: &State<T>) -> Self {
+        let r = state.position.magnitude();
+        let v = state.velocity.magnitude();
+        Self {
+            energy: calculate_energy(state),
+            angular_momentum: calculate_angular_momentum(state),
+            semi_major_axis: 1.0 / (2.0 / r - v * v / MU_EARTH),
+        }
+    }
+}
+
+impl fmt::Display for Invariants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.energy {
+            Some(energy) => write!(f, "energy {:.6e} J", energy)?,
+            None => write!(f, "energy undefined")?,
+        }
+        write!(
+            f,
+            ", |h| {:.6e} kg m²/s, a {:.3} km",
+            self.angular_momentum.magnitude(),
+            self.semi_major_axis / 1000.0
+        )
+    }
+}
+
+/// Invariants at the start and end of a run, and how far each drifted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConservationReport {
+    pub initial: Invariants,
+    pub last: Invariants,
+}
+
+impl ConservationReport {
+    /// (E - E₀) / |E₀|, NaN where either energy is undefined
+    pub fn relative_energy_change(&self) -> f64 {
+        match (self.last.energy, self.initial.energy) {
+            (Some(last), Some(initial)) => (last - initial) / initial.abs(),
+            _ => f64::NAN,
+        }
+    }
+
+    /// |h - h₀| / |h₀|, which also catches a change of orbit plane
+    pub fn relative_angular_momentum_change(&self) -> f64 {
+        (self.last.angular_momentum - self.initial.angular_momentum).magnitude()
+            / self.initial.angular_momentum.magnitude()
+    }
+
+    /// (a - a₀) / |a₀|
+    pub fn relative_semi_major_axis_change(&self) -> f64 {
+        (self.last.semi_major_axis - self.initial.semi_major_axis)
+            / self.initial.semi_major_axis.abs()
+    }
+}
+
+/// Two lines, the initial invariants and then the final ones with their relative changes
+impl fmt::Display for ConservationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "initial: {}", self.initial)?;
+        write!(
+            f,
+            "final:   {} (ΔE/E {:.3e}, Δh/h {:.3e}, Δa/a {:.3e})",
+            self.last,
+            self.relative_energy_change(),
+            self.relative_angular_momentum_change(),
+            self.relative_semi_major_axis_change()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    #[test]
+    fn test_zero_state_energy() {
+        let spacecraft = SimpleSat::default();
+        let mut state = State::zero(
+            &spacecraft,
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+
+        // The potential is undefined at the Earth's center
+        assert_eq!(calculate_energy(&state), None);
+
+        state.position = na::Vector3::new(7_000_000.0, 0.0, 0.0);
+        let expected = -MU_EARTH * SimpleSat::MASS / 7_000_000.0;
+        assert_relative_eq!(
+            calculate_energy(&state).unwrap(),
+            expected,
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_conservation_report() {
+        let spacecraft = SimpleSat::default();
+        let mut state = State::zero(
+            &spacecraft,
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        state.position = na::Vector3::new(7_000_000.0, 0.0, 0.0);
+        state.velocity = na::Vector3::new(0.0, (MU_EARTH / 7_000_000.0).sqrt(), 0.0);
+        let initial = Invariants::of(&state);
+        assert_relative_eq!(initial.semi_major_axis, 7_000_000.0, max_relative = 1e-12);
+
+        // A slightly faster spacecraft has more energy and a larger orbit
+        state.velocity *= 1.001;
+        let report = ConservationReport {
+            initial,
+            last: Invariants::of(&state),
+        };
+        assert!(report.relative_energy_change() > 0.0);
+        assert!(report.relative_semi_major_axis_change() > 0.0);
+        assert_relative_eq!(
+            report.relative_angular_momentum_change(),
+            0.001,
+            max_relative = 1e-9
+        );
+        assert_eq!(report.to_string().lines().count(), 2);
+    }
+}
@@ -1,10 +1,176 @@
 use crate::constants::*;
+use crate::coordinates::coordinate_transformation::{
+    earth_rotation_angle, itrs_to_geodetic_signed, EOPData,
+};
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use hifitime::Epoch;
 use nalgebra as na;
 
+const EARTH_DIPOLE_MOMENT: f64 = 7.94e22; // A·m²
+
+// Geomagnetic north pole of the IGRF dipole (epoch 2020)
+const GEOMAGNETIC_POLE_LATITUDE: f64 = 80.65; // deg
+const GEOMAGNETIC_POLE_LONGITUDE: f64 = -72.68; // deg
+
+/// Reference density profile: base altitude [km], density at the base [kg/m³] and scale
+/// height [km], after Vallado's exponential model (Table 8-4 of Fundamentals of
+/// Astrodynamics and Applications)
+const PIECEWISE_ATMOSPHERE: [(f64, f64, f64); 28] = [
+    (0.0, 1.225, 7.249),
+    (25.0, 3.899e-2, 6.349),
+    (30.0, 1.774e-2, 6.682),
+    (40.0, 3.972e-3, 7.554),
+    (50.0, 1.057e-3, 8.382),
+    (60.0, 3.206e-4, 7.714),
+    (70.0, 8.770e-5, 6.549),
+    (80.0, 1.905e-5, 5.799),
+    (90.0, 3.396e-6, 5.382),
+    (100.0, 5.297e-7, 5.877),
+    (110.0, 9.661e-8, 7.263),
+    (120.0, 2.438e-8, 9.473),
+    (130.0, 8.484e-9, 12.636),
+    (140.0, 3.845e-9, 16.149),
+    (150.0, 2.070e-9, 22.523),
+    (180.0, 5.464e-10, 29.740),
+    (200.0, 2.789e-10, 37.105),
+    (250.0, 7.248e-11, 45.546),
+    (300.0, 2.418e-11, 53.628),
+    (350.0, 9.518e-12, 53.298),
+    (400.0, 3.725e-12, 58.515),
+    (450.0, 1.585e-12, 60.828),
+    (500.0, 6.967e-13, 63.822),
+    (600.0, 1.454e-13, 71.835),
+    (700.0, 3.614e-14, 88.667),
+    (800.0, 1.170e-14, 124.64),
+    (900.0, 5.245e-15, 181.05),
+    (1000.0, 3.019e-15, 268.00),
+];
+
+/// Density model used for drag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AtmosphereModel {
+    /// Single exponential with a 7.2 km scale height from sea level. Cheap, but far too
+    /// dense in low Earth orbit.
+    #[default]
+    Exponential,
+    /// Exponential fitted piecewise to a reference profile, with a scale height that grows
+    /// with altitude
+    Piecewise,
+}
+
+impl AtmosphereModel {
+    /// Density [kg/m³] at a geodetic altitude [m]
+    pub fn density(&self, altitude: f64) -> f64 {
+        match self {
+            AtmosphereModel::Exponential => {
+                let scale_height = 7200.0; // meters
+                1.225 * (-altitude / scale_height).exp()
+            }
+            AtmosphereModel::Piecewise => {
+                let altitude_km = altitude / 1000.0;
+                let &(base, base_density, scale_height) = PIECEWISE_ATMOSPHERE
+                    .iter()
+                    .rev()
+                    .find(|(base, _, _)| altitude_km >= *base)
+                    .unwrap_or(&PIECEWISE_ATMOSPHERE[0]);
+                base_density * (-(altitude_km - base) / scale_height).exp()
+            }
+        }
+    }
+}
+
+/// Solar and geomagnetic activity for mean conditions: the 10.7 cm solar flux [sfu] and
+/// the daily planetary geomagnetic index Ap
+pub const MEAN_F107: f64 = 150.0;
+pub const MEAN_AP: f64 = 15.0;
+
+/// Altitude above which the density responds to solar and geomagnetic activity [m]
+const THERMOSPHERE_BASE: f64 = 100_000.0;
+
+/// Exospheric temperature [K] after Jacchia (1970): a solar term driven by F10.7 plus a
+/// geomagnetic heating term driven by Ap
+fn exospheric_temperature(f107: f64, ap: f64) -> f64 {
+    379.0 + 3.24 * f107 + ap + 100.0 * (1.0 - (-0.08 * ap).exp())
+}
+
+/// Atmosphere used for drag: a density model, adjusted for solar and geomagnetic activity
+/// and scaled by `density_scale` for sensitivity studies.
+///
+/// The density models describe mean activity. Above 100 km, where the scale heights grow
+/// in proportion to the exospheric temperature, the altitude above 100 km is shrunk by the
+/// ratio of the mean exospheric temperature to the actual one before the model is
+/// evaluated. A hotter, more active thermosphere is thus denser at a given altitude, by up
+/// to an order of magnitude in low Earth orbit between solar minimum and maximum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Atmosphere {
+    pub model: AtmosphereModel,
+    pub density_scale: f64,
+    /// 10.7 cm solar radio flux [sfu], ideally the 81-day mean
+    pub f107: f64,
+    /// Planetary geomagnetic index Ap
+    pub ap: f64,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self::new(AtmosphereModel::default())
+    }
+}
+
+impl From<AtmosphereModel> for Atmosphere {
+    fn from(model: AtmosphereModel) -> Self {
+        Self::new(model)
+    }
+}
+
+impl Atmosphere {
+    pub fn new(model: AtmosphereModel) -> Self {
+        Self {
+            model,
+            density_scale: 1.0,
+            f107: MEAN_F107,
+            ap: MEAN_AP,
+        }
+    }
+
+    pub fn with_solar_activity(self, f107: f64, ap: f64) -> Self {
+        Self { f107, ap, ..self }
+    }
+
+    pub fn with_density_scale(self, density_scale: f64) -> Self {
+        Self {
+            density_scale,
+            ..self
+        }
+    }
+
+    /// Density [kg/m³] at a geodetic altitude [m]
+    pub fn density(&self, altitude: f64) -> f64 {
+        let temperature_ratio =
+            exospheric_temperature(MEAN_F107, MEAN_AP) / exospheric_temperature(self.f107, self.ap);
+        let altitude = if altitude > THERMOSPHERE_BASE && temperature_ratio != 1.0 {
+            THERMOSPHERE_BASE + (altitude - THERMOSPHERE_BASE) * temperature_ratio
+        } else {
+            altitude
+        };
+        self.model.density(altitude) * self.density_scale
+    }
+
+    /// Density [kg/m³] at the inertial `position`. Altitude is measured above the WGS84
+    /// ellipsoid, which is symmetric about the polar axis that GCRS and ITRS share to within
+    /// precession and polar motion, so the inertial position is used without rotating it.
+    pub fn density_at(&self, position: &na::Vector3<f64>) -> f64 {
+        let (_, _, altitude) = itrs_to_geodetic_signed(position);
+        self.density(altitude)
+    }
+}
+
 pub struct Environment {
     #[allow(dead_code)]
     pub altitude: f64,
     pub density: f64,
+    /// Geomagnetic field [T] in GCRS
     #[allow(dead_code)]
     pub magnetic_field: na::Vector3<f64>,
     #[allow(dead_code)]
@@ -12,24 +178,236 @@ pub struct Environment {
 }
 
 impl Environment {
-    pub fn new(position: &na::Vector3<f64>) -> Self {
-        let altitude = position.magnitude() - R_EARTH;
-
-        // Simple exponential atmospheric model
-        let scale_height = 7200.0; // meters
-        let density = 1.225 * (-altitude / scale_height).exp();
+    /// Environment at the inertial `position` and `epoch`, see `Atmosphere::density_at` for
+    /// the altitude and `magnetic_field_inertial` for the field
+    pub fn new(position: &na::Vector3<f64>, epoch: &Epoch) -> Self {
+        Self::with_atmosphere(position, epoch, Atmosphere::default())
+    }
 
-        // Simplified dipole magnetic field model
-        let r = position.magnitude();
-        let m = 7.94e22; // Earth's magnetic dipole moment
-        let b0 = (M_0 * m) / (4.0 * std::f64::consts::PI * r.powi(3));
-        let magnetic_field = na::Vector3::new(0.0, 0.0, 2.0 * b0);
+    /// Same as `new`, with the density taken from `atmosphere`
+    pub fn with_atmosphere(
+        position: &na::Vector3<f64>,
+        epoch: &Epoch,
+        atmosphere: Atmosphere,
+    ) -> Self {
+        let (_, _, altitude) = itrs_to_geodetic_signed(position);
 
         Environment {
             altitude,
-            density,
-            magnetic_field,
+            density: atmosphere.density(altitude),
+            magnetic_field: magnetic_field_inertial(position, epoch),
             solar_flux: 1361.0, // W/m^2 at 1 AU
         }
     }
 }
+
+/// Unit vector towards the geomagnetic north pole in GCRS. The pole is fixed in the
+/// Earth-fixed frame and carried round by the Earth rotation angle; precession, nutation,
+/// polar motion and UT1-UTC are neglected.
+fn geomagnetic_pole(epoch: &Epoch) -> na::Vector3<f64> {
+    let era = earth_rotation_angle(
+        epoch,
+        &EOPData {
+            ut1_utc: 0.0,
+            ..EOPData::default()
+        },
+    );
+
+    let latitude = GEOMAGNETIC_POLE_LATITUDE.to_radians();
+    let longitude = GEOMAGNETIC_POLE_LONGITUDE.to_radians() + era;
+    na::Vector3::new(
+        latitude.cos() * longitude.cos(),
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+    )
+}
+
+/// Geomagnetic field [T] in GCRS at `position`, from a dipole tilted towards the geomagnetic
+/// pole. The Earth's dipole moment points at the southern geomagnetic pole, so field lines
+/// run northwards at the magnetic equator.
+pub fn magnetic_field_inertial(position: &na::Vector3<f64>, epoch: &Epoch) -> na::Vector3<f64> {
+    let r = position.magnitude();
+    let r_unit = position / r;
+    let moment = -geomagnetic_pole(epoch) * EARTH_DIPOLE_MOMENT;
+
+    (r_unit * (3.0 * moment.dot(&r_unit)) - moment) * (M_0 / (4.0 * PI * r.powi(3)))
+}
+
+/// Geomagnetic field [T] at the spacecraft, in body axes, for magnetorquer and B-dot control
+pub fn magnetic_field_body<T: SpacecraftProperties>(
+    state: &State<T>,
+    epoch: &Epoch,
+) -> na::Vector3<f64> {
+    // The attitude matrix maps body to inertial, so its transpose brings the field into body axes
+    state.quaternion.to_rotation_matrix().transpose()
+        * magnetic_field_inertial(&state.position, epoch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::numerics::quaternion::Quaternion;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_density_uses_geodetic_altitude() {
+        // Over the pole the ellipsoid lies 14 km inside a sphere of the mean radius
+        let polar_radius = WGS84_A * (1.0 - WGS84_F);
+        let position = na::Vector3::new(0.0, 0.0, polar_radius + 400_000.0);
+        let spherical_altitude = position.magnitude() - R_EARTH;
+
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let environment = Environment::new(&position, &epoch);
+        assert_relative_eq!(environment.altitude, 400_000.0, epsilon = 1e-6);
+        assert_relative_eq!(
+            environment.altitude - spherical_altitude,
+            R_EARTH - polar_radius,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            environment.density,
+            1.225 * (-400_000.0_f64 / 7200.0).exp(),
+            max_relative = 1e-9
+        );
+
+        // At the equator the geodetic altitude is measured from the equatorial radius
+        let equatorial = Environment::new(&na::Vector3::new(0.0, WGS84_A + 400_000.0, 0.0), &epoch);
+        assert_relative_eq!(equatorial.altitude, 400_000.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_atmosphere_models_differ() {
+        let position = na::Vector3::new(WGS84_A + 400_000.0, 0.0, 0.0);
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let exponential =
+            Environment::with_atmosphere(&position, &epoch, AtmosphereModel::Exponential.into());
+        let piecewise =
+            Environment::with_atmosphere(&position, &epoch, AtmosphereModel::Piecewise.into());
+
+        assert!(exponential.density.is_finite() && exponential.density > 0.0);
+        assert!(piecewise.density.is_finite() && piecewise.density > 0.0);
+        assert!(exponential.density != piecewise.density);
+
+        // The default keeps the original exponential model
+        assert_eq!(
+            Environment::new(&position, &epoch).density,
+            exponential.density
+        );
+        assert_eq!(
+            Atmosphere::default().density_at(&position),
+            exponential.density
+        );
+
+        // The piecewise profile matches its table at the base altitudes and is continuous
+        // to within the fit across the band edges
+        assert_relative_eq!(piecewise.density, 3.725e-12, max_relative = 1e-9);
+        let model = AtmosphereModel::Piecewise;
+        assert_relative_eq!(
+            model.density(499_999.0),
+            model.density(500_000.0),
+            max_relative = 0.1
+        );
+        assert_eq!(
+            model.density(-100.0),
+            model.density(0.0) * (100.0_f64 / 7249.0).exp()
+        );
+    }
+
+    #[test]
+    fn test_solar_activity_raises_thermospheric_density() {
+        let piecewise = Atmosphere::new(AtmosphereModel::Piecewise);
+        let solar_minimum = piecewise.with_solar_activity(70.0, 5.0);
+        let solar_maximum = piecewise.with_solar_activity(250.0, 5.0);
+        let storm = piecewise.with_solar_activity(150.0, 200.0);
+
+        // Mean conditions leave the model untouched
+        for altitude in [50_000.0, 400_123.456] {
+            assert_eq!(
+                piecewise.density(altitude),
+                AtmosphereModel::Piecewise.density(altitude)
+            );
+        }
+
+        // In low Earth orbit the solar cycle spans about an order of magnitude, and a
+        // geomagnetic storm adds to it
+        let altitude = 400_000.0;
+        let low = solar_minimum.density(altitude);
+        let high = solar_maximum.density(altitude);
+        assert!(high > piecewise.density(altitude) && piecewise.density(altitude) > low);
+        assert!(
+            high / low > 5.0 && high / low < 50.0,
+            "ratio {}",
+            high / low
+        );
+        assert!(storm.density(altitude) > piecewise.density(altitude));
+
+        // Below the thermosphere the activity has no effect
+        assert_eq!(
+            solar_maximum.density(80_000.0),
+            solar_minimum.density(80_000.0)
+        );
+    }
+
+    #[test]
+    fn test_dipole_field_strength() {
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let r = WGS84_A + 500_000.0;
+        let b0 = M_0 * EARTH_DIPOLE_MOMENT / (4.0 * PI * r.powi(3));
+        let pole = geomagnetic_pole(&epoch);
+
+        // Over the geomagnetic north pole the field is twice as strong and points down
+        let field = magnetic_field_inertial(&(pole * r), &epoch);
+        assert_relative_eq!(field, -pole * 2.0 * b0, max_relative = 1e-12);
+
+        // On the magnetic equator it runs parallel to the dipole axis, towards the north
+        let equator = pole.cross(&na::Vector3::z()).normalize() * r;
+        let field = magnetic_field_inertial(&equator, &epoch);
+        assert_relative_eq!(field, pole * b0, max_relative = 1e-12);
+
+        // The environment carries the same tilted dipole
+        assert_eq!(Environment::new(&equator, &epoch).magnetic_field, field);
+    }
+
+    #[test]
+    fn test_body_field_follows_attitude() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let half_angle = 0.3_f64;
+        let axis = na::Vector3::new(1.0, -2.0, 0.5).normalize();
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(4_000_000.0, 3_500_000.0, 4_200_000.0),
+            na::Vector3::new(-5_000.0, 5_500.0, 1_000.0),
+            Quaternion::new(
+                half_angle.cos(),
+                axis.x * half_angle.sin(),
+                axis.y * half_angle.sin(),
+                axis.z * half_angle.sin(),
+            ),
+            na::Vector3::zeros(),
+            epoch,
+        );
+
+        let inertial = magnetic_field_inertial(&state.position, &epoch);
+        let body = magnetic_field_body(&state, &epoch);
+
+        // Rotating the body-frame field by the attitude recovers the inertial field
+        assert_relative_eq!(
+            state.quaternion.to_rotation_matrix() * body,
+            inertial,
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(body.magnitude(), inertial.magnitude(), max_relative = 1e-12);
+
+        // The component along the rotation axis is unchanged, the rest is turned by -0.6 rad
+        assert_relative_eq!(body.dot(&axis), inertial.dot(&axis), max_relative = 1e-12);
+        let perpendicular = |v: na::Vector3<f64>| v - axis * v.dot(&axis);
+        assert_relative_eq!(
+            perpendicular(body).angle(&perpendicular(inertial)),
+            2.0 * half_angle,
+            epsilon = 1e-12
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use crate::constants::AU;
+use hifitime::Epoch;
+use nalgebra as na;
+
+/// Geocentric position of the Sun in GCRS [m], from the low-precision solar coordinates of
+/// the Astronomical Almanac (about 0.01° in direction between 1950 and 2050). Precession
+/// since J2000 is neglected.
+pub fn sun_position(epoch: &Epoch) -> na::Vector3<f64> {
+    // Days since J2000.0
+    let n = epoch.to_jde_tai(hifitime::Unit::Day) - 2451545.0;
+
+    // Mean longitude and mean anomaly [deg]
+    let mean_longitude = 280.460 + 0.9856474 * n;
+    let mean_anomaly = (357.528 + 0.9856003 * n).to_radians();
+
+    // Ecliptic longitude, obliquity and distance
+    let longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin())
+            .to_radians();
+    let obliquity = (23.439 - 0.0000004 * n).to_radians();
+    let distance = 1.00014 - 0.01671 * mean_anomaly.cos() - 0.00014 * (2.0 * mean_anomaly).cos();
+
+    na::Vector3::new(
+        longitude.cos(),
+        obliquity.cos() * longitude.sin(),
+        obliquity.sin() * longitude.sin(),
+    ) * (distance * AU)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_sun_at_march_equinox() {
+        // 2024 March equinox, 03:06 UTC: the Sun crosses the equator heading north
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 20, 3, 6, 0, 0);
+        let sun = sun_position(&epoch);
+
+        assert!(sun.normalize().angle(&na::Vector3::x()) < 1e-3);
+        assert_relative_eq!(sun.magnitude(), 0.996 * AU, max_relative = 1e-3);
+    }
+
+    #[test]
+    fn test_sun_at_june_solstice() {
+        // 2024 June solstice, 20:51 UTC: right ascension 90°, declination +23.44°
+        let epoch = Epoch::from_gregorian_utc(2024, 6, 20, 20, 51, 0, 0);
+        let direction = sun_position(&epoch).normalize();
+
+        let declination = direction.z.asin().to_degrees();
+        let right_ascension = direction.y.atan2(direction.x).to_degrees();
+        assert_relative_eq!(declination, 23.44, epsilon = 0.01);
+        assert_relative_eq!(right_ascension, 90.0, epsilon = 0.05);
+        assert_relative_eq!(
+            sun_position(&epoch).magnitude(),
+            1.0163 * AU,
+            max_relative = 1e-3
+        );
+    }
+}
@@ -1,8 +1,97 @@
-use crate::constants::{G, M_EARTH};
+use crate::constants::{EARTH_J2, MU_EARTH, WGS84_A};
 use nalgebra as na;
+#[cfg(not(any(feature = "std", test)))]
+use nalgebra::ComplexField;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 pub fn gravity_acceleration(position: &na::Vector3<f64>) -> na::Vector3<f64> {
     let r: f64 = position.magnitude();
-    let acceleration_magnitude: f64 = -G * M_EARTH / (r * r);
+    let acceleration_magnitude: f64 = -MU_EARTH / (r * r);
     position.normalize() * acceleration_magnitude
 }
+
+/// Perturbing acceleration of the J2 zonal term, to add to the point-mass gravity for
+/// studies where the oblateness matters. `position` is taken as Earth-fixed in z only, i.e.
+/// the pole is the GCRS z axis.
+pub fn j2_acceleration(position: &na::Vector3<f64>) -> na::Vector3<f64> {
+    let r = position.magnitude();
+    let z_ratio_sq = (position.z / r).powi(2);
+    let scale = -1.5 * EARTH_J2 * MU_EARTH * WGS84_A * WGS84_A / r.powi(5);
+    na::Vector3::new(
+        position.x * (1.0 - 5.0 * z_ratio_sq),
+        position.y * (1.0 - 5.0 * z_ratio_sq),
+        position.z * (3.0 - 5.0 * z_ratio_sq),
+    ) * scale
+}
+
+/// Potential energy per unit mass of the J2 zonal term [J/kg], to add to the point-mass
+/// -μ/r. Its negative gradient is `j2_acceleration`.
+pub fn j2_potential(position: &na::Vector3<f64>) -> f64 {
+    let r = position.magnitude();
+    let z_ratio_sq = (position.z / r).powi(2);
+    0.5 * EARTH_J2 * MU_EARTH * WGS84_A * WGS84_A / r.powi(3) * (3.0 * z_ratio_sq - 1.0)
+}
+
+/// Point-mass gravity at each of `positions`, for many bodies at once. Each acceleration is
+/// -μ r / |r|³ from a single square root and division, with no branches, so the loop is
+/// straight-line arithmetic the compiler can vectorize. Agrees with `gravity_acceleration`
+/// to rounding.
+pub fn gravity_acceleration_batch(positions: &[na::Vector3<f64>]) -> Vec<na::Vector3<f64>> {
+    positions
+        .iter()
+        .map(|position| {
+            let r_squared = position.norm_squared();
+            position * (-MU_EARTH / (r_squared * r_squared.sqrt()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_batch_matches_single() {
+        // A shell of positions from LEO out past GEO, in every octant
+        let positions: Vec<_> = (0..1000)
+            .map(|i| {
+                let i = i as f64;
+                let radius = 6.6e6 + 40.0e6 * (i / 1000.0);
+                let (longitude, latitude) = (i * 2.399963, (i * 0.618034).sin() * 1.5);
+                na::Vector3::new(
+                    radius * latitude.cos() * longitude.cos(),
+                    radius * latitude.cos() * longitude.sin(),
+                    radius * latitude.sin(),
+                )
+            })
+            .collect();
+
+        let batch = gravity_acceleration_batch(&positions);
+        assert_eq!(batch.len(), positions.len());
+        for (position, acceleration) in positions.iter().zip(&batch) {
+            assert_relative_eq!(
+                *acceleration,
+                gravity_acceleration(position),
+                max_relative = 1e-14
+            );
+        }
+        assert!(gravity_acceleration_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_j2_acceleration_is_potential_gradient() {
+        let position = na::Vector3::new(4_000_000.0, -3_000_000.0, 5_000_000.0);
+        let h = 1.0;
+        let gradient = na::Vector3::from_fn(|axis, _| {
+            let step = na::Vector3::from_fn(|i, _| if i == axis { h } else { 0.0 });
+            (j2_potential(&(position + step)) - j2_potential(&(position - step))) / (2.0 * h)
+        });
+        assert_relative_eq!(-gradient, j2_acceleration(&position), max_relative = 1e-6);
+    }
+}
@@ -1,7 +1,17 @@
+#[cfg(feature = "std")]
 pub mod attitude;
+#[cfg(feature = "std")]
+pub mod cr3bp;
+#[cfg(feature = "std")]
 pub mod drag;
+#[cfg(feature = "std")]
 pub mod dynamics;
+#[cfg(feature = "std")]
 pub mod energy;
+#[cfg(feature = "std")]
 pub mod environment;
+#[cfg(feature = "std")]
+pub mod ephemeris;
 pub mod gravity;
 pub mod orbital;
+pub mod orbital_errors;
@@ -1,5 +1,20 @@
-use crate::constants::{G, M_EARTH, PI};
+use super::orbital_errors::OrbitalErrors;
+use crate::constants::{EARTH_J2, MU_EARTH, PI, WGS84_A};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 use nalgebra as na;
+#[cfg(not(any(feature = "std", test)))]
+use nalgebra::{ComplexField, RealField};
+
+/// Anomaly in which `OrbitalMechanics::sample_orbit_with_spacing` spaces its points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnomalySpacing {
+    /// Equal angles seen from the focus, crowding the points around perigee
+    #[default]
+    TrueAnomaly,
+    /// Equal steps in eccentric anomaly, for a more even spacing along the ellipse
+    EccentricAnomaly,
+}
 
 pub struct OrbitalMechanics;
 
@@ -15,11 +30,11 @@ impl OrbitalMechanics {
     ///   omega: argument of periapsis [rad]
     ///   nu: true anomaly [rad]
     pub fn cartesian_to_keplerian(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> na::Vector6<f64> {
-        let mu = G * M_EARTH;
+        let mu = MU_EARTH;
         let mut elements = na::Vector6::zeros();
 
         // Calculate angular momentum vector
-        let h = r.cross(v);
+        let h = Self::angular_momentum_vector(r, v);
         let h_mag = h.magnitude();
 
         // Calculate node vector
@@ -30,7 +45,7 @@ impl OrbitalMechanics {
         // Calculate eccentricity vector
         let r_mag = r.magnitude();
         let v_mag = v.magnitude();
-        let e_vec = ((v_mag * v_mag - mu / r_mag) * r - r.dot(v) * v) / mu;
+        let e_vec = Self::eccentricity_vector(r, v);
         let e = e_vec.magnitude();
         elements[1] = e;
 
@@ -62,7 +77,7 @@ impl OrbitalMechanics {
             }
             omega
         } else {
-            let mut omega = (h.dot(&e_vec.cross(&n))).atan2(n.dot(&e_vec));
+            let mut omega = (n.cross(&e_vec).dot(&h) / h_mag).atan2(n.dot(&e_vec));
             if omega < 0.0 {
                 omega += 2.0 * PI;
             }
@@ -74,10 +89,11 @@ impl OrbitalMechanics {
             if n_mag < 1e-11 {
                 r.y.atan2(r.x)
             } else {
-                n.dot(&r.cross(&n)).atan2(n.dot(r))
+                // Argument of latitude, measured from the ascending node
+                (n.cross(r).dot(&h) / h_mag).atan2(n.dot(r))
             }
         } else {
-            let mut nu = h.dot(&e_vec.cross(r)).atan2(e_vec.dot(r));
+            let mut nu = (e_vec.cross(r).dot(&h) / h_mag).atan2(e_vec.dot(r));
             if nu < 0.0 {
                 nu += 2.0 * PI;
             }
@@ -87,16 +103,101 @@ impl OrbitalMechanics {
         elements
     }
 
+    /// Specific angular momentum h = r × v [m²/s], normal to the orbit plane
+    pub fn angular_momentum_vector(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> na::Vector3<f64> {
+        r.cross(v)
+    }
+
+    /// Eccentricity vector, pointing from the focus toward perigee with magnitude e. Its
+    /// direction is undefined for circular orbits.
+    pub fn eccentricity_vector(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> na::Vector3<f64> {
+        let r_mag = r.magnitude();
+        let v_mag = v.magnitude();
+        ((v_mag * v_mag - MU_EARTH / r_mag) * r - r.dot(v) * v) / MU_EARTH
+    }
+
     pub fn compute_orbital_period(a: f64) -> f64 {
-        2.0 * PI * (a.powi(3) / (G * M_EARTH)).sqrt()
+        2.0 * PI * (a.powi(3) / MU_EARTH).sqrt()
     }
 
     pub fn compute_circular_velocity(r: f64) -> f64 {
-        ((G * M_EARTH) / r).sqrt()
+        (MU_EARTH / r).sqrt()
+    }
+
+    /// Secular rate of the right ascension of the ascending node due to J2 [rad/s]
+    pub fn raan_dot_j2(a: f64, e: f64, i: f64) -> f64 {
+        let n = (MU_EARTH / a.powi(3)).sqrt();
+        let p = a * (1.0 - e * e);
+        -1.5 * n * EARTH_J2 * (WGS84_A / p).powi(2) * i.cos()
+    }
+
+    /// Secular rate of the argument of periapsis due to J2 [rad/s]
+    pub fn arg_perigee_dot_j2(a: f64, e: f64, i: f64) -> f64 {
+        let n = (MU_EARTH / a.powi(3)).sqrt();
+        let p = a * (1.0 - e * e);
+        0.75 * n * EARTH_J2 * (WGS84_A / p).powi(2) * (5.0 * i.cos().powi(2) - 1.0)
+    }
+
+    /// Mean semi-major axis [m]: the osculating value with the first-order J2 short-period
+    /// terms of Brouwer's theory removed, so it stays steady around the orbit under J2 and
+    /// only drifts with the secular effects (drag, thrust). Accurate to O(J2²) for
+    /// near-circular and moderately eccentric orbits.
+    pub fn mean_semi_major_axis(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> f64 {
+        let elements = Self::cartesian_to_keplerian(r, v);
+        let (a, e, i) = (elements[0], elements[1], elements[2]);
+        let a_over_r_cubed = (a / r.magnitude()).powi(3);
+        let eta_cubed = (1.0 - e * e).powf(1.5);
+
+        let u = Self::argument_of_latitude(r, v);
+
+        let gamma = EARTH_J2 / 2.0 * (WGS84_A / a).powi(2);
+        let cos_i_sq = i.cos().powi(2);
+        a - a
+            * gamma
+            * ((3.0 * cos_i_sq - 1.0) * (a_over_r_cubed - 1.0 / eta_cubed)
+                + 3.0 * (1.0 - cos_i_sq) * a_over_r_cubed * (2.0 * u).cos())
+    }
+
+    /// Argument of latitude u = ω + ν [rad] in [0, 2π): the angle from the ascending node to
+    /// the position, measured in the direction of motion. Defined for circular orbits, unlike
+    /// ω and ν separately. Equatorial orbits have no node, so the angle is then measured from
+    /// the GCRS x axis (the true longitude).
+    pub fn argument_of_latitude(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> f64 {
+        let h = Self::angular_momentum_vector(r, v).normalize();
+        let node = na::Vector3::z().cross(&h);
+        let node = if node.magnitude() > 1e-9 {
+            node.normalize()
+        } else {
+            na::Vector3::x()
+        };
+        let u = h.cross(&node).dot(r).atan2(node.dot(r));
+        if u < 0.0 {
+            u + 2.0 * PI
+        } else {
+            u
+        }
+    }
+
+    /// Returns (at ascending node, at descending node): whether the position lies within
+    /// `tolerance` metres of the equatorial plane, heading north or south. The counterpart of
+    /// `is_near_apsis` for plane-change and RAAN burns, which belong at the nodes.
+    pub fn is_near_node(
+        r: &na::Vector3<f64>,
+        v: &na::Vector3<f64>,
+        tolerance: f64,
+    ) -> (bool, bool) {
+        let in_plane = r.z.abs() < tolerance;
+        (in_plane && v.z > 0.0, in_plane && v.z < 0.0)
+    }
+
+    /// True for elliptical orbits (negative specific energy). Parabolic and hyperbolic
+    /// states have no apogee, so apsis-based logic must check this first.
+    pub fn is_bound(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> bool {
+        v.magnitude_squared() / 2.0 - MU_EARTH / r.magnitude() < 0.0
     }
 
     pub fn compute_apsides(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> (f64, f64) {
-        let mu = G * M_EARTH;
+        let mu = MU_EARTH;
         let r_mag = r.magnitude();
         let v_mag = v.magnitude();
         let specific_energy = (v_mag * v_mag / 2.0) - mu / r_mag;
@@ -104,7 +205,16 @@ impl OrbitalMechanics {
         let h_mag2 = h.dot(&h);
 
         let a = -mu / (2.0 * specific_energy);
-        let e = (1.0 + (2.0 * specific_energy * h_mag2) / (mu * mu)).sqrt();
+
+        // Rounding can push the radicand slightly negative for near-circular orbits,
+        // so clamp it at zero rather than letting the sqrt produce NaN
+        let e_squared = (1.0 + (2.0 * specific_energy * h_mag2) / (mu * mu)).max(0.0);
+
+        // Radicands at the level of floating-point noise are treated as exactly circular
+        if e_squared < 1e-15 {
+            return (a, a);
+        }
+        let e = e_squared.sqrt();
 
         let ra = a * (1.0 + e);
         let rp = a * (1.0 - e);
@@ -112,6 +222,41 @@ impl OrbitalMechanics {
         (ra, rp)
     }
 
+    /// Partial derivatives of the apoapsis and periapsis radii from `compute_apsides` with
+    /// respect to the velocity vector [s]: how far each apsis moves per m/s of impulsive Δv
+    /// along each axis. The prograde partial is the dot product with the velocity direction.
+    /// Undefined (infinite) on an exactly circular orbit, where the apsides are not distinct.
+    pub fn apsis_velocity_partials(
+        r: &na::Vector3<f64>,
+        v: &na::Vector3<f64>,
+    ) -> (na::Vector3<f64>, na::Vector3<f64>) {
+        let mu = MU_EARTH;
+        let r_mag = r.magnitude();
+        let a = 1.0 / (2.0 / r_mag - v.magnitude_squared() / mu);
+        let h_squared = Self::angular_momentum_vector(r, v).magnitude_squared();
+        let e = (1.0 - h_squared / (mu * a)).max(0.0).sqrt();
+
+        // a from vis-viva, and e² = 1 - h²/(μa) with h² = r²v² - (r·v)²
+        let da = v * (2.0 * a * a / mu);
+        let dh_squared = v * (2.0 * r_mag * r_mag) - r * (2.0 * r.dot(v));
+        let de = (da * (h_squared / (mu * a * a)) - dh_squared / (mu * a)) / (2.0 * e);
+
+        (da * (1.0 + e) + de * a, da * (1.0 - e) - de * a)
+    }
+
+    /// Apogee and perigee altitudes above the WGS84 equatorial radius, treating the Earth as
+    /// a sphere. Use `itrs_to_geodetic` for the altitude above the ellipsoid.
+    pub fn compute_apsis_altitudes(r: &na::Vector3<f64>, v: &na::Vector3<f64>) -> (f64, f64) {
+        let (ra, rp) = Self::compute_apsides(r, v);
+        (ra - WGS84_A, rp - WGS84_A)
+    }
+
+    /// Altitude above the WGS84 equatorial radius, on the same spherical basis as
+    /// `compute_apsis_altitudes`
+    pub fn current_altitude(position: &na::Vector3<f64>) -> f64 {
+        position.magnitude() - WGS84_A
+    }
+
     pub fn is_near_apsis(
         r: &na::Vector3<f64>,
         v: &na::Vector3<f64>,
@@ -149,28 +294,100 @@ impl OrbitalMechanics {
         M
     }
 
-    #[allow(dead_code)]
-    pub fn mean_to_eccentric_anomaly(M: f64, e: f64, tolerance: f64, max_iterations: i32) -> f64 {
+    /// Time since the last perigee passage [s] at true anomaly `nu` on an elliptical orbit
+    /// with semi-major axis `a` and eccentricity `e`, in [0, period)
+    pub fn time_since_perigee(nu: f64, a: f64, e: f64) -> f64 {
+        let E = Self::true_to_eccentric_anomaly(nu, e);
+        let M = Self::eccentric_to_mean_anomaly(E, e);
+        M / (MU_EARTH / a.powi(3)).sqrt()
+    }
+
+    /// Solves Kepler's equation M = E - e*sin(E) for the eccentric anomaly using Newton-Raphson.
+    /// Returns an error if the step size has not dropped below `tolerance` within `max_iterations`.
+    pub fn mean_to_eccentric_anomaly(
+        M: f64,
+        e: f64,
+        tolerance: f64,
+        max_iterations: i32,
+    ) -> Result<f64, OrbitalErrors> {
         if e < 1e-11 {
-            return M;
+            return Ok(M);
         }
 
-        // Initial guess
-        let mut E = if M < PI { M + e / 2.0 } else { M - e / 2.0 };
+        // Initial guess; the Danby starter is far more robust for highly eccentric orbits
+        let mut E = if e > 0.8 {
+            M + 0.85 * e * M.sin().signum()
+        } else if M < PI {
+            M + e / 2.0
+        } else {
+            M - e / 2.0
+        };
 
         // Newton-Raphson iteration
+        let mut delta = f64::INFINITY;
         for _ in 0..max_iterations {
-            let delta = (E - e * E.sin() - M) / (1.0 - e * E.cos());
+            delta = (E - e * E.sin() - M) / (1.0 - e * E.cos());
             E -= delta;
             if delta.abs() <= tolerance {
-                break;
+                if E < 0.0 {
+                    E += 2.0 * PI;
+                }
+                return Ok(E);
             }
         }
 
-        if E < 0.0 {
-            E += 2.0 * PI;
+        Err(OrbitalErrors::KeplerNonConvergence {
+            iterations: max_iterations,
+            residual: delta.abs(),
+        })
+    }
+
+    /// Advances a two-body state by `dt` seconds along its Keplerian orbit, by converting
+    /// to elements, advancing the mean anomaly and converting back. Only elliptical orbits
+    /// are supported.
+    pub fn propagate_kepler(
+        r: &na::Vector3<f64>,
+        v: &na::Vector3<f64>,
+        dt: f64,
+    ) -> Result<(na::Vector3<f64>, na::Vector3<f64>), OrbitalErrors> {
+        if !Self::is_bound(r, v) {
+            return Err(OrbitalErrors::UnboundOrbit {
+                specific_energy: v.magnitude_squared() / 2.0 - MU_EARTH / r.magnitude(),
+            });
         }
-        E
+
+        let mut elements = Self::cartesian_to_keplerian(r, v);
+        let (a, e, nu) = (elements[0], elements[1], elements[5]);
+
+        let E = Self::true_to_eccentric_anomaly(nu, e);
+        let n = (MU_EARTH / a.powi(3)).sqrt();
+        // Wrapped into [0, 2π) with floor, as rem_euclid needs std
+        let M = Self::eccentric_to_mean_anomaly(E, e) + n * dt;
+        let M = M - 2.0 * PI * (M / (2.0 * PI)).floor();
+        let E = Self::mean_to_eccentric_anomaly(M, e, 1e-14, 50)?;
+
+        elements[5] =
+            2.0 * ((1.0 + e).sqrt() * (E / 2.0).sin()).atan2((1.0 - e).sqrt() * (E / 2.0).cos());
+        Ok(Self::keplerian_to_cartesian(&elements))
+    }
+
+    /// Position and velocity on a circular orbit `altitude` above the WGS84 equatorial
+    /// radius. With no perigee to measure from, `true_anomaly` is the argument of latitude,
+    /// the angle from the ascending node.
+    pub fn circular_orbit_state(
+        altitude: f64,
+        inclination: f64,
+        raan: f64,
+        true_anomaly: f64,
+    ) -> (na::Vector3<f64>, na::Vector3<f64>) {
+        Self::keplerian_to_cartesian(&na::Vector6::new(
+            WGS84_A + altitude,
+            0.0,
+            inclination,
+            raan,
+            0.0,
+            true_anomaly,
+        ))
     }
 
     /// Converts Keplerian orbital elements to Cartesian state vectors
@@ -185,7 +402,7 @@ impl OrbitalMechanics {
     pub fn keplerian_to_cartesian(
         elements: &na::Vector6<f64>,
     ) -> (na::Vector3<f64>, na::Vector3<f64>) {
-        let mu = G * M_EARTH;
+        let mu = MU_EARTH;
         let (a, e, i, omega_cap, omega, nu) = (
             elements[0],
             elements[1],
@@ -209,16 +426,614 @@ impl OrbitalMechanics {
             0.0,
         );
 
-        // Rotation matrices
-        let rot_omega = na::Rotation3::from_axis_angle(&na::Vector3::z_axis(), omega);
-        let rot_i = na::Rotation3::from_axis_angle(&na::Vector3::x_axis(), i);
-        let rot_omega_cap = na::Rotation3::from_axis_angle(&na::Vector3::z_axis(), omega_cap);
-
         // Transform to ECI frame
-        let transform = rot_omega_cap * rot_i * rot_omega;
+        let transform = Self::perifocal_to_eci(i, omega_cap, omega);
         let r_eci = transform * r_orbital;
         let v_eci = transform * v_orbital;
 
         (r_eci, v_eci)
     }
+
+    /// Positions around one revolution of the orbit described by `elements`, spaced evenly in
+    /// true anomaly. See `sample_orbit_with_spacing`.
+    pub fn sample_orbit(elements: &na::Vector6<f64>, n_points: usize) -> Vec<na::Vector3<f64>> {
+        Self::sample_orbit_with_spacing(elements, n_points, AnomalySpacing::default())
+    }
+
+    /// Positions around one revolution of the elliptical orbit described by `elements` (whose
+    /// true anomaly is ignored), in ECI [m]. The samples run from periapsis back to periapsis,
+    /// so the first and last points coincide and close the curve; fewer than two points
+    /// returns the periapsis alone, or nothing.
+    pub fn sample_orbit_with_spacing(
+        elements: &na::Vector6<f64>,
+        n_points: usize,
+        spacing: AnomalySpacing,
+    ) -> Vec<na::Vector3<f64>> {
+        let (a, e) = (elements[0], elements[1]);
+        let transform = Self::perifocal_to_eci(elements[2], elements[3], elements[4]);
+        let p = a * (1.0 - e * e);
+        let b = a * (1.0 - e * e).sqrt();
+        let step = 2.0 * PI / n_points.saturating_sub(1).max(1) as f64;
+
+        (0..n_points)
+            .map(|k| {
+                let anomaly = k as f64 * step;
+                let r_orbital = match spacing {
+                    AnomalySpacing::TrueAnomaly => {
+                        let r_mag = p / (1.0 + e * anomaly.cos());
+                        na::Vector3::new(r_mag * anomaly.cos(), r_mag * anomaly.sin(), 0.0)
+                    }
+                    AnomalySpacing::EccentricAnomaly => {
+                        na::Vector3::new(a * (anomaly.cos() - e), b * anomaly.sin(), 0.0)
+                    }
+                };
+                transform * r_orbital
+            })
+            .collect()
+    }
+
+    /// Rotation from the perifocal frame (x to periapsis, z along the orbit normal) to ECI
+    fn perifocal_to_eci(i: f64, omega_cap: f64, omega: f64) -> na::Rotation3<f64> {
+        let rot_omega = na::Rotation3::from_axis_angle(&na::Vector3::z_axis(), omega);
+        let rot_i = na::Rotation3::from_axis_angle(&na::Vector3::x_axis(), i);
+        let rot_omega_cap = na::Rotation3::from_axis_angle(&na::Vector3::z_axis(), omega_cap);
+        rot_omega_cap * rot_i * rot_omega
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_compute_apsides_circular_orbit() {
+        for altitude in [200_000.0, 400_000.0, 780_000.0, 35_786_000.0] {
+            let radius = crate::constants::WGS84_A + altitude;
+            let r = na::Vector3::new(radius, 0.0, 0.0);
+            let v = na::Vector3::new(
+                0.0,
+                OrbitalMechanics::compute_circular_velocity(radius),
+                0.0,
+            );
+
+            let (ra, rp) = OrbitalMechanics::compute_apsides(&r, &v);
+
+            assert!(!ra.is_nan() && !rp.is_nan());
+            assert_relative_eq!(ra, radius, max_relative = 1e-9);
+            assert_relative_eq!(rp, radius, max_relative = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_compute_apsides_elliptical_orbit() {
+        let rp_expected = crate::constants::WGS84_A + 300_000.0;
+        let ra_expected = crate::constants::WGS84_A + 1_000_000.0;
+        let a = (ra_expected + rp_expected) / 2.0;
+
+        // Start at perigee, where the speed follows from vis-viva
+        let r = na::Vector3::new(rp_expected, 0.0, 0.0);
+        let v = na::Vector3::new(0.0, (MU_EARTH * (2.0 / rp_expected - 1.0 / a)).sqrt(), 0.0);
+
+        let (ra, rp) = OrbitalMechanics::compute_apsides(&r, &v);
+
+        assert_relative_eq!(ra, ra_expected, max_relative = 1e-9);
+        assert_relative_eq!(rp, rp_expected, max_relative = 1e-9);
+    }
+
+    #[test]
+    fn test_apsis_altitudes() {
+        let (perigee_altitude, apogee_altitude) = (250_000.0, 35_786_000.0);
+        let elements = na::Vector6::new(
+            WGS84_A + (perigee_altitude + apogee_altitude) / 2.0,
+            (apogee_altitude - perigee_altitude)
+                / (2.0 * WGS84_A + perigee_altitude + apogee_altitude),
+            28.5_f64.to_radians(),
+            0.4,
+            1.2,
+            2.0,
+        );
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+
+        let (apogee, perigee) = OrbitalMechanics::compute_apsis_altitudes(&r, &v);
+        assert_relative_eq!(apogee, apogee_altitude, max_relative = 1e-9);
+        assert_relative_eq!(perigee, perigee_altitude, max_relative = 1e-9);
+
+        // The current altitude lies between the two away from the apsides
+        let altitude = OrbitalMechanics::current_altitude(&r);
+        assert!(perigee < altitude && altitude < apogee);
+        assert_relative_eq!(
+            OrbitalMechanics::current_altitude(&na::Vector3::new(0.0, 0.0, WGS84_A + 400_000.0)),
+            400_000.0
+        );
+    }
+
+    #[test]
+    fn test_keplerian_round_trip() {
+        // Inclined eccentric orbit, then an inclined circular one where the argument of
+        // latitude takes the place of the true anomaly
+        let cases = [
+            na::Vector6::new(WGS84_A + 3_000_000.0, 0.2, 0.9, 1.1, 0.7, 0.4),
+            na::Vector6::new(WGS84_A + 800_000.0, 0.0, 1.7, 4.0, 0.0, 2.5),
+        ];
+
+        for elements in cases {
+            let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+            let recovered = OrbitalMechanics::cartesian_to_keplerian(&r, &v);
+            assert_relative_eq!(recovered[0], elements[0], max_relative = 1e-12);
+            assert_relative_eq!(recovered[1], elements[1], epsilon = 1e-12);
+            for k in 2..6 {
+                assert_relative_eq!(recovered[k], elements[k], epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eccentricity_and_angular_momentum_vectors() {
+        let elements = na::Vector6::new(WGS84_A + 3_000_000.0, 0.2, 0.9, 1.1, 0.7, 2.0);
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let e_vec = OrbitalMechanics::eccentricity_vector(&r, &v);
+        let h = OrbitalMechanics::angular_momentum_vector(&r, &v);
+
+        // The eccentricity vector points at the perigee position
+        let mut perigee = elements;
+        perigee[5] = 0.0;
+        let (r_perigee, _) = OrbitalMechanics::keplerian_to_cartesian(&perigee);
+        assert_relative_eq!(e_vec.magnitude(), elements[1], epsilon = 1e-12);
+        assert_relative_eq!(e_vec.normalize(), r_perigee.normalize(), epsilon = 1e-12);
+
+        // h is normal to both position and velocity, and its direction sets the inclination
+        assert_relative_eq!(h.normalize().dot(&r.normalize()), 0.0, epsilon = 1e-12);
+        assert_relative_eq!(h.normalize().dot(&v.normalize()), 0.0, epsilon = 1e-12);
+        assert_relative_eq!(h.normalize().z, elements[2].cos(), epsilon = 1e-12);
+        assert_relative_eq!(
+            h.magnitude(),
+            (MU_EARTH * elements[0] * (1.0 - elements[1].powi(2))).sqrt(),
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_circular_orbit_state() {
+        let (altitude, inclination, raan, true_anomaly) = (550_000.0, 0.9, 2.1, 0.7);
+        let (r, v) =
+            OrbitalMechanics::circular_orbit_state(altitude, inclination, raan, true_anomaly);
+
+        assert_relative_eq!(r.magnitude(), WGS84_A + altitude, max_relative = 1e-12);
+        assert_relative_eq!(
+            v.magnitude(),
+            OrbitalMechanics::compute_circular_velocity(WGS84_A + altitude),
+            max_relative = 1e-12
+        );
+
+        let elements = OrbitalMechanics::cartesian_to_keplerian(&r, &v);
+        assert_relative_eq!(elements[0], WGS84_A + altitude, max_relative = 1e-12);
+        assert!(elements[1] < 1e-12);
+        assert_relative_eq!(elements[2], inclination, epsilon = 1e-12);
+        assert_relative_eq!(elements[3], raan, epsilon = 1e-12);
+        assert_relative_eq!(elements[5], true_anomaly, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_sample_orbit() {
+        let elements = na::Vector6::new(WGS84_A + 10_000_000.0, 0.3, 0.9, 1.1, 0.4, 2.0);
+        let (rp, ra) = (elements[0] * 0.7, elements[0] * 1.3);
+
+        let points = OrbitalMechanics::sample_orbit(&elements, 181);
+        assert_eq!(points.len(), 181);
+        assert_relative_eq!(points[0], points[180], epsilon = 1e-6);
+
+        // Periapsis first, apoapsis halfway round and at the largest radius
+        let radii: Vec<f64> = points.iter().map(|point| point.magnitude()).collect();
+        let farthest = (0..radii.len())
+            .max_by(|&i, &j| radii[i].total_cmp(&radii[j]))
+            .unwrap();
+        assert_eq!(farthest, 90);
+        assert_relative_eq!(radii[0], rp, max_relative = 1e-12);
+        assert_relative_eq!(radii[90], ra, max_relative = 1e-12);
+
+        // Every point agrees with the full conversion at the same true anomaly
+        for (k, point) in points.iter().enumerate().step_by(30) {
+            let mut at_point = elements;
+            at_point[5] = (2.0 * k as f64).to_radians();
+            let (expected, _) = OrbitalMechanics::keplerian_to_cartesian(&at_point);
+            assert_relative_eq!(*point, expected, max_relative = 1e-12);
+        }
+
+        // Equal steps in eccentric anomaly trace the same ellipse with more even spacing
+        let even = OrbitalMechanics::sample_orbit_with_spacing(
+            &elements,
+            181,
+            AnomalySpacing::EccentricAnomaly,
+        );
+        assert_relative_eq!(even[0], points[0], max_relative = 1e-12);
+        assert_relative_eq!(even[90], points[90], max_relative = 1e-12);
+        let gaps = |points: &[na::Vector3<f64>]| -> (f64, f64) {
+            let gaps: Vec<f64> = points
+                .windows(2)
+                .map(|w| (w[1] - w[0]).magnitude())
+                .collect();
+            (
+                gaps.iter().copied().fold(f64::INFINITY, f64::min),
+                gaps.iter().copied().fold(0.0, f64::max),
+            )
+        };
+        let (min_true, max_true) = gaps(&points);
+        let (min_even, max_even) = gaps(&even);
+        assert!(max_even / min_even < max_true / min_true);
+
+        assert!(OrbitalMechanics::sample_orbit(&elements, 0).is_empty());
+        assert_eq!(
+            OrbitalMechanics::sample_orbit(&elements, 1),
+            vec![points[0]]
+        );
+    }
+
+    #[test]
+    fn test_apsis_velocity_partials_match_finite_differences() {
+        // Away from the apsides, so the flight path angle is not zero
+        let elements = na::Vector6::new(WGS84_A + 3_000_000.0, 0.2, 0.7, 0.3, 1.1, 2.2);
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let (d_apoapsis, d_periapsis) = OrbitalMechanics::apsis_velocity_partials(&r, &v);
+
+        let h = 1e-3; // m/s
+        for axis in 0..3 {
+            let mut step = na::Vector3::zeros();
+            step[axis] = h;
+            let (ra_plus, rp_plus) = OrbitalMechanics::compute_apsides(&r, &(v + step));
+            let (ra_minus, rp_minus) = OrbitalMechanics::compute_apsides(&r, &(v - step));
+            assert_relative_eq!(
+                d_apoapsis[axis],
+                (ra_plus - ra_minus) / (2.0 * h),
+                max_relative = 1e-6
+            );
+            assert_relative_eq!(
+                d_periapsis[axis],
+                (rp_plus - rp_minus) / (2.0 * h),
+                max_relative = 1e-6
+            );
+        }
+
+        // At perigee a prograde burn leaves the perigee where it is and moves the apogee by
+        // twice the change in semi-major axis, da/dv = 2a²v/μ
+        let mut at_perigee = elements;
+        at_perigee[5] = 0.0;
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&at_perigee);
+        let (d_apoapsis, d_periapsis) = OrbitalMechanics::apsis_velocity_partials(&r, &v);
+        let prograde = v.normalize();
+        let a = elements[0];
+        assert_relative_eq!(
+            d_apoapsis.dot(&prograde),
+            4.0 * a * a * v.magnitude() / MU_EARTH,
+            max_relative = 1e-9
+        );
+        assert_relative_eq!(d_periapsis.dot(&prograde), 0.0, epsilon = 1e-6);
+    }
+
+    // The integrator and dynamics need std
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_mean_semi_major_axis_filters_j2_oscillation() {
+        use crate::config::spacecraft::SimpleSat;
+        use crate::integrators::rk4::RK4;
+        use crate::models::TranslationalState;
+        use crate::physics::dynamics::TranslationalDynamics;
+        use crate::physics::environment::Atmosphere;
+
+        let elements = na::Vector6::new(WGS84_A + 500_000.0, 0.01, 0.9, 0.4, 1.0, 0.2);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let spacecraft = SimpleSat::default();
+        let mut state = TranslationalState::new(&spacecraft, position, velocity);
+
+        // One orbit under point-mass gravity and J2 alone
+        let vacuum = Atmosphere {
+            density_scale: 0.0,
+            ..Default::default()
+        };
+        let integrator = RK4::new(
+            TranslationalDynamics::<SimpleSat>::new(None)
+                .with_atmosphere(vacuum)
+                .with_j2(),
+        );
+        let dt = 5.0;
+        let (mut osculating, mut mean) = (Vec::new(), Vec::new());
+        for _ in 0..(OrbitalMechanics::compute_orbital_period(elements[0]) / dt) as usize {
+            state = integrator.integrate(&state, dt);
+            let (r, v) = (state.position, state.velocity);
+            osculating.push(OrbitalMechanics::cartesian_to_keplerian(&r, &v)[0]);
+            mean.push(OrbitalMechanics::mean_semi_major_axis(&r, &v));
+        }
+
+        let spread = |values: &[f64]| {
+            values.iter().cloned().fold(f64::MIN, f64::max)
+                - values.iter().cloned().fold(f64::MAX, f64::min)
+        };
+        // The osculating axis swings by kilometres; the mean one holds to tens of metres
+        assert!(spread(&osculating) > 10_000.0, "{}", spread(&osculating));
+        assert!(spread(&mean) < 25.0, "{}", spread(&mean));
+    }
+
+    #[test]
+    fn test_argument_of_latitude_and_nodes() {
+        // Inclined and eccentric, so u differs from the true anomaly by ω
+        let mut elements = na::Vector6::new(WGS84_A + 1_000_000.0, 0.1, 0.9, 0.6, 1.1, 0.0);
+        for nu in [0.0, 1.0, 3.0, 5.5] {
+            elements[5] = nu;
+            let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+            assert_relative_eq!(
+                OrbitalMechanics::argument_of_latitude(&r, &v),
+                (1.1 + nu) % (2.0 * PI),
+                epsilon = 1e-9
+            );
+        }
+
+        // At u = 0 the orbit crosses the equator northbound, at u = π southbound
+        elements[5] = PI - 1.1;
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        assert_eq!(OrbitalMechanics::is_near_node(&r, &v, 100.0), (false, true));
+
+        elements[5] = 2.0 * PI - 1.1;
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        assert_relative_eq!(r.z, 0.0, epsilon = 1e-6);
+        assert_eq!(OrbitalMechanics::is_near_node(&r, &v, 100.0), (true, false));
+
+        // A few seconds later the spacecraft has left the tolerance
+        let (r_later, v_later) = OrbitalMechanics::propagate_kepler(&r, &v, 2.0).unwrap();
+        assert!(r_later.z > 1_000.0);
+        assert_eq!(
+            OrbitalMechanics::is_near_node(&r_later, &v_later, 100.0),
+            (false, false)
+        );
+        assert_eq!(
+            OrbitalMechanics::is_near_node(&r_later, &v_later, 100_000.0),
+            (true, false)
+        );
+    }
+
+    #[test]
+    fn test_is_bound() {
+        let r = na::Vector3::new(WGS84_A + 400_000.0, 0.0, 0.0);
+        let escape_velocity = (2.0 * MU_EARTH / r.magnitude()).sqrt();
+
+        assert!(OrbitalMechanics::is_bound(
+            &r,
+            &na::Vector3::new(0.0, 0.999 * escape_velocity, 0.0)
+        ));
+        assert!(!OrbitalMechanics::is_bound(
+            &r,
+            &na::Vector3::new(0.0, 1.001 * escape_velocity, 0.0)
+        ));
+        assert!(!OrbitalMechanics::is_bound(
+            &r,
+            &na::Vector3::new(0.0, 0.0, 1.1 * escape_velocity)
+        ));
+    }
+
+    #[test]
+    fn test_orbital_period_400km() {
+        // Published period for a 400 km circular orbit above the WGS84 equatorial radius
+        // with GM = 3.986004418e14 m³/s²: 5553.62 s (92.56 min)
+        let period =
+            OrbitalMechanics::compute_orbital_period(crate::constants::WGS84_A + 400_000.0);
+        assert_relative_eq!(period, 5553.62, epsilon = 0.01);
+    }
+
+    #[test]
+    fn test_orbital_period_geostationary() {
+        // A geostationary orbit repeats once per sidereal day, 86164.09 s
+        let period = OrbitalMechanics::compute_orbital_period(42_164_170.0);
+        assert_relative_eq!(period, 86164.09, epsilon = 0.1);
+    }
+
+    #[test]
+    fn test_compute_circular_velocity() {
+        // Textbook values: ~7.67 km/s at 400 km and ~3.07 km/s at geostationary radius
+        let v_leo =
+            OrbitalMechanics::compute_circular_velocity(crate::constants::WGS84_A + 400_000.0);
+        assert_relative_eq!(v_leo, 7668.56, epsilon = 0.01);
+
+        let v_geo = OrbitalMechanics::compute_circular_velocity(42_164_137.0);
+        assert_relative_eq!(v_geo, 3074.66, epsilon = 0.01);
+    }
+
+    #[test]
+    fn test_is_near_apsis() {
+        let rp = crate::constants::WGS84_A + 300_000.0;
+        let ra = crate::constants::WGS84_A + 1_000_000.0;
+        let a = (ra + rp) / 2.0;
+        let v_perigee = (MU_EARTH * (2.0 / rp - 1.0 / a)).sqrt();
+        let v_apogee = (MU_EARTH * (2.0 / ra - 1.0 / a)).sqrt();
+
+        let at_perigee = OrbitalMechanics::is_near_apsis(
+            &na::Vector3::new(rp, 0.0, 0.0),
+            &na::Vector3::new(0.0, v_perigee, 0.0),
+            1.0,
+        );
+        assert_eq!(at_perigee, (false, true));
+
+        let at_apogee = OrbitalMechanics::is_near_apsis(
+            &na::Vector3::new(-ra, 0.0, 0.0),
+            &na::Vector3::new(0.0, -v_apogee, 0.0),
+            1.0,
+        );
+        assert_eq!(at_apogee, (true, false));
+    }
+
+    #[test]
+    fn test_anomaly_conversions() {
+        // For e = 0.5 and ν = 90°, cos E = (e + cos ν) / (1 + e cos ν) = 0.5, so E = 60°
+        let e = 0.5;
+        let ecc_anomaly = OrbitalMechanics::true_to_eccentric_anomaly(PI / 2.0, e);
+        assert_relative_eq!(ecc_anomaly, PI / 3.0, epsilon = 1e-12);
+
+        let mean_anomaly = OrbitalMechanics::eccentric_to_mean_anomaly(ecc_anomaly, e);
+        assert_relative_eq!(mean_anomaly, 0.6141848493043783, epsilon = 1e-12);
+
+        let recovered =
+            OrbitalMechanics::mean_to_eccentric_anomaly(mean_anomaly, e, 1e-14, 50).unwrap();
+        assert_relative_eq!(recovered, ecc_anomaly, epsilon = 1e-12);
+
+        // Apoapsis maps to π in all three anomalies, and the descending half stays in [0, 2π)
+        assert_relative_eq!(
+            OrbitalMechanics::true_to_eccentric_anomaly(PI, e),
+            PI,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            OrbitalMechanics::true_to_eccentric_anomaly(3.0 * PI / 2.0, e),
+            5.0 * PI / 3.0,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            OrbitalMechanics::eccentric_to_mean_anomaly(5.0 * PI / 3.0, e),
+            2.0 * PI - 0.6141848493043783,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_time_since_perigee() {
+        let a = WGS84_A + 2_000_000.0;
+        let period = OrbitalMechanics::compute_orbital_period(a);
+        for e in [0.0, 0.1, 0.7] {
+            assert_eq!(OrbitalMechanics::time_since_perigee(0.0, a, e), 0.0);
+            assert_relative_eq!(
+                OrbitalMechanics::time_since_perigee(PI, a, e),
+                period / 2.0,
+                max_relative = 1e-12
+            );
+        }
+
+        // The spacecraft moves fastest near perigee, so 90° past perigee comes well before a
+        // quarter period, and the time from there to 270° matches the mean anomalies of the
+        // e = 0.5 example above
+        let e = 0.5;
+        let quarter = OrbitalMechanics::time_since_perigee(PI / 2.0, a, e);
+        assert_relative_eq!(
+            quarter,
+            0.6141848493043783 / (2.0 * PI) * period,
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            OrbitalMechanics::time_since_perigee(3.0 * PI / 2.0, a, e),
+            period - quarter,
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_sun_synchronous_inclination_700km() {
+        let a = crate::constants::WGS84_A + 700_000.0;
+
+        // The node must follow the Sun's mean motion, 360° per tropical year (~0.9856°/day)
+        let target_rate = 2.0 * PI / (365.2422 * 86400.0);
+        let n = (MU_EARTH / a.powi(3)).sqrt();
+        let cos_i = -target_rate / (1.5 * n * EARTH_J2 * (WGS84_A / a).powi(2));
+        let inclination = cos_i.acos();
+
+        assert_relative_eq!(
+            OrbitalMechanics::raan_dot_j2(a, 0.0, inclination).to_degrees() * 86400.0,
+            0.9856,
+            epsilon = 1e-4
+        );
+        assert!(
+            (inclination.to_degrees() - 98.0).abs() < 0.5,
+            "inclination {}",
+            inclination.to_degrees()
+        );
+
+        // Near the critical inclination the apsidal line stops rotating
+        let critical = (1.0 / 5.0_f64.sqrt()).acos();
+        assert_relative_eq!(
+            OrbitalMechanics::arg_perigee_dot_j2(a, 0.01, critical),
+            0.0,
+            epsilon = 1e-18
+        );
+        assert!(OrbitalMechanics::arg_perigee_dot_j2(a, 0.01, 0.5) > 0.0);
+    }
+
+    #[test]
+    fn test_mean_to_eccentric_anomaly_high_eccentricity() {
+        let e = 0.95;
+        for mean_anomaly in [0.01, 0.1, 0.5, 1.0, PI - 0.01, PI + 0.5, 2.0 * PI - 0.05] {
+            let ecc_anomaly =
+                OrbitalMechanics::mean_to_eccentric_anomaly(mean_anomaly, e, 1e-14, 50)
+                    .expect("Kepler solver should converge for e = 0.95");
+
+            // E must satisfy Kepler's equation
+            let residual = ecc_anomaly - e * ecc_anomaly.sin() - mean_anomaly;
+            assert!(
+                residual.abs() < 1e-12,
+                "residual {residual} for M = {mean_anomaly}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mean_to_eccentric_anomaly_reports_non_convergence() {
+        let result = OrbitalMechanics::mean_to_eccentric_anomaly(0.1, 0.95, 1e-14, 1);
+        assert!(matches!(
+            result,
+            Err(OrbitalErrors::KeplerNonConvergence { iterations: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_keplerian_to_cartesian_equatorial_circular() {
+        // With i = 0 the node and periapsis are degenerate: only Ω + ω + ν fixes the position
+        let a = 7_000_000.0;
+        let elements = na::Vector6::new(a, 0.0, 0.0, 0.3, 0.2, 0.5);
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+
+        let u: f64 = 0.3 + 0.2 + 0.5;
+        let v_circ = (MU_EARTH / a).sqrt();
+        assert_relative_eq!(
+            r,
+            na::Vector3::new(a * u.cos(), a * u.sin(), 0.0),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            v,
+            na::Vector3::new(-v_circ * u.sin(), v_circ * u.cos(), 0.0),
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn test_keplerian_to_cartesian_retrograde_equatorial() {
+        // i = π flips the orbit normal to -z, so increasing ω + ν turns the position clockwise
+        let a = 7_000_000.0;
+        let elements = na::Vector6::new(a, 0.0, PI, 0.4, 0.25, 0.35);
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+
+        let theta: f64 = 0.4 - (0.25 + 0.35);
+        let v_circ = (MU_EARTH / a).sqrt();
+        assert_relative_eq!(
+            r,
+            na::Vector3::new(a * theta.cos(), a * theta.sin(), 0.0),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            v,
+            na::Vector3::new(v_circ * theta.sin(), -v_circ * theta.cos(), 0.0),
+            epsilon = 1e-9
+        );
+        assert!(r.cross(&v).z < 0.0);
+    }
+
+    #[test]
+    fn test_keplerian_to_cartesian_polar_eccentric_at_periapsis() {
+        let a = 8_000_000.0;
+        let e = 0.1;
+        let elements = na::Vector6::new(a, e, PI / 2.0, 0.0, 0.0, 0.0);
+        let (r, v) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+
+        // Periapsis sits on the ascending node, with the velocity straight up the z axis
+        let p = a * (1.0 - e * e);
+        let v_periapsis = (MU_EARTH / p).sqrt() * (1.0 + e);
+        assert_relative_eq!(r, na::Vector3::new(a * (1.0 - e), 0.0, 0.0), epsilon = 1e-6);
+        assert_relative_eq!(v, na::Vector3::new(0.0, 0.0, v_periapsis), epsilon = 1e-9);
+    }
 }
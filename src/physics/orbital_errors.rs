@@ -0,0 +1,29 @@
+use core::{error::Error, fmt};
+
+#[derive(Debug)]
+pub enum OrbitalErrors {
+    KeplerNonConvergence { iterations: i32, residual: f64 },
+    UnboundOrbit { specific_energy: f64 },
+}
+
+impl fmt::Display for OrbitalErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrbitalErrors::KeplerNonConvergence {
+                iterations,
+                residual,
+            } => write!(
+                f,
+                "Kepler's equation did not converge after {} iterations (last step {:e} rad)",
+                iterations, residual
+            ),
+            OrbitalErrors::UnboundOrbit { specific_energy } => write!(
+                f,
+                "Orbit is not elliptical (specific energy {:e} J/kg)",
+                specific_energy
+            ),
+        }
+    }
+}
+
+impl Error for OrbitalErrors {}
@@ -0,0 +1,190 @@
+use crate::batch::{build_simulation, BatchConfig};
+use crate::config::spacecraft::SimpleSat;
+use crate::constants::{PI, WGS84_A};
+use crate::errors::KosmossError;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::physics::energy::ConservationReport;
+use crate::physics::orbital::OrbitalMechanics;
+use crate::telemetry::writer::{TelemetryOptions, TelemetryWriter};
+use hifitime::Epoch;
+use nalgebra as na;
+use std::path::PathBuf;
+
+/// Steps between telemetry records, besides every step with thrust
+const TELEMETRY_INTERVAL: usize = 600;
+
+/// Settings for `apogee_raise`. The default is the demo scenario: a 50 x 400 km polar orbit
+/// starting at apogee, with a burn commanded at 1000 s.
+#[derive(Debug, Clone)]
+pub struct ApogeeRaiseConfig {
+    /// Orbit, attitude, gains, burn target and maneuver time, as for a batch run
+    pub simulation: BatchConfig,
+    pub sample_interval: f64, // seconds between states kept in the trajectory
+    /// CSV telemetry file to write, if any
+    pub telemetry: Option<PathBuf>,
+}
+
+impl Default for ApogeeRaiseConfig {
+    fn default() -> Self {
+        let ra = WGS84_A + 400_000.0;
+        let rp = WGS84_A + 50_000.0;
+        Self {
+            simulation: BatchConfig {
+                elements: na::Vector6::new(
+                    (ra + rp) / 2.0,
+                    (ra - rp) / (ra + rp),
+                    89.0_f64.to_radians(),
+                    PI,
+                    PI * 0.075,
+                    PI,
+                ),
+                inertia: SimpleSat::inertia_tensor(),
+                angular_velocity: na::Vector3::new(0.05, 0.02, 0.01),
+                start_epoch: Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+                duration: 3200.0,
+                dt: 0.01,
+                kp: 1.0,
+                kd: 0.1,
+                target_apogee_radius: ra,
+                maneuver_time: Some(1000.0),
+            },
+            sample_interval: 60.0,
+            telemetry: None,
+        }
+    }
+}
+
+/// Trajectory and diagnostics of a scenario run
+#[derive(Debug, Clone)]
+pub struct SimulationResult<'a, T: SpacecraftProperties> {
+    /// The initial state, one state per sample interval, and the final state
+    pub trajectory: Vec<State<'a, T>>,
+    /// Mission elapsed times at which maneuvers were commanded
+    pub maneuver_times: Vec<f64>,
+    /// Time at which the spacecraft reached the ground, if it did before the end
+    pub decayed_at: Option<f64>,
+    pub conservation: ConservationReport,
+}
+
+impl<'a, T: SpacecraftProperties> SimulationResult<'a, T> {
+    /// (apogee, perigee) altitudes at the start [m]
+    pub fn initial_apsis_altitudes(&self) -> (f64, f64) {
+        let state = &self.trajectory[0];
+        OrbitalMechanics::compute_apsis_altitudes(&state.position, &state.velocity)
+    }
+
+    /// (apogee, perigee) altitudes at the last state reached [m]
+    pub fn final_apsis_altitudes(&self) -> (f64, f64) {
+        let state = &self.trajectory[self.trajectory.len() - 1];
+        OrbitalMechanics::compute_apsis_altitudes(&state.position, &state.velocity)
+    }
+}
+
+/// Flies one apogee-raising burn with the closed-loop simulation: nadir pointing, the
+/// maneuver at `maneuver_time` (if set) targeting `target_apogee_radius`, then coasting
+/// until `duration`. Reaching the ground ends the run early but is reported in the result
+/// rather than as an error.
+pub fn apogee_raise<'a, T: SpacecraftProperties>(
+    spacecraft: &'a T,
+    config: &ApogeeRaiseConfig,
+) -> Result<SimulationResult<'a, T>, KosmossError> {
+    // A NaN or non-positive interval would never advance the sampling loop
+    if config.sample_interval.is_nan() || config.sample_interval <= 0.0 {
+        return Err(KosmossError::InvalidConfig(format!(
+            "sample interval must be positive, got {}",
+            config.sample_interval
+        )));
+    }
+
+    let mut simulation = build_simulation(spacecraft, &config.simulation);
+    let mut trajectory = vec![simulation.state().clone()];
+    if let Some(path) = &config.telemetry {
+        simulation.set_telemetry(
+            TelemetryWriter::create(
+                path,
+                TelemetryOptions {
+                    pointing_error: true,
+                    ..Default::default()
+                },
+            )?,
+            TELEMETRY_INTERVAL,
+        );
+    }
+
+    let duration = config.simulation.duration;
+    let mut decayed_at = None;
+    let mut time = 0.0;
+    while time < duration {
+        time = (time + config.sample_interval).min(duration);
+        match simulation.run_until(time) {
+            Ok(state) => trajectory.push(state.clone()),
+            Err(KosmossError::OrbitDecayed { time, .. }) => {
+                trajectory.push(simulation.state().clone());
+                decayed_at = Some(time);
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    simulation.finish()?;
+
+    Ok(SimulationResult {
+        trajectory,
+        maneuver_times: simulation.maneuvers().commanded_times().to_vec(),
+        decayed_at,
+        conservation: simulation.conservation_report(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_apogee_raise_raises_apogee() {
+        let spacecraft = SimpleSat::default();
+        let mut config = ApogeeRaiseConfig::default();
+        config.simulation.elements = na::Vector6::new(WGS84_A + 400_000.0, 0.0, 0.9, 0.0, 0.0, 0.0);
+        config.simulation.duration = 600.0;
+        config.simulation.dt = 0.1;
+        config.simulation.target_apogee_radius = WGS84_A + 600_000.0;
+        config.simulation.maneuver_time = Some(150.0);
+
+        let result = apogee_raise(&spacecraft, &config).unwrap();
+
+        assert_eq!(result.maneuver_times.len(), 1);
+        assert_relative_eq!(result.maneuver_times[0], 150.0, epsilon = 0.1);
+        assert_eq!(result.decayed_at, None);
+        // The initial state plus one per minute
+        assert_eq!(result.trajectory.len(), 11);
+        assert_relative_eq!(
+            result.trajectory[10].mission_elapsed_time,
+            600.0,
+            epsilon = 1e-6
+        );
+
+        let (initial_apogee, _) = result.initial_apsis_altitudes();
+        let (final_apogee, _) = result.final_apsis_altitudes();
+        assert!(
+            final_apogee > initial_apogee + 150_000.0,
+            "{final_apogee} m"
+        );
+    }
+
+    #[test]
+    fn test_apogee_raise_rejects_non_positive_sample_interval() {
+        let spacecraft = SimpleSat::default();
+        for sample_interval in [0.0, -60.0, f64::NAN] {
+            let config = ApogeeRaiseConfig {
+                sample_interval,
+                ..Default::default()
+            };
+            assert!(matches!(
+                apogee_raise(&spacecraft, &config),
+                Err(KosmossError::InvalidConfig(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,1377 @@
+use crate::coordinates::coordinate_transformation::itrs_to_geodetic_signed;
+use crate::coordinates::eop_provider::{EOPProvider, ManagedEOP};
+use crate::errors::KosmossError;
+use crate::fsm::maneuver_schedule::{Maneuver, ManeuverSchedule};
+use crate::fsm::spacecraft_states::SpacecraftState;
+use crate::fsm::state_machine::SpacecraftFSM;
+use crate::gnc::control::attitude_controller::GeometricAttitudeController;
+use crate::gnc::control::reaction_wheels::ReactionWheelArray;
+use crate::gnc::guidance::thrust_ramp::ThrustRamp;
+use crate::gnc::guidance::Guidance;
+use crate::integrators::rk4::RK4;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::numerics::quaternion::Quaternion;
+use crate::physics::dynamics::SpacecraftDynamics;
+use crate::physics::energy::{ConservationReport, Invariants};
+use crate::physics::environment::Atmosphere;
+use crate::physics::ephemeris::sun_position;
+use crate::physics::orbital::OrbitalMechanics;
+use crate::telemetry::writer::TelemetryWriter;
+use chrono::Duration;
+use nalgebra as na;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+/// Scales the time step with altitude, so draggy low-altitude phases take small steps while
+/// high-altitude coasting takes large ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AltitudeStepScaling {
+    pub reference_altitude: f64, // m, altitude at which the nominal dt is used
+    pub min_dt: f64,             // s
+    pub max_dt: f64,             // s
+}
+
+/// Called after every step with the new state and FSM mode; `Break` ends the run
+pub type StepHook<'a, T> = Box<dyn FnMut(&State<'a, T>, &SpacecraftState) -> ControlFlow<()> + 'a>;
+
+/// Drives a single spacecraft through the closed loop of FSM, guidance, control and
+/// integration, optionally logging telemetry along the way.
+pub struct Simulation<'a, T: SpacecraftProperties> {
+    state: State<'a, T>,
+    dt: f64,
+    step_count: usize,
+    elapsed: f64,
+    step_scaling: Option<AltitudeStepScaling>,
+    max_angular_rate: Option<f64>,
+
+    attitude_controller: GeometricAttitudeController,
+    sun_pointing_axis: na::Vector3<f64>,
+    guidance: Guidance,
+    thrust_ramp: Option<ThrustRamp>,
+    reaction_wheels: Option<ReactionWheelArray>,
+    atmosphere: Atmosphere,
+    fsm: SpacecraftFSM,
+    maneuvers: ManeuverSchedule,
+    step_hook: Option<StepHook<'a, T>>,
+    stopped: bool,
+    orbit_bound: bool,
+
+    telemetry: Option<TelemetryWriter<Box<dyn Write>>>,
+    sample_interval: usize,
+    sample_cadence: Option<f64>,
+    next_sample: usize,
+    eop_provider: Box<dyn EOPProvider>,
+    eop_age_warning: Option<Duration>,
+    eop_age_checked: bool,
+    eop_age_warned: bool,
+    initial_invariants: Invariants,
+}
+
+impl<'a, T: SpacecraftProperties> Simulation<'a, T> {
+    pub fn new(
+        initial_state: State<'a, T>,
+        attitude_controller: GeometricAttitudeController,
+        guidance: impl Into<Guidance>,
+        dt: f64,
+    ) -> Self {
+        let initial_invariants = Invariants::of(&initial_state);
+        let orbit_bound =
+            OrbitalMechanics::is_bound(&initial_state.position, &initial_state.velocity);
+
+        Self {
+            state: initial_state,
+            dt,
+            step_count: 0,
+            elapsed: 0.0,
+            step_scaling: None,
+            max_angular_rate: None,
+            attitude_controller,
+            sun_pointing_axis: na::Vector3::z(),
+            guidance: guidance.into(),
+            thrust_ramp: None,
+            reaction_wheels: None,
+            atmosphere: Atmosphere::default(),
+            fsm: SpacecraftFSM::new(),
+            maneuvers: ManeuverSchedule::new(),
+            step_hook: None,
+            stopped: false,
+            orbit_bound,
+            telemetry: None,
+            sample_interval: 1,
+            sample_cadence: None,
+            next_sample: 0,
+            eop_provider: Box::new(ManagedEOP::default()),
+            eop_age_warning: None,
+            eop_age_checked: false,
+            eop_age_warned: false,
+            initial_invariants,
+        }
+    }
+
+    /// Body axis (e.g. the solar array normal) held on the Sun in safe mode; body +z by default
+    pub fn set_sun_pointing_axis(&mut self, axis: na::Vector3<f64>) {
+        self.sun_pointing_axis = axis;
+    }
+
+    /// Passes guidance thrust commands through `ramp`, so burns build up and tail off over
+    /// several steps rather than switching on and off in one
+    pub fn set_thrust_ramp(&mut self, ramp: ThrustRamp) {
+        self.thrust_ramp = Some(ramp);
+    }
+
+    /// Delivers control torques through `wheels`, so the attitude sees only what the wheels
+    /// can produce within their torque and speed limits
+    pub fn set_reaction_wheels(&mut self, wheels: ReactionWheelArray) {
+        self.reaction_wheels = Some(wheels);
+    }
+
+    pub fn reaction_wheels(&self) -> Option<&ReactionWheelArray> {
+        self.reaction_wheels.as_ref()
+    }
+
+    /// Selects the atmosphere density model, and any scaling of it, used for drag
+    pub fn set_atmosphere(&mut self, atmosphere: Atmosphere) {
+        self.atmosphere = atmosphere;
+    }
+
+    /// Commands a maneuver at the first step at or after `time` (seconds since start)
+    /// during which the FSM is able to accept it
+    pub fn schedule_maneuver(&mut self, time: f64) {
+        self.add_maneuver(Maneuver::at(time));
+    }
+
+    /// Adds `maneuver` to the mission plan. Maneuvers are commanded one at a time, in order
+    /// of their earliest trigger time.
+    pub fn add_maneuver(&mut self, maneuver: Maneuver) {
+        self.maneuvers.push(maneuver);
+    }
+
+    pub fn maneuvers(&self) -> &ManeuverSchedule {
+        &self.maneuvers
+    }
+
+    /// Runs `hook` at the end of every step, e.g. for live plotting or custom logging. When
+    /// it returns `ControlFlow::Break` the simulation is stopped at the state the hook saw:
+    /// `run_until` and `states` end there, successfully, and `is_stopped` reports it.
+    pub fn set_step_hook(
+        &mut self,
+        hook: impl FnMut(&State<'a, T>, &SpacecraftState) -> ControlFlow<()> + 'a,
+    ) {
+        self.step_hook = Some(Box::new(hook));
+    }
+
+    /// Logs a telemetry record every `sample_interval` steps and on every thrusting step
+    pub fn set_telemetry(
+        &mut self,
+        telemetry: TelemetryWriter<Box<dyn Write>>,
+        sample_interval: usize,
+    ) {
+        self.telemetry = Some(telemetry);
+        self.sample_interval = sample_interval.max(1);
+        self.sample_cadence = None;
+    }
+
+    /// Logs a telemetry record at every exact multiple of `cadence` seconds, interpolating
+    /// between the bracketing integration steps when `dt` does not divide the cadence.
+    /// Fails unless `cadence` is finite and positive.
+    pub fn set_telemetry_cadence(
+        &mut self,
+        telemetry: TelemetryWriter<Box<dyn Write>>,
+        cadence: f64,
+    ) -> Result<(), KosmossError> {
+        // A zero, negative or NaN cadence would never move the sample time past the clock
+        if !cadence.is_finite() || cadence <= 0.0 {
+            return Err(KosmossError::InvalidConfig(format!(
+                "telemetry cadence must be finite and positive, got {}",
+                cadence
+            )));
+        }
+        self.telemetry = Some(telemetry);
+        self.sample_cadence = Some(cadence);
+        self.next_sample = (self.current_time() / cadence).ceil() as usize;
+        Ok(())
+    }
+
+    /// Replaces the source of Earth orientation parameters used for telemetry
+    pub fn set_eop_provider(&mut self, eop_provider: Box<dyn EOPProvider>) {
+        self.eop_provider = eop_provider;
+    }
+
+    /// Warns at the first step when the EOP data of the provider is older than `threshold`,
+    /// whether or not telemetry is logged
+    pub fn set_eop_age_warning(&mut self, threshold: Duration) {
+        self.eop_age_warning = Some(threshold);
+        self.eop_age_checked = false;
+    }
+
+    /// True once the EOP age check has found the data older than the threshold set with
+    /// `set_eop_age_warning`
+    pub fn is_eop_data_stale(&self) -> bool {
+        self.eop_age_warned
+    }
+
+    pub fn state(&self) -> &State<'a, T> {
+        &self.state
+    }
+
+    /// True once the step hook has asked to end the run
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    pub fn fsm(&self) -> &SpacecraftFSM {
+        &self.fsm
+    }
+
+    pub fn current_time(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// False once the orbit has reached escape energy, which guidance will not burn on
+    pub fn is_orbit_bound(&self) -> bool {
+        self.orbit_bound
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.step_count
+    }
+
+    /// Energy, angular momentum and semi-major axis at the start and now, with their
+    /// relative changes. Printing it gives a two-line check of whether the run conserved
+    /// what it should.
+    pub fn conservation_report(&self) -> ConservationReport {
+        ConservationReport {
+            initial: self.initial_invariants,
+            last: Invariants::of(&self.state),
+        }
+    }
+
+    /// Scales the nominal `dt` in proportion to the current altitude above `WGS84_A`,
+    /// bounded to `[min_dt, max_dt]`. Fails unless the bounds are finite with
+    /// `0 < min_dt <= max_dt` and the reference altitude is positive.
+    pub fn set_step_scaling(
+        &mut self,
+        step_scaling: AltitudeStepScaling,
+    ) -> Result<(), KosmossError> {
+        let AltitudeStepScaling {
+            reference_altitude,
+            min_dt,
+            max_dt,
+        } = step_scaling;
+        // Clamping to bounds that are NaN or out of order would panic on the first step
+        if !min_dt.is_finite() || !max_dt.is_finite() || min_dt <= 0.0 || min_dt > max_dt {
+            return Err(KosmossError::InvalidConfig(format!(
+                "step bounds must be finite with 0 < min_dt <= max_dt, got [{}, {}]",
+                min_dt, max_dt
+            )));
+        }
+        if reference_altitude.is_nan() || reference_altitude <= 0.0 {
+            return Err(KosmossError::InvalidConfig(format!(
+                "reference altitude must be positive, got {}",
+                reference_altitude
+            )));
+        }
+        self.step_scaling = Some(step_scaling);
+        Ok(())
+    }
+
+    /// Clamps the body rate to at most `limit` rad/s at the start of every step, so a
+    /// runaway controller or disturbance cannot spin the attitude up until the propagation
+    /// overflows. Off by default. Fails unless `limit` is finite and positive.
+    pub fn set_max_angular_rate(&mut self, limit: f64) -> Result<(), KosmossError> {
+        // A negative limit would reverse the rate, a zero one freeze the attitude and a NaN
+        // one disable the clamp
+        if !limit.is_finite() || limit <= 0.0 {
+            return Err(KosmossError::InvalidConfig(format!(
+                "maximum angular rate must be finite and positive, got {}",
+                limit
+            )));
+        }
+        self.max_angular_rate = Some(limit);
+        Ok(())
+    }
+
+    /// Size of the next time step
+    pub fn next_dt(&self) -> f64 {
+        match self.step_scaling {
+            Some(scaling) => {
+                let altitude = OrbitalMechanics::current_altitude(&self.state.position);
+                (self.dt * altitude / scaling.reference_altitude)
+                    .clamp(scaling.min_dt, scaling.max_dt)
+            }
+            None => self.dt,
+        }
+    }
+
+    /// Advances the simulation by one time step and returns the new state
+    pub fn step(&mut self) -> Result<&State<'a, T>, KosmossError> {
+        self.advance(self.next_dt())
+    }
+
+    /// Streams the simulation: an iterator that steps `interval` times per item and yields a
+    /// copy of the state reached. See `States` for how errors end the stream.
+    pub fn states(&mut self, interval: usize) -> States<'_, 'a, T> {
+        States {
+            simulation: self,
+            interval: interval.max(1),
+            error: None,
+        }
+    }
+
+    /// Steps the simulation until the mission elapsed time reaches `t_end` seconds, or the
+    /// step hook stops it
+    pub fn run_until(&mut self, t_end: f64) -> Result<&State<'a, T>, KosmossError> {
+        while !self.stopped {
+            let dt = self.next_dt();
+            let remaining = t_end - self.current_time();
+            if remaining <= 1e-6 * dt {
+                break;
+            }
+            // Shorten the last step so the run lands on t_end even when dt does not divide it
+            self.advance(dt.min(remaining))?;
+        }
+        Ok(&self.state)
+    }
+
+    fn advance(&mut self, dt: f64) -> Result<&State<'a, T>, KosmossError> {
+        let (thrust, control_torque) = self.prepare_step(dt)?;
+        let next = RK4::new(self.dynamics(thrust, control_torque)).integrate(&self.state, dt);
+        self.complete_step(next, dt, &thrust, &control_torque)
+    }
+
+    /// First half of a step of `dt`: runs the FSM, maneuvers, guidance and control, and logs
+    /// telemetry, returning the thrust and control torque to integrate the step with
+    pub(crate) fn prepare_step(
+        &mut self,
+        dt: f64,
+    ) -> Result<(na::Vector3<f64>, na::Vector3<f64>), KosmossError> {
+        let current_time = self.current_time();
+        self.sync_time(current_time);
+        self.check_eop_age();
+
+        if let Some(limit) = self.max_angular_rate {
+            let rate = self.state.angular_velocity.magnitude();
+            if rate > limit {
+                self.state.angular_velocity *= limit / rate;
+            }
+        }
+
+        // Evaluate state transitions
+        self.fsm.evaluate_transition(&self.state);
+
+        // Apply an impulsive maneuver and end it at once. End any other once its guidance
+        // reports the target reached or it has burned for its duration, or drop it if the
+        // FSM has left the maneuver states (e.g. for an emergency)
+        if let Some(maneuver) = self.maneuvers.active() {
+            match self.fsm.get_current_state() {
+                SpacecraftState::ManeuverPrep => {}
+                SpacecraftState::Maneuvering => {
+                    let finished = match maneuver.impulse {
+                        Some(impulse) => {
+                            impulse.apply(&mut self.state);
+                            true
+                        }
+                        None => {
+                            maneuver
+                                .guidance
+                                .as_ref()
+                                .unwrap_or(&self.guidance)
+                                .is_target_achieved(&self.state.position, &self.state.velocity)
+                                || current_time - self.fsm.get_last_state_change()
+                                    >= maneuver.duration
+                        }
+                    };
+                    if finished {
+                        self.fsm.complete_maneuver(current_time);
+                        self.maneuvers.finish_active();
+                    }
+                }
+                state => {
+                    println!("Maneuver abandoned at t={:.2}s in {}", current_time, state);
+                    self.maneuvers.finish_active();
+                }
+            }
+        }
+
+        // Guidance that decides for itself when to burn, like station keeping, is given a
+        // maneuver as soon as it asks for one
+        if self.maneuvers.active().is_none()
+            && !self.maneuvers.is_due(&self.state)
+            && self
+                .guidance
+                .requests_burn(&self.state.position, &self.state.velocity)
+        {
+            self.maneuvers.push(Maneuver::at(current_time));
+        }
+
+        // Check if it's time to start the next maneuver
+        if self.maneuvers.is_due(&self.state) && self.fsm.command_maneuver(current_time) {
+            println!("Maneuver commanded at t={:.2}s", current_time);
+            self.maneuvers.start_next(current_time);
+        }
+
+        // Compute control inputs based on current state
+        let (thrust, control_torque) = if self.fsm.should_apply_control() {
+            let control_torque = if self.fsm.should_point_at_sun() {
+                let sun = sun_position(&self.state.epoch) - self.state.position;
+                self.attitude_controller.compute_sun_pointing_torque(
+                    &sun.normalize(),
+                    &self.sun_pointing_axis,
+                    &self.state.quaternion,
+                    &self.state.angular_velocity,
+                )
+            } else {
+                self.attitude_controller.compute_control_torque(
+                    &self.state.position,
+                    &self.state.velocity,
+                    &self.state.quaternion,
+                    &self.state.angular_velocity,
+                )
+            };
+
+            let thrust = if self.fsm.should_apply_thrust() {
+                let guidance = match self
+                    .maneuvers
+                    .active_mut()
+                    .and_then(|maneuver| maneuver.guidance.as_mut())
+                {
+                    Some(guidance) => guidance,
+                    None => &mut self.guidance,
+                };
+                guidance.get_desired_force(
+                    self.state.spacecraft,
+                    &self.state.position,
+                    &self.state.velocity,
+                    current_time,
+                )
+            } else {
+                na::Vector3::zeros()
+            };
+
+            (thrust, control_torque)
+        } else {
+            (na::Vector3::zeros(), na::Vector3::zeros())
+        };
+        let thrust = match (self.thrust_ramp.as_mut(), self.fsm.get_current_state()) {
+            (
+                Some(ramp),
+                SpacecraftState::NominalOperation
+                | SpacecraftState::ManeuverPrep
+                | SpacecraftState::Maneuvering,
+            ) => ramp.apply(&thrust, dt),
+            // A fault shuts the engine off at once rather than letting the burn tail off
+            (Some(ramp), _) => {
+                ramp.cut_off();
+                na::Vector3::zeros()
+            }
+            (None, _) => thrust,
+        };
+        let control_torque = match self.reaction_wheels.as_mut() {
+            Some(wheels) => wheels.apply(&control_torque, dt),
+            None => control_torque,
+        };
+
+        // Write telemetry at the regular sampling interval or whenever thrust is applied
+        if self.sample_cadence.is_none()
+            && (self.step_count.is_multiple_of(self.sample_interval) || thrust.magnitude() > 0.0)
+        {
+            self.write_telemetry(&self.state.clone(), &thrust, &control_torque)?;
+        }
+
+        Ok((thrust, control_torque))
+    }
+
+    /// Equations of motion for a step with the given control inputs
+    pub(crate) fn dynamics(
+        &self,
+        thrust: na::Vector3<f64>,
+        control_torque: na::Vector3<f64>,
+    ) -> SpacecraftDynamics<'a, T> {
+        SpacecraftDynamics::new(Some(thrust), Some(control_torque)).with_atmosphere(self.atmosphere)
+    }
+
+    /// Second half of a step of `dt` from `prepare_step`: takes `next`, the integrated state,
+    /// checks it, and emits the samples and hook call that fall within the step
+    pub(crate) fn complete_step(
+        &mut self,
+        next: State<'a, T>,
+        dt: f64,
+        thrust: &na::Vector3<f64>,
+        control_torque: &na::Vector3<f64>,
+    ) -> Result<&State<'a, T>, KosmossError> {
+        let current_time = self.current_time();
+        let previous = std::mem::replace(&mut self.state, next);
+
+        // RK4 lets the quaternion norm drift, geometrically once the body rate times the step
+        // is large, so bring it back to unit length every step
+        self.state.quaternion = self.state.quaternion.normalize();
+
+        self.step_count += 1;
+        self.elapsed += dt;
+        self.sync_time(self.current_time());
+
+        if !self.state.is_finite() {
+            return Err(KosmossError::NumericalDivergence {
+                time: self.current_time(),
+            });
+        }
+
+        // The ellipsoid is symmetric about the polar axis, which GCRS and ITRS share to within
+        // precession and polar motion, so the inertial position is close enough here
+        let (_, _, altitude) = itrs_to_geodetic_signed(&self.state.position);
+        if altitude < 0.0 {
+            return Err(KosmossError::OrbitDecayed {
+                time: self.current_time(),
+                altitude,
+            });
+        }
+
+        let orbit_bound = OrbitalMechanics::is_bound(&self.state.position, &self.state.velocity);
+        if orbit_bound != self.orbit_bound {
+            if orbit_bound {
+                println!("Orbit bound again at t={:.2}s", self.current_time());
+            } else {
+                println!(
+                    "Warning: orbit unbound (escape trajectory) at t={:.2}s",
+                    self.current_time()
+                );
+            }
+            self.orbit_bound = orbit_bound;
+        }
+
+        // Emit any fixed-cadence samples that fall within this step
+        if let Some(cadence) = self.sample_cadence {
+            loop {
+                let sample_time = self.next_sample as f64 * cadence;
+                if sample_time > self.current_time() + 1e-9 * dt {
+                    break;
+                }
+
+                let fraction = (sample_time - current_time) / dt;
+                let mut sample = previous.interpolate(&self.state, fraction);
+                sample.set_mission_elapsed_time(sample_time);
+
+                self.write_telemetry(&sample, thrust, control_torque)?;
+                self.next_sample += 1;
+            }
+        }
+
+        if let Some(hook) = self.step_hook.as_mut() {
+            if hook(&self.state, &self.fsm.get_current_state()).is_break() {
+                self.stopped = true;
+            }
+        }
+
+        Ok(&self.state)
+    }
+
+    /// Flushes any buffered telemetry
+    pub fn finish(&mut self) -> Result<(), KosmossError> {
+        if let Some(telemetry) = self.telemetry.as_mut() {
+            telemetry.flush()?;
+        }
+        Ok(())
+    }
+
+    fn sync_time(&mut self, time: f64) {
+        self.state.set_mission_elapsed_time(time);
+    }
+
+    fn check_eop_age(&mut self) {
+        let Some(threshold) = self.eop_age_warning else {
+            return;
+        };
+        if self.eop_age_checked {
+            return;
+        }
+        self.eop_age_checked = true;
+
+        if let Some(age) = self.eop_provider.data_age() {
+            if age > threshold {
+                eprintln!(
+                    "Warning: EOP data is {:.1} days old at t={:.2}s",
+                    age.num_seconds() as f64 / 86_400.0,
+                    self.current_time()
+                );
+                self.eop_age_warned = true;
+            }
+        }
+    }
+
+    fn write_telemetry(
+        &mut self,
+        state: &State<'a, T>,
+        thrust: &na::Vector3<f64>,
+        control_torque: &na::Vector3<f64>,
+    ) -> Result<(), KosmossError> {
+        let Some(telemetry) = self.telemetry.as_mut() else {
+            return Ok(());
+        };
+
+        // Undefined energies (at r = 0) show up as NaN rather than an infinite error
+        let report = ConservationReport {
+            initial: self.initial_invariants,
+            last: Invariants::of(state),
+        };
+        let energy_error = report.relative_energy_change().abs();
+        let angular_momentum_error = report.relative_angular_momentum_change();
+
+        if let Some(wheels) = self.reaction_wheels.as_ref() {
+            telemetry.update_wheels(wheels);
+        }
+
+        // The reference the controller tracks in the current mode
+        let desired_frame = if self.fsm.should_point_at_sun() {
+            let sun = sun_position(&state.epoch) - state.position;
+            GeometricAttitudeController::sun_pointing_frame(
+                &sun.normalize(),
+                &self.sun_pointing_axis,
+                &state.quaternion,
+            )
+        } else {
+            self.attitude_controller
+                .nadir_frame(&state.position, &state.velocity)
+        };
+        telemetry.update_desired_attitude(&Quaternion::from_rotation_matrix(&desired_frame));
+
+        let eop = self.eop_provider.eop_data(state.epoch)?;
+        telemetry.write_sample_with_eop(
+            state,
+            thrust,
+            control_torque,
+            &self.fsm,
+            energy_error,
+            angular_momentum_error,
+            &eop,
+        )
+    }
+}
+
+/// Iterator over the states of a `Simulation`, from `Simulation::states`. It only ends when
+/// the step hook stops the simulation, yielding the state it stopped at last, or at the
+/// first step that fails (e.g. on orbit decay), keeping the error for `error`. Use `by_ref`
+/// to look at the error after an adaptor like `take_while`.
+pub struct States<'s, 'a, T: SpacecraftProperties> {
+    simulation: &'s mut Simulation<'a, T>,
+    interval: usize,
+    error: Option<KosmossError>,
+}
+
+impl<'s, 'a, T: SpacecraftProperties> States<'s, 'a, T> {
+    /// The error that ended the iteration, if any
+    pub fn error(&self) -> Option<&KosmossError> {
+        self.error.as_ref()
+    }
+}
+
+impl<'s, 'a, T: SpacecraftProperties> Iterator for States<'s, 'a, T> {
+    type Item = State<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() || self.simulation.is_stopped() {
+            return None;
+        }
+        for _ in 0..self.interval {
+            if let Err(error) = self.simulation.step() {
+                self.error = Some(error);
+                return None;
+            }
+            if self.simulation.is_stopped() {
+                break;
+            }
+        }
+        Some(self.simulation.state().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use crate::constants::WGS84_A;
+    use crate::coordinates::coordinate_transformation::EOPData;
+    use crate::coordinates::eop_errors::EOPErrors;
+    use crate::coordinates::eop_provider::StaticEOP;
+    use crate::gnc::guidance::hohmann::{ApsisTargeting, ApsisType};
+    use crate::gnc::guidance::impulsive::{ImpulsiveBurn, ManeuverFrame};
+    use crate::gnc::guidance::station_keeping::StationKeeping;
+    use crate::gnc::guidance::thrust_ramp::RampShape;
+    use crate::telemetry::writer::TelemetryOptions;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Collects telemetry output in memory so a test can read it back
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn circular_simulation(spacecraft: &SimpleSat, dt: f64) -> Simulation<'_, SimpleSat> {
+        let guidance = ApsisTargeting::new(WGS84_A + 800_000.0, ApsisType::Apogee, 0.0);
+        circular_simulation_with(spacecraft, dt, guidance)
+    }
+
+    fn circular_simulation_with(
+        spacecraft: &SimpleSat,
+        dt: f64,
+        guidance: impl Into<Guidance>,
+    ) -> Simulation<'_, SimpleSat> {
+        let (position, velocity) = OrbitalMechanics::circular_orbit_state(400_000.0, 0.0, 0.0, 0.0);
+        let initial_state = State::new(
+            spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.05, 0.02, 0.01),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let controller = GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+        Simulation::new(initial_state, controller, guidance, dt)
+    }
+
+    #[test]
+    fn test_states_iterator_matches_stepping() {
+        let spacecraft = SimpleSat::default();
+        let mut manual = circular_simulation(&spacecraft, 1.0);
+        let mut streamed = circular_simulation(&spacecraft, 1.0);
+
+        let expected: Vec<State<SimpleSat>> =
+            (0..9).map(|_| manual.step().unwrap().clone()).collect();
+
+        let mut states = streamed.states(1);
+        let first: Vec<State<SimpleSat>> = states.by_ref().take(3).collect();
+        assert!(states.error().is_none());
+        drop(states);
+
+        // Sampling every third step continues from where the first stream left off
+        let sampled: Vec<State<SimpleSat>> = streamed.states(3).take(2).collect();
+
+        let streamed_states = first.iter().chain(&sampled);
+        let expected_states = expected[..3].iter().chain([&expected[5], &expected[8]]);
+        for (state, expected) in streamed_states.zip(expected_states) {
+            assert_eq!(state.mission_elapsed_time, expected.mission_elapsed_time);
+            assert_eq!(state.position, expected.position);
+            assert_eq!(state.velocity, expected.velocity);
+            assert_eq!(state.quaternion.data, expected.quaternion.data);
+            assert_eq!(state.angular_velocity, expected.angular_velocity);
+        }
+        assert_eq!(streamed.step_count(), 9);
+
+        // Lazy adaptors stop the simulation where the condition fails
+        let mut streamed = circular_simulation(&spacecraft, 1.0);
+        let early = streamed
+            .states(1)
+            .take_while(|state| state.mission_elapsed_time < 4.5)
+            .count();
+        assert_eq!(early, 4);
+        assert_eq!(streamed.step_count(), 5);
+    }
+
+    #[test]
+    fn test_run_until_short_span() {
+        let spacecraft = SimpleSat::default();
+        let radius = WGS84_A + 400_000.0;
+        let start_time = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+
+        let initial_state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(radius, 0.0, 0.0),
+            na::Vector3::new(
+                0.0,
+                OrbitalMechanics::compute_circular_velocity(radius),
+                0.0,
+            ),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::new(0.05, 0.02, 0.01),
+            start_time,
+        );
+        let controller = GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+        let guidance = ApsisTargeting::new(WGS84_A + 800_000.0, ApsisType::Apogee, 0.0);
+
+        let mut simulation = Simulation::new(initial_state, controller, guidance, 0.1);
+        let final_state = simulation.run_until(60.0).unwrap();
+
+        // Time bookkeeping stays consistent with the number of steps taken
+        assert_relative_eq!(final_state.mission_elapsed_time, 60.0, epsilon = 1e-9);
+        assert_relative_eq!(
+            (final_state.epoch - start_time).to_seconds(),
+            60.0,
+            epsilon = 1e-6
+        );
+
+        // A minute of coasting keeps the orbit circular and moves the spacecraft along-track
+        assert_relative_eq!(
+            final_state.position.magnitude(),
+            radius,
+            max_relative = 1e-5
+        );
+        assert!(final_state.position.y > 0.0);
+
+        // The initial body rate exceeds the detumble threshold, so the FSM has left safe mode
+        assert_ne!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::SafeMode
+        );
+    }
+
+    #[test]
+    fn test_two_scheduled_maneuvers_are_flown_in_order() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 0.1);
+
+        // Queued out of order; each burns for 20 s under its own guidance
+        let burn = |time, target_altitude| {
+            Maneuver::at(time)
+                .with_guidance(ApsisTargeting::new(
+                    WGS84_A + target_altitude,
+                    ApsisType::Apogee,
+                    0.0,
+                ))
+                .with_duration(20.0)
+        };
+        simulation.add_maneuver(burn(250.0, 500_000.0));
+        simulation.add_maneuver(burn(150.0, 450_000.0));
+
+        // Each maneuver goes through prep (at least 5 s) and its 20 s burn, then back to
+        // nominal operation before the next one is commanded
+        let mut expect = |time: f64, state: SpacecraftState, pending: usize| {
+            simulation.run_until(time).unwrap();
+            assert_eq!(simulation.fsm().get_current_state(), state, "t={time}");
+            assert_eq!(simulation.maneuvers().pending(), pending, "t={time}");
+        };
+        expect(140.0, SpacecraftState::NominalOperation, 2);
+        expect(152.0, SpacecraftState::ManeuverPrep, 1);
+        expect(160.0, SpacecraftState::Maneuvering, 1);
+        expect(180.0, SpacecraftState::NominalOperation, 1);
+        expect(252.0, SpacecraftState::ManeuverPrep, 0);
+        expect(260.0, SpacecraftState::Maneuvering, 0);
+        expect(280.0, SpacecraftState::NominalOperation, 0);
+
+        let commanded = simulation.maneuvers().commanded_times();
+        assert_eq!(commanded.len(), 2);
+        assert_relative_eq!(commanded[0], 150.0, epsilon = 0.11);
+        assert_relative_eq!(commanded[1], 250.0, epsilon = 0.11);
+        assert!(simulation.maneuvers().active().is_none());
+
+        // The first burn flew its own guidance from the circular orbit's "perigee". The
+        // second finds the spacecraft off perigee and does not thrust.
+        let state = simulation.state();
+        let (apogee, perigee) =
+            OrbitalMechanics::compute_apsis_altitudes(&state.position, &state.velocity);
+        assert_relative_eq!(apogee, 450_000.0, epsilon = 1_000.0);
+        assert_relative_eq!(perigee, 400_000.0, epsilon = 1_000.0);
+    }
+
+    #[test]
+    fn test_conservation_report_for_coasting_orbit() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 10.0);
+        simulation.run_until(5600.0).unwrap();
+
+        // No maneuver is scheduled, so the orbit coasts for about one revolution
+        let report = simulation.conservation_report();
+        assert!(report.relative_energy_change().abs() < 1e-9);
+        assert!(report.relative_angular_momentum_change() < 1e-9);
+        assert!(report.relative_semi_major_axis_change().abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_divergence_stops_the_run() {
+        let spacecraft = SimpleSat::default();
+
+        // A body rate large enough that the gyroscopic term overflows
+        let runaway = |spacecraft| {
+            let mut simulation = circular_simulation(spacecraft, 1.0);
+            simulation.state.angular_velocity = na::Vector3::new(1e200, -2e200, 3e200);
+            simulation
+        };
+
+        let mut simulation = runaway(&spacecraft);
+        match simulation.run_until(10.0) {
+            Err(KosmossError::NumericalDivergence { time }) => assert_eq!(time, 1.0),
+            other => panic!("expected a divergence, got {:?}", other.cloned()),
+        }
+
+        // Clamping the rate keeps the run going
+        let mut simulation = runaway(&spacecraft);
+        simulation.set_max_angular_rate(1.0).unwrap();
+        let state = simulation.run_until(10.0).unwrap();
+        assert!(state.is_finite());
+    }
+
+    #[test]
+    fn test_max_angular_rate_clamps_magnitude_and_keeps_direction() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 0.01);
+        let spin = na::Vector3::new(3.0, -2.0, 1.0);
+        simulation.state.angular_velocity = spin;
+
+        for limit in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                simulation.set_max_angular_rate(limit),
+                Err(KosmossError::InvalidConfig(_))
+            ));
+        }
+        let limit = 0.1;
+        simulation.set_max_angular_rate(limit).unwrap();
+
+        // Clamped to the limit at the start of the step, the controller and gyroscopic
+        // torques then barely move the rate over 10 ms
+        let rate = simulation.step().unwrap().angular_velocity;
+        assert!(rate.magnitude() <= limit, "{rate}");
+        assert_relative_eq!(rate.magnitude(), limit, max_relative = 1e-2);
+        assert!(rate.angle(&spin) < 1e-4, "{rate}");
+    }
+
+    #[test]
+    fn test_maneuver_completes_when_target_reached() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 0.1);
+        simulation.schedule_maneuver(150.0);
+
+        // No duration is set, so only reaching the 800 km apogee ends the burn
+        simulation.run_until(160.0).unwrap();
+        assert_eq!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::Maneuvering
+        );
+
+        simulation.run_until(300.0).unwrap();
+        assert_eq!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::NominalOperation
+        );
+        assert!(simulation.maneuvers().active().is_none());
+
+        let apogee = |simulation: &Simulation<SimpleSat>| {
+            let state = simulation.state();
+            OrbitalMechanics::compute_apsis_altitudes(&state.position, &state.velocity).0
+        };
+        let reached = apogee(&simulation);
+        assert_relative_eq!(reached, 800_000.0, epsilon = 100.0);
+
+        // With the maneuver over the thrust is off, and the orbit no longer changes
+        simulation.run_until(600.0).unwrap();
+        assert_relative_eq!(apogee(&simulation), reached, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_station_keeping_guidance_commands_its_own_burn() {
+        let spacecraft = SimpleSat::default();
+        let (position, velocity) = OrbitalMechanics::circular_orbit_state(400_000.0, 0.0, 0.0, 0.0);
+        let target = OrbitalMechanics::mean_semi_major_axis(&position, &velocity) + 500.0;
+        let guidance = StationKeeping::new(target, 200.0, 1.0);
+        let mut simulation = circular_simulation_with(&spacecraft, 0.1, guidance);
+
+        // Already below the deadband, so a maneuver is asked for without one being scheduled
+        simulation.run_until(300.0).unwrap();
+        assert_eq!(simulation.maneuvers().commanded_times().len(), 1);
+        assert!(simulation.maneuvers().active().is_none());
+        assert_eq!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::NominalOperation
+        );
+
+        // The burn ended once the mean semi-major axis was back up to the target
+        let state = simulation.state();
+        let mean_semi_major_axis =
+            OrbitalMechanics::mean_semi_major_axis(&state.position, &state.velocity);
+        assert!(
+            mean_semi_major_axis >= target - 1.0 && mean_semi_major_axis < target + 20.0,
+            "mean semi-major axis {} m from target",
+            mean_semi_major_axis - target
+        );
+    }
+
+    #[test]
+    fn test_emergency_cuts_off_ramped_thrust() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 0.1);
+        simulation.set_thrust_ramp(ThrustRamp::new(10.0, RampShape::Smooth));
+        simulation.schedule_maneuver(150.0);
+        simulation.run_until(160.0).unwrap();
+        assert_eq!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::Maneuvering
+        );
+
+        // Spun up past the emergency rate mid-burn, the spacecraft coasts from the next step
+        simulation.state.angular_velocity = na::Vector3::new(1.0, 0.0, 0.0);
+        let before = simulation.state().clone();
+        simulation.step().unwrap();
+        assert_eq!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::Emergency
+        );
+        let coasting = SpacecraftDynamics::<SimpleSat>::new(Some(na::Vector3::zeros()), None);
+        let expected = RK4::new(coasting).integrate(&before, 0.1);
+        assert_eq!(simulation.state().position, expected.position);
+        assert_eq!(simulation.state().velocity, expected.velocity);
+    }
+
+    #[test]
+    fn test_impulsive_maneuver() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 0.1);
+        simulation.add_maneuver(Maneuver::at(150.0).with_impulse(ImpulsiveBurn::new(
+            na::Vector3::new(0.0, 0.0, 30.0),
+            ManeuverFrame::Rsw,
+        )));
+
+        let inclination = |simulation: &Simulation<SimpleSat>| {
+            let state = simulation.state();
+            OrbitalMechanics::cartesian_to_keplerian(&state.position, &state.velocity)[2]
+        };
+        let before = inclination(&simulation);
+        simulation.run_until(300.0).unwrap();
+
+        // The burn is applied once and ends the maneuver straight away, without thrusting
+        // towards the guidance's apogee target
+        assert_eq!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::NominalOperation
+        );
+        assert!(simulation.maneuvers().active().is_none());
+        let state = simulation.state();
+        let (apogee, _) =
+            OrbitalMechanics::compute_apsis_altitudes(&state.position, &state.velocity);
+        assert!(apogee < 450_000.0);
+        let v = OrbitalMechanics::compute_circular_velocity(WGS84_A + 400_000.0);
+        assert_relative_eq!(
+            (inclination(&simulation) - before).abs(),
+            (30.0 / v).atan(),
+            max_relative = 1e-3
+        );
+    }
+
+    #[test]
+    fn test_eop_age_warning() {
+        /// Static EOP last updated `age` ago
+        struct AgedEOP {
+            age: Duration,
+        }
+
+        impl EOPProvider for AgedEOP {
+            fn eop_data(&self, _epoch: Epoch) -> Result<EOPData, EOPErrors> {
+                Ok(EOPData::default())
+            }
+
+            fn data_age(&self) -> Option<Duration> {
+                Some(self.age)
+            }
+        }
+
+        let spacecraft = SimpleSat::default();
+        let run = |age, with_telemetry| {
+            let mut simulation = circular_simulation(&spacecraft, 1.0);
+            simulation.set_eop_provider(Box::new(AgedEOP { age }));
+            simulation.set_eop_age_warning(Duration::days(7));
+            if with_telemetry {
+                let telemetry = TelemetryWriter::new(
+                    Box::new(SharedBuffer::default()) as Box<dyn Write>,
+                    TelemetryOptions::default(),
+                )
+                .unwrap();
+                simulation.set_telemetry(telemetry, 1);
+            }
+            simulation.run_until(5.0).unwrap();
+            simulation.is_eop_data_stale()
+        };
+
+        assert!(!run(Duration::hours(3), true));
+        assert!(run(Duration::days(30), true));
+
+        // The age is checked on runs without telemetry too
+        assert!(run(Duration::days(30), false));
+    }
+
+    #[test]
+    fn test_step_hook_stops_the_run() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 0.1);
+        simulation.schedule_maneuver(150.0);
+
+        // The burn raises the apogee, so the altitude soon climbs past 401 km
+        let steps = Rc::new(RefCell::new(0));
+        let counter = steps.clone();
+        simulation.set_step_hook(move |state, _mode| {
+            *counter.borrow_mut() += 1;
+            if OrbitalMechanics::current_altitude(&state.position) > 401_000.0 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+        // Stopping is not a failure: the run ends early, but successfully
+        let state = simulation.run_until(3000.0).unwrap().clone();
+        assert!(simulation.is_stopped());
+
+        // The run stops at the first step past the threshold, and the hook ran every step
+        assert_eq!(state.mission_elapsed_time, simulation.current_time());
+        assert_eq!(*steps.borrow(), simulation.step_count());
+        let altitude = OrbitalMechanics::current_altitude(&state.position);
+        assert!(altitude > 401_000.0 && altitude < 401_100.0, "{altitude} m");
+        assert!(simulation.current_time() < 3000.0);
+
+        // And it stays stopped
+        simulation.run_until(3000.0).unwrap();
+        assert_eq!(*steps.borrow(), simulation.step_count());
+        assert_eq!(simulation.states(1).count(), 0);
+    }
+
+    #[test]
+    fn test_fixed_cadence_telemetry_with_non_dividing_dt() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 0.7);
+        simulation.set_eop_provider(Box::new(StaticEOP::default()));
+
+        let buffer = SharedBuffer::default();
+        let telemetry = TelemetryWriter::new(
+            Box::new(buffer.clone()) as Box<dyn Write>,
+            TelemetryOptions::default(),
+        )
+        .unwrap();
+        simulation.set_telemetry_cadence(telemetry, 60.0).unwrap();
+        simulation.run_until(300.0).unwrap();
+        simulation.finish().unwrap();
+
+        let output = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        let times: Vec<f64> = output
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(1).unwrap().parse().unwrap())
+            .collect();
+
+        // 0.7 s does not divide 60 s, yet every sample lands exactly on the cadence
+        assert_eq!(times, vec![0.0, 60.0, 120.0, 180.0, 240.0, 300.0]);
+    }
+
+    #[test]
+    fn test_rejects_non_positive_telemetry_cadence() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 1.0);
+
+        for cadence in [0.0, -60.0, f64::NAN, f64::INFINITY] {
+            let telemetry = TelemetryWriter::new(
+                Box::new(SharedBuffer::default()) as Box<dyn Write>,
+                TelemetryOptions::default(),
+            )
+            .unwrap();
+            assert!(matches!(
+                simulation.set_telemetry_cadence(telemetry, cadence),
+                Err(KosmossError::InvalidConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_step_scaling() {
+        let spacecraft = SimpleSat::default();
+        let mut simulation = circular_simulation(&spacecraft, 1.0);
+        let valid = AltitudeStepScaling {
+            reference_altitude: 1_000_000.0,
+            min_dt: 1.0,
+            max_dt: 60.0,
+        };
+        assert!(simulation.set_step_scaling(valid).is_ok());
+
+        let invalid = [
+            AltitudeStepScaling {
+                min_dt: 60.0,
+                max_dt: 1.0,
+                ..valid
+            },
+            AltitudeStepScaling {
+                min_dt: 0.0,
+                ..valid
+            },
+            AltitudeStepScaling {
+                min_dt: f64::NAN,
+                ..valid
+            },
+            AltitudeStepScaling {
+                max_dt: f64::INFINITY,
+                ..valid
+            },
+            AltitudeStepScaling {
+                reference_altitude: 0.0,
+                ..valid
+            },
+            AltitudeStepScaling {
+                reference_altitude: f64::NAN,
+                ..valid
+            },
+        ];
+        for scaling in invalid {
+            assert!(
+                matches!(
+                    simulation.set_step_scaling(scaling),
+                    Err(KosmossError::InvalidConfig(_))
+                ),
+                "{scaling:?}"
+            );
+        }
+
+        // The rejected settings leave the valid one in place
+        let expected = (1.0 * OrbitalMechanics::current_altitude(&simulation.state().position)
+            / valid.reference_altitude)
+            .clamp(valid.min_dt, valid.max_dt);
+        assert_relative_eq!(simulation.next_dt(), expected);
+    }
+
+    #[test]
+    fn test_interpolated_sample_matches_integrated_state() {
+        let spacecraft = SimpleSat::default();
+        let mut coarse = circular_simulation(&spacecraft, 2.0);
+        let mut fine = circular_simulation(&spacecraft, 1.0);
+
+        let start = coarse.state().clone();
+        let end = coarse.step().unwrap().clone();
+        let midpoint = start.interpolate(&end, 0.5);
+        let reference = fine.step().unwrap();
+
+        assert_relative_eq!(midpoint.mission_elapsed_time, 1.0);
+        assert_relative_eq!(midpoint.position, reference.position, epsilon = 1e-3);
+        assert_relative_eq!(midpoint.velocity, reference.velocity, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_altitude_step_scaling_on_eccentric_orbit() {
+        let spacecraft = SimpleSat::default();
+        let rp = WGS84_A + 300_000.0;
+        let ra = WGS84_A + 5_000_000.0;
+        let elements = na::Vector6::new((ra + rp) / 2.0, (ra - rp) / (ra + rp), 0.5, 0.0, 0.0, 0.0);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+        let duration = OrbitalMechanics::compute_orbital_period(elements[0]);
+
+        let run = |dt: f64, scaling: Option<AltitudeStepScaling>| {
+            let initial_state = State::new(
+                &spacecraft,
+                SimpleSat::inertia_tensor(),
+                position,
+                velocity,
+                Quaternion::new(1.0, 0.0, 0.0, 0.0),
+                na::Vector3::zeros(),
+                Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+            );
+            let controller =
+                GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+            let guidance = ApsisTargeting::new(ra, ApsisType::Apogee, 0.0);
+            let mut simulation = Simulation::new(initial_state, controller, guidance, dt);
+            if let Some(scaling) = scaling {
+                simulation.set_step_scaling(scaling).unwrap();
+            }
+            let final_position = simulation.run_until(duration).unwrap().position;
+            assert_relative_eq!(simulation.current_time(), duration, epsilon = 1e-6);
+            (final_position, simulation.step_count())
+        };
+
+        let (reference, _) = run(1.0, None);
+        let (fixed, fixed_steps) = run(10.0, None);
+        let (scaled, scaled_steps) = run(
+            10.0,
+            Some(AltitudeStepScaling {
+                reference_altitude: 1_000_000.0,
+                min_dt: 10.0,
+                max_dt: 60.0,
+            }),
+        );
+
+        let fixed_error = (fixed - reference).magnitude();
+        let scaled_error = (scaled - reference).magnitude();
+
+        // Steps stay small through perigee and grow on the way to apogee
+        assert!(
+            scaled_steps * 2 < fixed_steps,
+            "{scaled_steps} vs {fixed_steps} steps"
+        );
+        assert!(
+            scaled_error < 1.0,
+            "scaled-step error {scaled_error} m (fixed {fixed_error} m)"
+        );
+    }
+
+    #[test]
+    fn test_safe_mode_points_axis_at_sun() {
+        let spacecraft = SimpleSat::default();
+        let radius = WGS84_A + 400_000.0;
+        let initial_state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(radius, 0.0, 0.0),
+            na::Vector3::new(
+                0.0,
+                OrbitalMechanics::compute_circular_velocity(radius),
+                0.0,
+            ),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        // Gentle, critically damped gains keep the slew below the detumbling threshold, so
+        // the FSM stays in safe mode throughout
+        let controller = GeometricAttitudeController::new(1e-4, 0.02, SimpleSat::inertia_tensor());
+        let guidance = ApsisTargeting::new(radius, ApsisType::Apogee, 0.0);
+        let mut simulation = Simulation::new(initial_state, controller, guidance, 1.0);
+        let panel_axis = na::Vector3::new(0.0, 1.0, 0.0);
+        simulation.set_sun_pointing_axis(panel_axis);
+
+        let pointing_error = |state: &State<SimpleSat>| {
+            let sun = sun_position(&state.epoch) - state.position;
+            (state.quaternion.normalize().to_rotation_matrix() * panel_axis).angle(&sun)
+        };
+        let initial_error = pointing_error(simulation.state());
+        assert!(initial_error > 0.5);
+
+        simulation.run_until(1500.0).unwrap();
+        assert_eq!(
+            simulation.fsm().get_current_state(),
+            SpacecraftState::SafeMode
+        );
+        let final_error = pointing_error(simulation.state());
+        assert!(final_error < 0.02, "pointing error {final_error} rad");
+    }
+
+    #[test]
+    fn test_decay_below_surface_is_reported() {
+        // Suborbital: 100 km up and far too slow to stay there
+        let spacecraft = SimpleSat::default();
+        let initial_state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(WGS84_A + 100_000.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 1_000.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let controller = GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+        let guidance = ApsisTargeting::new(WGS84_A + 800_000.0, ApsisType::Apogee, 0.0);
+        let mut simulation = Simulation::new(initial_state, controller, guidance, 1.0);
+
+        match simulation.run_until(5000.0) {
+            Err(KosmossError::OrbitDecayed { time, altitude }) => {
+                // Free fall from 100 km would take under 3 minutes, before drag slows the
+                // final descent to a few tens of m/s
+                assert!(time > 140.0 && time < 5000.0, "decayed at t={time}");
+                assert!(altitude < 0.0 && altitude > -2_000.0, "altitude {altitude}");
+            }
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("the spacecraft should not survive to t=5000 s"),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::errors::KosmossError;
+use crate::numerics::quaternion::Quaternion;
+use std::io::{self, Read, Write};
+
+const QUATERNION_COLUMNS: [&str; 4] = [
+    "Quaternion W",
+    "Quaternion X",
+    "Quaternion Y",
+    "Quaternion Z",
+];
+
+/// Removes the q/-q sign ambiguity from a sampled attitude history: each sample is negated
+/// when it lies in the opposite hemisphere to the one before it, so the series describes the
+/// same rotations without jumps in any component. The first sample keeps its sign.
+pub fn make_quaternions_continuous(series: &mut [Quaternion]) {
+    for i in 1..series.len() {
+        if series[i].data.dot(&series[i - 1].data) < 0.0 {
+            series[i].data = -series[i].data;
+        }
+    }
+}
+
+/// Copies telemetry CSV from `input` to `output` with the quaternion columns made
+/// continuous, leaving every other column as written
+pub fn make_telemetry_quaternions_continuous<R: Read, W: Write>(
+    input: R,
+    output: W,
+) -> Result<(), KosmossError> {
+    let mut reader = csv::Reader::from_reader(input);
+    let mut writer = csv::Writer::from_writer(output);
+
+    let headers = reader.headers()?.clone();
+    let mut columns = [0; 4];
+    for (column, name) in columns.iter_mut().zip(QUATERNION_COLUMNS) {
+        *column = headers.iter().position(|h| h == name).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("no '{}' column", name))
+        })?;
+    }
+    writer.write_record(&headers)?;
+
+    let mut records = Vec::new();
+    let mut quaternions = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let mut components = [0.0; 4];
+        for (component, &column) in components.iter_mut().zip(&columns) {
+            *component = record[column].parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid quaternion component '{}'", &record[column]),
+                )
+            })?;
+        }
+        quaternions.push(Quaternion::new(
+            components[0],
+            components[1],
+            components[2],
+            components[3],
+        ));
+        records.push(record);
+    }
+
+    make_quaternions_continuous(&mut quaternions);
+
+    for (record, quaternion) in records.iter().zip(&quaternions) {
+        let mut fields: Vec<String> = record.iter().map(str::to_string).collect();
+        for (k, &column) in columns.iter().enumerate() {
+            fields[column] = quaternion.data[k].to_string();
+        }
+        writer.write_record(&fields)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use nalgebra as na;
+
+    #[test]
+    fn test_sign_flip_is_removed() {
+        // A steady slew about z, with every third sample logged as -q
+        let original: Vec<Quaternion> = (0..10)
+            .map(|k| {
+                let half_angle = 0.1 * k as f64;
+                let q = Quaternion::new(half_angle.cos(), 0.0, 0.0, half_angle.sin());
+                if k % 3 == 2 {
+                    Quaternion { data: -q.data }
+                } else {
+                    q
+                }
+            })
+            .collect();
+
+        let mut series = original.clone();
+        make_quaternions_continuous(&mut series);
+
+        for (fixed, logged) in series.iter().zip(&original) {
+            assert_relative_eq!(
+                fixed.to_rotation_matrix(),
+                logged.to_rotation_matrix(),
+                epsilon = 1e-15
+            );
+            assert_relative_eq!(fixed.scalar(), logged.scalar().abs());
+        }
+        for pair in series.windows(2) {
+            assert!(pair[0].data.dot(&pair[1].data) > 0.0);
+        }
+
+        // The same series through logged CSV, with another column left untouched
+        let mut csv =
+            String::from("Time (s),Quaternion W,Quaternion X,Quaternion Y,Quaternion Z\n");
+        for (k, q) in original.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                k, q.data[0], q.data[1], q.data[2], q.data[3]
+            ));
+        }
+        let mut output = Vec::new();
+        make_telemetry_quaternions_continuous(csv.as_bytes(), &mut output).unwrap();
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        for (k, record) in reader.records().enumerate() {
+            let record = record.unwrap();
+            let parsed: Vec<f64> = record.iter().map(|x| x.parse().unwrap()).collect();
+            assert_eq!(parsed[0], k as f64);
+            assert_eq!(na::Vector4::from_row_slice(&parsed[1..]), series[k].data);
+        }
+    }
+}
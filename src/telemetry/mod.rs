@@ -0,0 +1,2 @@
+pub mod continuity;
+pub mod writer;
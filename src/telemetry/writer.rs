@@ -0,0 +1,705 @@
+use crate::analysis::beta_angle::beta_angle;
+use crate::coordinates::coordinate_transformation::{gcrs_to_itrs, itrs_to_geodetic, EOPData};
+use crate::coordinates::eop_provider::{EOPProvider, ManagedEOP};
+use crate::errors::KosmossError;
+use crate::fsm::state_machine::SpacecraftFSM;
+use crate::gnc::control::attitude_controller::pointing_error_angle;
+use crate::gnc::control::reaction_wheels::ReactionWheelArray;
+use crate::models::spacecraft::SpacecraftProperties;
+use crate::models::State;
+use crate::numerics::quaternion::Quaternion;
+use crate::physics::ephemeris::sun_position;
+use crate::physics::orbital::OrbitalMechanics;
+use csv::Writer;
+use nalgebra as na;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+const HEADER: [&str; 28] = [
+    "UTC Time",
+    "Time (s)",
+    "GCRS Position X (km)",
+    "GCRS Position Y (km)",
+    "GCRS Position Z (km)",
+    "GCRS Velocity X (km/s)",
+    "GCRS Velocity Y (km/s)",
+    "GCRS Velocity Z (km/s)",
+    "Longitude (deg)",
+    "Latitude (deg)",
+    "Altitude (km)",
+    "Quaternion W",
+    "Quaternion X",
+    "Quaternion Y",
+    "Quaternion Z",
+    "Angular Velocity X (rad/s)",
+    "Angular Velocity Y (rad/s)",
+    "Angular Velocity Z (rad/s)",
+    "Energy Error",
+    "Angular Momentum Error",
+    "Control Torque X (N⋅m)",
+    "Control Torque Y (N⋅m)",
+    "Control Torque Z (N⋅m)",
+    "Thrust X (N)",
+    "Thrust Y (N)",
+    "Thrust Z (N)",
+    "Current State",
+    "Time Since State Change (s)",
+];
+
+const ELEMENTS_HEADER: [&str; 6] = [
+    "Semi-major Axis (km)",
+    "Eccentricity",
+    "Inclination (deg)",
+    "RAAN (deg)",
+    "Argument of Periapsis (deg)",
+    "True Anomaly (deg)",
+];
+
+const ITRS_HEADER: [&str; 3] = [
+    "ITRS Position X (km)",
+    "ITRS Position Y (km)",
+    "ITRS Position Z (km)",
+];
+
+const BETA_ANGLE_HEADER: &str = "Beta Angle (deg)";
+
+const POINTING_ERROR_HEADER: &str = "Pointing Error (deg)";
+
+const WHEEL_MOMENTUM_HEADER: &str = "Wheel Momentum (N⋅m⋅s)";
+
+fn wheel_header(wheel: usize) -> [String; 3] {
+    [
+        format!("Wheel {} Speed (rad/s)", wheel),
+        format!("Wheel {} Commanded Torque (N⋅m)", wheel),
+        format!("Wheel {} Torque (N⋅m)", wheel),
+    ]
+}
+
+/// Units of the length and speed columns. Everything else is written in SI either way, and
+/// the simulation itself always works in SI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// Lengths in km and speeds in km/s
+    #[default]
+    Kilometres,
+    /// Lengths in m and speeds in m/s
+    Si,
+}
+
+impl UnitSystem {
+    /// Converts a length or speed from SI to the output units
+    pub fn convert(&self, value: f64) -> f64 {
+        match self {
+            UnitSystem::Kilometres => value / 1000.0,
+            UnitSystem::Si => value,
+        }
+    }
+
+    /// Column name with the unit label for these units
+    fn header(&self, column: &str) -> String {
+        match self {
+            UnitSystem::Kilometres => column.to_string(),
+            UnitSystem::Si => column.replace("(km)", "(m)").replace("(km/s)", "(m/s)"),
+        }
+    }
+}
+
+/// Optional column groups appended after the standard telemetry columns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TelemetryOptions {
+    /// Units of the position, velocity, altitude and semi-major axis columns
+    pub units: UnitSystem,
+    /// Earth-fixed (ITRS) position alongside the inertial (GCRS) state
+    pub itrs_position: bool,
+    /// Osculating Keplerian elements
+    pub osculating_elements: bool,
+    /// Elevation of the Sun above the orbit plane
+    pub beta_angle: bool,
+    /// Angle between the attitude and the controller's reference attitude
+    pub pointing_error: bool,
+    /// Number of reaction wheels to log speed and torque columns for, followed by the stored
+    /// momentum magnitude; zero leaves the wheel columns out
+    pub reaction_wheels: usize,
+}
+
+/// Writes simulation telemetry as CSV, one record per sample. Position and velocity are in
+/// GCRS, while the geodetic columns come from the ITRS position; the optional column groups
+/// in `TelemetryOptions` follow the standard columns, ITRS position first and the reaction
+/// wheels last.
+pub struct TelemetryWriter<W: Write> {
+    writer: Writer<W>,
+    options: TelemetryOptions,
+    wheels: Option<ReactionWheelArray>,
+    desired_attitude: Option<Quaternion>,
+}
+
+impl TelemetryWriter<Box<dyn Write>> {
+    /// Creates the output file (and any missing parent directories) and writes the header
+    pub fn create(path: &Path, options: TelemetryOptions) -> Result<Self, KosmossError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file: Box<dyn Write> = Box::new(File::create(path)?);
+        Ok(Self::new(file, options)?)
+    }
+}
+
+impl<W: Write> TelemetryWriter<W> {
+    pub fn new(inner: W, options: TelemetryOptions) -> Result<Self, csv::Error> {
+        let mut writer = Writer::from_writer(inner);
+
+        let units = options.units;
+        let mut header: Vec<String> = HEADER.iter().map(|h| units.header(h)).collect();
+        if options.itrs_position {
+            header.extend(ITRS_HEADER.map(|h| units.header(h)));
+        }
+        if options.osculating_elements {
+            header.extend(ELEMENTS_HEADER.map(|h| units.header(h)));
+        }
+        if options.beta_angle {
+            header.push(BETA_ANGLE_HEADER.to_string());
+        }
+        if options.pointing_error {
+            header.push(POINTING_ERROR_HEADER.to_string());
+        }
+        if options.reaction_wheels > 0 {
+            header.extend((1..=options.reaction_wheels).flat_map(wheel_header));
+            header.push(WHEEL_MOMENTUM_HEADER.to_string());
+        }
+        writer.write_record(header)?;
+
+        Ok(Self {
+            writer,
+            options,
+            wheels: None,
+            desired_attitude: None,
+        })
+    }
+
+    /// Records the wheel state logged with the following samples. Until the first update, and
+    /// for wheels beyond those in `wheels`, the wheel columns are left empty.
+    pub fn update_wheels(&mut self, wheels: &ReactionWheelArray) {
+        match &mut self.wheels {
+            Some(snapshot) => snapshot.clone_from(wheels),
+            None => self.wheels = Some(wheels.clone()),
+        }
+    }
+
+    /// Records the reference attitude the following samples' pointing error is measured
+    /// from. Until the first update the pointing error column is left empty.
+    pub fn update_desired_attitude(&mut self, desired_attitude: &Quaternion) {
+        self.desired_attitude = Some(desired_attitude.clone());
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_sample<T: SpacecraftProperties>(
+        &mut self,
+        state: &State<T>,
+        thrust: &na::Vector3<f64>,
+        control_torque: &na::Vector3<f64>,
+        fsm: &SpacecraftFSM,
+        energy_error: f64,
+        angular_momentum_error: f64,
+    ) -> Result<(), KosmossError> {
+        let eop = ManagedEOP::default().eop_data(state.epoch)?;
+        self.write_sample_with_eop(
+            state,
+            thrust,
+            control_torque,
+            fsm,
+            energy_error,
+            angular_momentum_error,
+            &eop,
+        )
+    }
+
+    /// Same as `write_sample`, but with the Earth orientation parameters supplied by the caller
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_sample_with_eop<T: SpacecraftProperties>(
+        &mut self,
+        state: &State<T>,
+        thrust: &na::Vector3<f64>,
+        control_torque: &na::Vector3<f64>,
+        fsm: &SpacecraftFSM,
+        energy_error: f64,
+        angular_momentum_error: f64,
+        eop: &EOPData,
+    ) -> Result<(), KosmossError> {
+        // Convert to geographic coordinates
+        let itrs_pos = gcrs_to_itrs(&state.position, &state.epoch, eop);
+        let (longitude, latitude, altitude) = itrs_to_geodetic(&itrs_pos);
+        let units = self.options.units;
+
+        let mut record = vec![
+            state.epoch.to_string(),
+            state.mission_elapsed_time.to_string(),
+            units.convert(state.position.x).to_string(),
+            units.convert(state.position.y).to_string(),
+            units.convert(state.position.z).to_string(),
+            units.convert(state.velocity.x).to_string(),
+            units.convert(state.velocity.y).to_string(),
+            units.convert(state.velocity.z).to_string(),
+            longitude.to_string(),
+            latitude.to_string(),
+            units.convert(altitude).to_string(),
+            state.quaternion.scalar().to_string(),
+            state.quaternion.vector()[0].to_string(),
+            state.quaternion.vector()[1].to_string(),
+            state.quaternion.vector()[2].to_string(),
+            state.angular_velocity[0].to_string(),
+            state.angular_velocity[1].to_string(),
+            state.angular_velocity[2].to_string(),
+            energy_error.to_string(),
+            angular_momentum_error.to_string(),
+            control_torque[0].to_string(),
+            control_torque[1].to_string(),
+            control_torque[2].to_string(),
+            thrust[0].to_string(),
+            thrust[1].to_string(),
+            thrust[2].to_string(),
+            fsm.get_current_state().to_string(),
+            (state.mission_elapsed_time - fsm.get_last_state_change()).to_string(),
+        ];
+
+        if self.options.itrs_position {
+            record.extend(itrs_pos.iter().map(|x| units.convert(*x).to_string()));
+        }
+
+        if self.options.osculating_elements {
+            let elements =
+                OrbitalMechanics::cartesian_to_keplerian(&state.position, &state.velocity);
+            record.push(units.convert(elements[0]).to_string());
+            record.push(elements[1].to_string());
+            record.extend((2..6).map(|i| elements[i].to_degrees().to_string()));
+        }
+
+        if self.options.beta_angle {
+            let beta = beta_angle(
+                &state.position,
+                &state.velocity,
+                &sun_position(&state.epoch),
+            );
+            record.push(beta.to_degrees().to_string());
+        }
+
+        if self.options.pointing_error {
+            record.push(
+                self.desired_attitude
+                    .as_ref()
+                    .map_or(String::new(), |desired| {
+                        pointing_error_angle(&state.quaternion, desired).to_string()
+                    }),
+            );
+        }
+
+        if self.options.reaction_wheels > 0 {
+            let wheels = self.wheels.as_ref().map_or(&[][..], |array| array.wheels());
+            for i in 0..self.options.reaction_wheels {
+                match wheels.get(i) {
+                    Some(wheel) => record.extend([
+                        wheel.speed().to_string(),
+                        wheel.commanded_torque().to_string(),
+                        wheel.torque().to_string(),
+                    ]),
+                    None => record.extend([String::new(), String::new(), String::new()]),
+                }
+            }
+            record.push(self.wheels.as_ref().map_or(String::new(), |array| {
+                array.momentum().magnitude().to_string()
+            }));
+        }
+
+        self.writer.write_record(&record)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::spacecraft::SimpleSat;
+    use approx::assert_relative_eq;
+    use hifitime::Epoch;
+
+    fn write_single_sample(options: TelemetryOptions, state: &State<SimpleSat>) -> String {
+        let mut buffer = Vec::new();
+        {
+            let mut telemetry = TelemetryWriter::new(&mut buffer, options).unwrap();
+            telemetry
+                .write_sample_with_eop(
+                    state,
+                    &na::Vector3::zeros(),
+                    &na::Vector3::zeros(),
+                    &SpacecraftFSM::new(),
+                    0.0,
+                    0.0,
+                    &EOPData::default(),
+                )
+                .unwrap();
+            telemetry.flush().unwrap();
+        }
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_osculating_element_columns() {
+        let spacecraft = SimpleSat::default();
+        let elements_in = na::Vector6::new(7_000_000.0, 0.01, 0.9, 1.2, 0.4, 2.1);
+        let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements_in);
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+
+        let options = TelemetryOptions {
+            osculating_elements: true,
+            ..Default::default()
+        };
+        let output = write_single_sample(options, &state);
+        let mut lines = output.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let record: Vec<f64> = lines.next().unwrap().split(',').collect::<Vec<_>>()[28..]
+            .iter()
+            .map(|field| field.parse().unwrap())
+            .collect();
+
+        assert_eq!(header.len(), 34);
+        assert_eq!(header[28..], ELEMENTS_HEADER);
+
+        let expected = OrbitalMechanics::cartesian_to_keplerian(&position, &velocity);
+        assert_relative_eq!(record[0], expected[0] / 1000.0, max_relative = 1e-12);
+        assert_relative_eq!(record[1], expected[1], max_relative = 1e-12);
+        for i in 2..6 {
+            assert_relative_eq!(record[i], expected[i].to_degrees(), max_relative = 1e-12);
+        }
+
+        // Without the toggle the record keeps the standard columns only
+        let output = write_single_sample(TelemetryOptions::default(), &state);
+        for line in output.lines() {
+            assert_eq!(line.split(',').count(), 28);
+        }
+    }
+
+    #[test]
+    fn test_unit_system() {
+        let spacecraft = SimpleSat::default();
+        let (position, velocity) = OrbitalMechanics::circular_orbit_state(500_000.0, 0.9, 1.2, 0.4);
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+
+        let columns = |units| {
+            let options = TelemetryOptions {
+                units,
+                itrs_position: true,
+                osculating_elements: true,
+                ..Default::default()
+            };
+            let output = write_single_sample(options, &state);
+            let mut lines = output.lines();
+            let header: Vec<String> = lines.next().unwrap().split(',').map(String::from).collect();
+            let fields: Vec<f64> = lines.next().unwrap().split(',').collect::<Vec<_>>()[2..8]
+                .iter()
+                .map(|field| field.parse().unwrap())
+                .collect();
+            (header, fields)
+        };
+
+        // SI writes the internal state unchanged
+        let (header, si) = columns(UnitSystem::Si);
+        assert_eq!(header[2], "GCRS Position X (m)");
+        assert_eq!(header[5], "GCRS Velocity X (m/s)");
+        assert_eq!(header[10], "Altitude (m)");
+        assert_eq!(header[28], "ITRS Position X (m)");
+        assert_eq!(header[31], "Semi-major Axis (m)");
+        assert_eq!(si[..3], position.as_slice()[..]);
+        assert_eq!(si[3..], velocity.as_slice()[..]);
+
+        // Kilometres divide lengths and speeds by 1000
+        let (header, km) = columns(UnitSystem::Kilometres);
+        assert_eq!(header[2], "GCRS Position X (km)");
+        assert_eq!(header[5], "GCRS Velocity X (km/s)");
+        for (km, si) in km.iter().zip(&si) {
+            assert_relative_eq!(*km, si / 1000.0, max_relative = 1e-15);
+        }
+    }
+
+    #[test]
+    fn test_itrs_position_columns() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let position = na::Vector3::new(7_000_000.0, 0.0, 0.0);
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            na::Vector3::new(0.0, 7_500.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            epoch,
+        );
+
+        let options = TelemetryOptions {
+            itrs_position: true,
+            osculating_elements: true,
+            ..Default::default()
+        };
+        let output = write_single_sample(options, &state);
+        let mut lines = output.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let fields: Vec<&str> = lines.next().unwrap().split(',').collect();
+
+        assert_eq!(header.len(), 37);
+        assert_eq!(
+            header[2..5],
+            [
+                "GCRS Position X (km)",
+                "GCRS Position Y (km)",
+                "GCRS Position Z (km)"
+            ]
+        );
+        assert_eq!(header[28..31], ITRS_HEADER);
+        assert_eq!(header[31..], ELEMENTS_HEADER);
+
+        let gcrs: Vec<f64> = fields[2..5].iter().map(|f| f.parse().unwrap()).collect();
+        let itrs: Vec<f64> = fields[28..31].iter().map(|f| f.parse().unwrap()).collect();
+        let itrs = na::Vector3::new(itrs[0], itrs[1], itrs[2]);
+        assert_eq!(gcrs, [7000.0, 0.0, 0.0]);
+
+        // Both frames share an origin, so the (approximately orthonormal) rotation preserves
+        // the radius
+        assert_relative_eq!(itrs.magnitude(), 7000.0, max_relative = 1e-9);
+    }
+
+    #[test]
+    fn test_geodetic_columns_match_ground_track() {
+        use crate::analysis::ground_track::ground_track;
+        use crate::coordinates::eop_provider::StaticEOP;
+
+        // The executable logs the sub-satellite point through the telemetry, while the
+        // ground track analysis converts states itself; both must land on the same point
+        let spacecraft = SimpleSat::default();
+        let (position, velocity) = OrbitalMechanics::circular_orbit_state(500_000.0, 1.2, 2.0, 0.7);
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+
+        let output = write_single_sample(TelemetryOptions::default(), &state);
+        let mut lines = output.lines();
+        let headers: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let record: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let fields: Vec<f64> = ["Longitude (deg)", "Latitude (deg)"]
+            .iter()
+            .map(|name| {
+                let column = headers.iter().position(|h| h == name).unwrap();
+                record[column].parse().unwrap()
+            })
+            .collect();
+
+        let track = ground_track(&[state], &StaticEOP::default()).unwrap();
+        assert_eq!(fields, [track[0].0, track[0].1]);
+    }
+
+    #[test]
+    fn test_beta_angle_column() {
+        let spacecraft = SimpleSat::default();
+        let epoch = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+        let (position, velocity) =
+            OrbitalMechanics::circular_orbit_state(500_000.0, 98_f64.to_radians(), 1.0, 0.3);
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            position,
+            velocity,
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            epoch,
+        );
+
+        let options = TelemetryOptions {
+            beta_angle: true,
+            ..Default::default()
+        };
+        let output = write_single_sample(options, &state);
+        let mut lines = output.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let fields: Vec<&str> = lines.next().unwrap().split(',').collect();
+
+        assert_eq!(header.len(), 29);
+        assert_eq!(header[28], BETA_ANGLE_HEADER);
+        let expected = beta_angle(&position, &velocity, &sun_position(&epoch)).to_degrees();
+        assert_relative_eq!(fields[28].parse::<f64>().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pointing_error_column() {
+        let spacecraft = SimpleSat::default();
+        let half = 15.0_f64.to_radians();
+        let state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(7_000_000.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_500.0, 0.0),
+            Quaternion::new(half.cos(), half.sin(), 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let options = TelemetryOptions {
+            pointing_error: true,
+            ..Default::default()
+        };
+
+        // Empty until a reference is given, then 30° off an identity reference
+        let mut buffer = Vec::new();
+        {
+            let mut telemetry = TelemetryWriter::new(&mut buffer, options).unwrap();
+            let (fsm, zero, eop) = (
+                SpacecraftFSM::new(),
+                na::Vector3::zeros(),
+                EOPData::default(),
+            );
+            telemetry
+                .write_sample_with_eop(&state, &zero, &zero, &fsm, 0.0, 0.0, &eop)
+                .unwrap();
+            telemetry.update_desired_attitude(&Quaternion::new(1.0, 0.0, 0.0, 0.0));
+            telemetry
+                .write_sample_with_eop(&state, &zero, &zero, &fsm, 0.0, 0.0, &eop)
+                .unwrap();
+            telemetry.flush().unwrap();
+        }
+
+        let output = String::from_utf8(buffer).unwrap();
+        let mut lines = output.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(header.len(), 29);
+        assert_eq!(header[28], POINTING_ERROR_HEADER);
+
+        let records: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert!(records[0][28].is_empty());
+        assert_relative_eq!(
+            records[1][28].parse::<f64>().unwrap(),
+            30.0,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_wheel_columns_track_a_slew() {
+        use crate::integrators::rk4::RK4;
+        use crate::physics::dynamics::SpacecraftDynamics;
+
+        let spacecraft = SimpleSat::default();
+        let mut state = State::new(
+            &spacecraft,
+            SimpleSat::inertia_tensor(),
+            na::Vector3::new(7_000_000.0, 0.0, 0.0),
+            na::Vector3::new(0.0, 7_500.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            na::Vector3::zeros(),
+            Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+        );
+        let mut wheels = ReactionWheelArray::orthogonal(0.01, 0.05, 600.0);
+        let options = TelemetryOptions {
+            reaction_wheels: 3,
+            ..Default::default()
+        };
+
+        // Spin up a slew about x and y, sampling before the first wheel command and after each
+        let commanded = na::Vector3::new(0.01, -0.005, 0.0);
+        let dt = 1.0;
+        let mut buffer = Vec::new();
+        {
+            let mut telemetry = TelemetryWriter::new(&mut buffer, options).unwrap();
+            let fsm = SpacecraftFSM::new();
+            let zero = na::Vector3::zeros();
+            telemetry
+                .write_sample_with_eop(&state, &zero, &zero, &fsm, 0.0, 0.0, &EOPData::default())
+                .unwrap();
+            for _ in 0..20 {
+                let delivered = wheels.apply(&commanded, dt);
+                state =
+                    RK4::new(SpacecraftDynamics::new(None, Some(delivered))).integrate(&state, dt);
+                telemetry.update_wheels(&wheels);
+                telemetry
+                    .write_sample_with_eop(
+                        &state,
+                        &zero,
+                        &delivered,
+                        &fsm,
+                        0.0,
+                        0.0,
+                        &EOPData::default(),
+                    )
+                    .unwrap();
+            }
+            telemetry.flush().unwrap();
+        }
+
+        let output = String::from_utf8(buffer).unwrap();
+        let mut lines = output.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(header.len(), 38);
+        assert_eq!(header[28], "Wheel 1 Speed (rad/s)");
+        assert_eq!(header[36], "Wheel 3 Torque (N⋅m)");
+        assert_eq!(header[37], WHEEL_MOMENTUM_HEADER);
+
+        let records: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(records.len(), 21);
+
+        // No wheel state has been recorded for the first sample
+        assert!(records[0][28..].iter().all(|field| field.is_empty()));
+
+        let column = |index: usize| -> Vec<f64> {
+            records[1..]
+                .iter()
+                .map(|record| record[index].parse().unwrap())
+                .collect()
+        };
+        let x_speed = column(28);
+        let y_speed = column(31);
+        let z_speed = column(34);
+        let momentum = column(37);
+
+        // The x and y wheels spin up against the slew, the z wheel stays idle
+        assert!(x_speed.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(y_speed.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!(z_speed.iter().all(|&speed| speed == 0.0));
+        assert!(momentum.windows(2).all(|pair| pair[1] > pair[0]));
+        assert_relative_eq!(x_speed[19], -20.0, max_relative = 1e-12);
+        assert_relative_eq!(
+            momentum[19],
+            20.0 * commanded.magnitude(),
+            max_relative = 1e-12
+        );
+
+        // Within the limits the delivered torque is the commanded one
+        assert_relative_eq!(column(29)[0], -0.01, max_relative = 1e-12);
+        assert_relative_eq!(column(30)[0], -0.01, max_relative = 1e-12);
+
+        // The body picks up the momentum the wheels gave away
+        assert!(state.angular_velocity.x > 0.0 && state.angular_velocity.y < 0.0);
+    }
+}
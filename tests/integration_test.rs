@@ -0,0 +1,296 @@
+use approx::assert_relative_eq;
+use hifitime::Epoch;
+use kosmoss::config::spacecraft::SimpleSat;
+use kosmoss::constants::*;
+use kosmoss::coordinates::coordinate_transformation::{gcrs_to_itrs, itrs_to_geodetic, EOPData};
+use kosmoss::coordinates::eop_provider::StaticEOP;
+use kosmoss::errors::KosmossError;
+use kosmoss::fsm::spacecraft_states::SpacecraftState;
+use kosmoss::gnc::control::attitude_controller::GeometricAttitudeController;
+use kosmoss::gnc::guidance::hohmann::{ApsisTargeting, ApsisType};
+use kosmoss::models::spacecraft::SpacecraftProperties;
+use kosmoss::models::State;
+use kosmoss::numerics::quaternion::Quaternion;
+use kosmoss::physics::energy::{calculate_angular_momentum, calculate_energy};
+use kosmoss::physics::environment::Atmosphere;
+use kosmoss::physics::orbital::OrbitalMechanics;
+use kosmoss::sim::{AltitudeStepScaling, Simulation};
+use kosmoss::telemetry::writer::{TelemetryOptions, TelemetryWriter};
+use nalgebra as na;
+
+/// Fixed Earth orientation so the ground track does not depend on downloaded EOP data
+fn static_eop() -> EOPData {
+    EOPData {
+        x_pole: 0.161556,
+        y_pole: 0.247219,
+        ut1_utc: -0.0890529,
+        lod: 0.0017,
+        ddpsi: -0.052,
+        ddeps: -0.003,
+    }
+}
+
+fn geodetic<T: SpacecraftProperties>(state: &State<T>) -> (f64, f64, f64) {
+    let itrs_pos = gcrs_to_itrs(&state.position, &state.epoch, &static_eop());
+    itrs_to_geodetic(&itrs_pos)
+}
+
+/// Steps for the full run, about a fifth as many as the fixed 0.01 s the scenario was first
+/// validated with. The step shrinks with altitude below 100 km, where drag and aerodynamic
+/// torque are strongest; the trajectory stays within a metre of the 0.01 s run (see
+/// `fine_step_position_at_3000s`). The attitude loop is the limit: at a fixed 0.05 s it
+/// tumbles into emergency mode shortly before reentry.
+const COARSE_DT: f64 = 0.1;
+const COARSE_STEP_SCALING: AltitudeStepScaling = AltitudeStepScaling {
+    reference_altitude: 100_000.0,
+    min_dt: 0.02,
+    max_dt: COARSE_DT,
+};
+
+/// GCRS position [m] at t = 3000 s in the full run, from the same scenario stepped at 0.01 s
+fn fine_step_position_at_3000s() -> na::Vector3<f64> {
+    na::Vector3::new(-6041417.558617531, 35743.167105886845, -2047724.6720580421)
+}
+
+/// Steps until `t_end` like `Simulation::run_until`, appending each FSM state entered to
+/// `visited`
+fn run_recording_fsm<'s, 'a>(
+    simulation: &'s mut Simulation<'a, SimpleSat>,
+    t_end: f64,
+    visited: &mut Vec<SpacecraftState>,
+) -> Result<&'s State<'a, SimpleSat>, KosmossError> {
+    while t_end - simulation.current_time() > 1e-6 * simulation.next_dt() {
+        let next = simulation.current_time() + simulation.next_dt();
+        simulation.run_until(next.min(t_end))?;
+        let state = simulation.fsm().get_current_state();
+        if visited.last() != Some(&state) {
+            visited.push(state);
+        }
+    }
+    Ok(simulation.state())
+}
+
+#[test]
+fn test_full_simulation() -> Result<(), KosmossError> {
+    let spacecraft = SimpleSat::default();
+    let perigee_alt = 50_000.0; // meters
+    let apogee_alt = 400_000.0; // meters
+    let ra = WGS84_A + apogee_alt;
+    let rp = WGS84_A + perigee_alt;
+    let a = (ra + rp) / 2.0;
+    let e = (ra - rp) / (ra + rp);
+
+    let elements = na::Vector6::new(
+        a,                     // semi-major axis
+        e,                     // eccentricity
+        89.0_f64.to_radians(), // inclination
+        PI * 1.0,              // RAAN
+        PI * 0.075,            // argument of periapsis
+        PI,                    // true anomaly
+    );
+
+    let (initial_position, initial_velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+    let start_time = Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0);
+
+    let initial_state = State::new(
+        &spacecraft,
+        SimpleSat::inertia_tensor(),
+        initial_position,
+        initial_velocity,
+        Quaternion::new(1.0, 0.0, 0.0, 0.0),
+        na::Vector3::new(0.05, 0.02, 0.01),
+        start_time,
+    );
+
+    let attitude_controller =
+        GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+    let hohmann_guidance = ApsisTargeting::new(WGS84_A + 450_000.0, ApsisType::Apogee, 0.0);
+
+    let mut simulation = Simulation::new(
+        initial_state,
+        attitude_controller,
+        hohmann_guidance,
+        COARSE_DT,
+    );
+    simulation.set_step_scaling(COARSE_STEP_SCALING)?;
+    simulation.schedule_maneuver(1000.0);
+    simulation.set_eop_provider(Box::new(StaticEOP::new(static_eop())));
+
+    let output_path = std::env::temp_dir().join(format!(
+        "kosmoss_integration_test_{}.csv",
+        std::process::id()
+    ));
+    simulation.set_telemetry_cadence(
+        TelemetryWriter::create(&output_path, TelemetryOptions::default())?,
+        6.0,
+    )?;
+
+    // Ground track before and after the maneuver is commanded
+    let expected = [
+        (600.0, -148.662957530, -52.059168025),
+        (1800.0, 23.941826816, -48.100338282),
+    ];
+    let mut visited = vec![simulation.fsm().get_current_state()];
+    for (time, expected_longitude, expected_latitude) in expected {
+        let state = run_recording_fsm(&mut simulation, time, &mut visited)?;
+        let (longitude, latitude, _) = geodetic(state);
+        assert_relative_eq!(longitude, expected_longitude, epsilon = 1e-3);
+        assert_relative_eq!(latitude, expected_latitude, epsilon = 1e-3);
+    }
+
+    // Shortly before reentry, where the coarse step is under the most strain
+    let state = run_recording_fsm(&mut simulation, 3000.0, &mut visited)?;
+    assert_relative_eq!(state.position, fine_step_position_at_3000s(), epsilon = 1.0);
+
+    // Drag brings the 50 km perigee down, and the spacecraft reaches the ground before 3200 s
+    match run_recording_fsm(&mut simulation, 3200.0, &mut visited) {
+        Err(KosmossError::OrbitDecayed { time, .. }) => {
+            assert!(time > 3100.0 && time < 3200.0, "decayed at t={time}");
+        }
+        other => panic!(
+            "expected the orbit to decay, got {:?}",
+            other.map(|s| s.epoch)
+        ),
+    }
+    simulation.finish()?;
+
+    // Detumbled out of safe mode, then held in the burn until the end: raising the apogee
+    // to 450 km takes a burn at perigee, which reentry comes before
+    assert_eq!(
+        visited,
+        [
+            SpacecraftState::SafeMode,
+            SpacecraftState::Detumbling,
+            SpacecraftState::NominalOperation,
+            SpacecraftState::ManeuverPrep,
+            SpacecraftState::Maneuvering,
+        ]
+    );
+
+    // Header plus one record per sampling interval at the least
+    let records = std::fs::read_to_string(&output_path)?.lines().count();
+    std::fs::remove_file(&output_path)?;
+    assert!(records > 3100 / 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_apogee_raising_maneuver_completes() -> Result<(), KosmossError> {
+    let spacecraft = SimpleSat::default();
+    let (rp, ra) = (WGS84_A + 400_000.0, WGS84_A + 600_000.0);
+
+    // About 400 s before perigee, where the apogee-raising burn belongs
+    let elements = na::Vector6::new(
+        (ra + rp) / 2.0,
+        (ra - rp) / (ra + rp),
+        51.6_f64.to_radians(),
+        0.3,
+        0.5,
+        -0.45,
+    );
+    let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+    let initial_state = State::new(
+        &spacecraft,
+        SimpleSat::inertia_tensor(),
+        position,
+        velocity,
+        Quaternion::new(1.0, 0.0, 0.0, 0.0),
+        na::Vector3::new(0.05, 0.02, 0.01),
+        Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+    );
+    let attitude_controller =
+        GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+    let guidance = ApsisTargeting::new(WGS84_A + 700_000.0, ApsisType::Apogee, 0.0);
+
+    let mut simulation = Simulation::new(initial_state, attitude_controller, guidance, 0.1);
+    simulation.schedule_maneuver(200.0);
+    simulation.set_eop_provider(Box::new(StaticEOP::new(static_eop())));
+
+    let apogee_altitude = |state: &State<SimpleSat>| {
+        OrbitalMechanics::compute_apsis_altitudes(&state.position, &state.velocity).0
+    };
+    let mut visited = vec![simulation.fsm().get_current_state()];
+    let state = run_recording_fsm(&mut simulation, 200.0, &mut visited)?;
+    assert_relative_eq!(apogee_altitude(state), 600_000.0, epsilon = 1_000.0);
+
+    // Held in the maneuver until perigee, then burned up to the target and back to nominal
+    let state = run_recording_fsm(&mut simulation, 600.0, &mut visited)?;
+    let reached = apogee_altitude(state);
+    assert_relative_eq!(reached, 700_000.0, epsilon = 100.0);
+    assert_eq!(
+        visited,
+        [
+            SpacecraftState::SafeMode,
+            SpacecraftState::Detumbling,
+            SpacecraftState::NominalOperation,
+            SpacecraftState::ManeuverPrep,
+            SpacecraftState::Maneuvering,
+            SpacecraftState::NominalOperation,
+        ]
+    );
+    assert!(simulation.maneuvers().active().is_none());
+
+    // With the maneuver over the thrust stays off
+    let state = simulation.run_until(1200.0)?;
+    assert_relative_eq!(apogee_altitude(state), reached, epsilon = 1.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_coasting_conserves_energy() -> Result<(), KosmossError> {
+    let spacecraft = SimpleSat::default();
+    let elements = na::Vector6::new(
+        WGS84_A + 700_000.0,
+        0.01,
+        51.6_f64.to_radians(),
+        0.3,
+        0.5,
+        0.0,
+    );
+    let (position, velocity) = OrbitalMechanics::keplerian_to_cartesian(&elements);
+    let initial_state = State::new(
+        &spacecraft,
+        SimpleSat::inertia_tensor(),
+        position,
+        velocity,
+        Quaternion::new(1.0, 0.0, 0.0, 0.0),
+        na::Vector3::new(0.05, 0.02, 0.01),
+        Epoch::from_gregorian_utc(2024, 3, 1, 23, 10, 0, 0),
+    );
+    let initial_energy = calculate_energy(&initial_state).unwrap();
+    let initial_momentum = calculate_angular_momentum(&initial_state);
+
+    // No maneuver and no drag, so only point-mass gravity acts on the orbit
+    let attitude_controller =
+        GeometricAttitudeController::new(1.0, 0.1, SimpleSat::inertia_tensor());
+    let guidance = ApsisTargeting::new(WGS84_A + 700_000.0, ApsisType::Apogee, 0.0);
+    let mut simulation = Simulation::new(initial_state, attitude_controller, guidance, 1.0);
+    simulation.set_atmosphere(Atmosphere::default().with_density_scale(0.0));
+    simulation.set_eop_provider(Box::new(StaticEOP::new(static_eop())));
+
+    // Three orbits, sampled once a minute
+    let period = OrbitalMechanics::compute_orbital_period(elements[0]);
+    while simulation.current_time() < 3.0 * period {
+        let state = simulation.run_until(simulation.current_time() + 60.0)?;
+
+        let energy = calculate_energy(state).unwrap();
+        let energy_error = ((energy - initial_energy) / initial_energy).abs();
+        assert!(
+            energy_error < 1e-6,
+            "relative energy error {energy_error:e} at t={}",
+            state.mission_elapsed_time
+        );
+
+        let momentum = calculate_angular_momentum(state);
+        let momentum_error =
+            (momentum - initial_momentum).magnitude() / initial_momentum.magnitude();
+        assert!(
+            momentum_error < 1e-6,
+            "angular momentum error {momentum_error:e}"
+        );
+    }
+
+    Ok(())
+}
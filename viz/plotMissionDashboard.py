@@ -95,9 +95,9 @@ df = pd.read_csv(data_file)
 # Validate data silently
 expected_columns = [
     "Time (s)",
-    "Position X (km)",
-    "Position Y (km)",
-    "Position Z (km)",
+    "GCRS Position X (km)",
+    "GCRS Position Y (km)",
+    "GCRS Position Z (km)",
     "Longitude (deg)",
     "Latitude (deg)",
     "Altitude (km)",
@@ -235,7 +235,7 @@ def create_dashboard(fig):
 
         # Create colored trajectory
         points = np.array(
-            [df["Position X (km)"], df["Position Y (km)"], df["Position Z (km)"]]
+            [df["GCRS Position X (km)"], df["GCRS Position Y (km)"], df["GCRS Position Z (km)"]]
         ).T.reshape(-1, 1, 3)
         segments = np.concatenate([points[:-1], points[1:]], axis=1)
 
@@ -263,9 +263,9 @@ def create_dashboard(fig):
 
         # Set equal aspect ratio and limits
         max_range = max(
-            df["Position X (km)"].abs().max(),
-            df["Position Y (km)"].abs().max(),
-            df["Position Z (km)"].abs().max(),
+            df["GCRS Position X (km)"].abs().max(),
+            df["GCRS Position Y (km)"].abs().max(),
+            df["GCRS Position Z (km)"].abs().max(),
         )
         max_range = max_range * 1.1  # Add 10% margin
         ax.set_xlim([-max_range, max_range])
@@ -436,9 +436,9 @@ def create_dashboard(fig):
         # Altitude vs Velocity plot
         ax = fig.add_subplot(gs[0, 0])
         velocity_magnitude = np.sqrt(
-            df["Velocity X (km/s)"] ** 2
-            + df["Velocity Y (km/s)"] ** 2
-            + df["Velocity Z (km/s)"] ** 2
+            df["GCRS Velocity X (km/s)"] ** 2
+            + df["GCRS Velocity Y (km/s)"] ** 2
+            + df["GCRS Velocity Z (km/s)"] ** 2
         )
 
         # Create scatter plot with time-based coloring
@@ -501,7 +501,7 @@ create_dashboard(interactive_fig)
 # Create separate interactive 3D plot window
 fig3d = plt.figure(figsize=(8, 8))
 ax3d = fig3d.add_subplot(111, projection="3d")
-ax3d.plot(df["Position X (km)"], df["Position Y (km)"], df["Position Z (km)"])
+ax3d.plot(df["GCRS Position X (km)"], df["GCRS Position Y (km)"], df["GCRS Position Z (km)"])
 ax3d.set_xlabel("X [km]")
 ax3d.set_ylabel("Y [km]")
 ax3d.set_zlabel("Z [km]")